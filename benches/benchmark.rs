@@ -1,17 +1,23 @@
 use criterion::{criterion_group, criterion_main};
 
+mod benchmark_batch_verify;
 mod benchmark_poseidon;
 mod benchmark_poseidon_top_level;
 mod benchmark_sha;
+mod benchmark_traversal;
 
+use benchmark_batch_verify::bench_function_batch_verify;
 use benchmark_poseidon::bench_function_poseidon;
 use benchmark_poseidon_top_level::bench_function_poseidon_top_level;
 use benchmark_sha::bench_function_sha;
+use benchmark_traversal::bench_function_traversal;
 
 criterion_group!(
     benches,
     bench_function_poseidon_top_level,
     bench_function_sha,
-    bench_function_poseidon
+    bench_function_poseidon,
+    bench_function_batch_verify,
+    bench_function_traversal
 );
 criterion_main!(benches);
@@ -0,0 +1,75 @@
+use std::hint::black_box;
+
+use criterion::Criterion;
+use rand::Rng;
+
+use hashsig::{
+    MESSAGE_LENGTH,
+    signature::{
+        SignatureScheme,
+        generalized_xmss::{
+            GeneralizedXMSSBatchItem,
+            instantiations_poseidon_top_level::lifetime_2_to_the_18::SIGTopLevelTargetSumLifetime18Dim64Base8,
+        },
+    },
+};
+
+/// Number of signatures in each batch
+const BATCH_SIZE: usize = 256;
+
+/// Number of keys that produced the signatures in each batch
+const NUM_KEYS: usize = 4;
+
+/// Benchmarks verifying a batch of signatures using `verify_batch`,
+/// compared to verifying them one after the other.
+pub fn bench_function_batch_verify(c: &mut Criterion) {
+    type S = SIGTopLevelTargetSumLifetime18Dim64Base8;
+
+    let mut group = c.benchmark_group(format!(
+        "Batch verification: Poseidon top level, lifetime 2^18, {BATCH_SIZE} signatures"
+    ));
+    group.sample_size(10);
+
+    let mut rng = rand::rng();
+
+    // keys are only active for the prepared interval, to keep key generation fast
+    let keys: Vec<_> = (0..NUM_KEYS)
+        .map(|_| S::key_gen(&mut rng, 0, 1 << 10))
+        .collect();
+    let messages: Vec<[u8; MESSAGE_LENGTH]> = (0..BATCH_SIZE).map(|_| rng.random()).collect();
+    let signatures: Vec<_> = (0..BATCH_SIZE)
+        .map(|i| {
+            let (_, sk) = &keys[i % NUM_KEYS];
+            let epoch = (i / NUM_KEYS) as u64;
+            S::sign(sk, epoch, &messages[i]).expect("Signing failed")
+        })
+        .collect();
+    let batch: Vec<GeneralizedXMSSBatchItem<'_, _, _>> = (0..BATCH_SIZE)
+        .map(|i| {
+            let (pk, _) = &keys[i % NUM_KEYS];
+            let epoch = (i / NUM_KEYS) as u64;
+            (pk, epoch, &messages[i], &signatures[i])
+        })
+        .collect();
+
+    group.bench_function("- naive loop", |b| {
+        b.iter(|| {
+            for (pk, epoch, message, signature) in &batch {
+                assert!(S::verify(
+                    black_box(pk),
+                    black_box(*epoch),
+                    black_box(message),
+                    black_box(signature)
+                ));
+            }
+        });
+    });
+
+    group.bench_function("- verify_batch", |b| {
+        b.iter(|| {
+            assert!(S::verify_batch(black_box(&batch)).is_ok());
+        });
+    });
+
+    group.finish();
+}
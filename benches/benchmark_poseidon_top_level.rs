@@ -60,8 +60,7 @@ pub fn benchmark_signature_scheme<S: SignatureScheme>(c: &mut Criterion, descrip
             let message = rng.random();
 
             // Sample random epoch within the prepared interval
-            let epoch =
-                rng.random_range(prepared_interval.start as u32..prepared_interval.end as u32);
+            let epoch = rng.random_range(prepared_interval.clone());
 
             // Benchmark signing
             let _ = S::sign(black_box(&sk), black_box(epoch), black_box(&message));
@@ -69,12 +68,11 @@ pub fn benchmark_signature_scheme<S: SignatureScheme>(c: &mut Criterion, descrip
     });
 
     // Pre-generate messages, epochs, and signatures for verification
-    let precomputed: Vec<(u32, [u8; MESSAGE_LENGTH], S::Signature)> = (0..2000)
+    let precomputed: Vec<(u64, [u8; MESSAGE_LENGTH], S::Signature)> = (0..2000)
         .map(|_| {
             let message = rng.random();
             // Use epochs within the prepared interval
-            let epoch =
-                rng.random_range(prepared_interval.start as u32..prepared_interval.end as u32);
+            let epoch = rng.random_range(prepared_interval.clone());
 
             let signature = S::sign(&sk, epoch, &message).expect("Signing should succeed");
             (epoch, message, signature)
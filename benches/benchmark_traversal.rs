@@ -0,0 +1,131 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use criterion::Criterion;
+use rand::Rng;
+use serde::{Serialize, de::DeserializeOwned};
+
+use hashsig::{
+    MESSAGE_LENGTH,
+    signature::{
+        SignatureScheme, SignatureSchemeSecretKey,
+        generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_18::{
+            SIGTopLevelTargetSumLifetime18Dim64Base8,
+            SIGTopLevelTargetSumLifetime18Dim64Base8Traversal,
+        },
+    },
+};
+
+/// Keys are active for 2^LOG_ACTIVATION_DURATION epochs, to keep key generation fast
+const LOG_ACTIVATION_DURATION: usize = 12;
+
+/// Secret key that walks through its activation interval, one epoch at a time.
+/// Once it reaches the end, it starts over with a copy of the original key.
+struct EpochWalker<S: SignatureScheme> {
+    original: Vec<u8>,
+    sk: S::SecretKey,
+    epoch: u64,
+}
+
+impl<S: SignatureScheme> EpochWalker<S>
+where
+    S::SecretKey: Serialize + DeserializeOwned,
+{
+    fn new(sk: &S::SecretKey) -> Self {
+        let original = bincode::serde::encode_to_vec(sk, bincode::config::standard())
+            .expect("Serializing the key should succeed");
+        let (sk, epoch) = Self::restore(&original);
+        Self {
+            original,
+            sk,
+            epoch,
+        }
+    }
+
+    fn restore(original: &[u8]) -> (S::SecretKey, u64) {
+        let (sk, _): (S::SecretKey, _) =
+            bincode::serde::decode_from_slice(original, bincode::config::standard())
+                .expect("Deserializing the key should succeed");
+        let epoch = sk.get_prepared_interval().start;
+        (sk, epoch)
+    }
+
+    /// Prepares the key for the next epoch and signs a message for it,
+    /// and returns the time this took.
+    fn step(&mut self, message: &[u8; MESSAGE_LENGTH]) -> Duration {
+        if !self.sk.get_activation_interval().contains(&self.epoch) {
+            (self.sk, self.epoch) = Self::restore(&self.original);
+        }
+        let start = Instant::now();
+        // for keys with a traversal, this advances the preparation by a single epoch,
+        // for the other keys, it only advances the preparation once in a while
+        self.sk
+            .prepare_for_epoch(self.epoch)
+            .expect("Preparing the key should succeed");
+        let signature = S::sign(black_box(&self.sk), black_box(self.epoch), message);
+        let elapsed = start.elapsed();
+        black_box(signature).expect("Signing should succeed");
+        self.epoch += 1;
+        elapsed
+    }
+}
+
+/// Walks through all epochs of a key, and prints the memory of the key,
+/// as well as the average and the worst-case latency of preparing and signing.
+fn summarize<S: SignatureScheme>(description: &str, sk: &S::SecretKey)
+where
+    S::SecretKey: Serialize + DeserializeOwned,
+{
+    let mut walker = EpochWalker::<S>::new(sk);
+    let message = [0x42; MESSAGE_LENGTH];
+    let mut max_memory_bytes = 0;
+    let (mut total, mut worst) = (Duration::ZERO, Duration::ZERO);
+    let num_epochs = 1 << LOG_ACTIVATION_DURATION;
+    for _ in 0..num_epochs {
+        max_memory_bytes = max_memory_bytes.max(walker.sk.tree_memory_bytes());
+        let elapsed = walker.step(&message);
+        total += elapsed;
+        worst = worst.max(elapsed);
+    }
+    println!(
+        "{description}: tree memory {max_memory_bytes} bytes, \
+         prepare + sign: average {:?}, worst case {worst:?}",
+        total / num_epochs
+    );
+}
+
+/// Benchmarks keys with a Merkle tree traversal, compared to keys storing sub-trees.
+///
+/// Besides the average time of preparing the key for the next epoch and signing, which
+/// is measured by criterion, it prints the memory of the keys and the worst-case latency.
+pub fn bench_function_traversal(c: &mut Criterion) {
+    type S = SIGTopLevelTargetSumLifetime18Dim64Base8;
+    type T = SIGTopLevelTargetSumLifetime18Dim64Base8Traversal;
+
+    let mut group = c.benchmark_group(format!(
+        "Traversal: Poseidon top level, lifetime 2^18, {} active epochs",
+        1 << LOG_ACTIVATION_DURATION
+    ));
+    group.sample_size(10);
+
+    let mut rng = rand::rng();
+    let (_, sk) = S::key_gen(&mut rng, 0, 1 << LOG_ACTIVATION_DURATION);
+    let (_, traversal_sk) = T::key_gen(&mut rng, 0, 1 << LOG_ACTIVATION_DURATION);
+
+    summarize::<S>("Sub-trees", &sk);
+    summarize::<T>("Traversal", &traversal_sk);
+
+    let message = rng.random();
+    let mut walker = EpochWalker::<S>::new(&sk);
+    group.bench_function("- prepare + sign, sub-trees", |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| walker.step(&message)).sum());
+    });
+    let mut walker = EpochWalker::<T>::new(&traversal_sk);
+    group.bench_function("- prepare + sign, traversal", |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| walker.step(&message)).sum());
+    });
+
+    group.finish();
+}
@@ -1,11 +1,15 @@
+use alloc::{vec, vec::Vec};
+use core::cmp::min;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
 use dashmap::DashMap;
+#[cfg(feature = "std")]
 use dashmap::mapref::one::Ref;
 use num_bigint::BigUint;
 use num_traits::One;
 use num_traits::ToPrimitive;
 use num_traits::Zero;
-use std::cmp::min;
-use std::ops::RangeInclusive;
+#[cfg(feature = "std")]
 use std::sync::LazyLock;
 
 /// Max dimension precomputed for layer sizes.
@@ -43,15 +47,30 @@ impl LayerInfo {
 type AllLayerInfoForBase = Vec<LayerInfo>;
 
 /// Global cache for layer info (sizes and prefix sums) for each base `w`.
+#[cfg(feature = "std")]
 static ALL_LAYER_INFO_OF_BASE: LazyLock<DashMap<usize, AllLayerInfoForBase>> =
     LazyLock::new(DashMap::new);
 
+/// Global cache for layer info without std. The layer info is never freed,
+/// so we can hand out references to it after releasing the lock.
+#[cfg(not(feature = "std"))]
+static ALL_LAYER_INFO_OF_BASE: spin::Mutex<
+    alloc::collections::BTreeMap<usize, &'static AllLayerInfoForBase>,
+> = spin::Mutex::new(alloc::collections::BTreeMap::new());
+
+/// Reference to the cached layer info for one base `w`.
+#[cfg(feature = "std")]
+type CachedLayerInfo<'a> = Ref<'a, usize, AllLayerInfoForBase>;
+#[cfg(not(feature = "std"))]
+type CachedLayerInfo<'a> = &'a AllLayerInfoForBase;
+
 /// Provides thread-safe, on-demand access to the cached layer data for a given base `w`.
 ///
 /// It ensures that the expensive computation to prepare layer info is only run once per `w`.
-struct AllLayerData<'a>(Ref<'a, usize, AllLayerInfoForBase>);
+struct AllLayerData<'a>(CachedLayerInfo<'a>);
 
 impl AllLayerData<'_> {
+    #[cfg(feature = "std")]
     fn new(w: usize) -> Self {
         // Atomically get or compute the layer info for the given base `w`.
         ALL_LAYER_INFO_OF_BASE
@@ -60,6 +79,14 @@ impl AllLayerData<'_> {
         Self(ALL_LAYER_INFO_OF_BASE.get(&w).unwrap())
     }
 
+    #[cfg(not(feature = "std"))]
+    fn new(w: usize) -> Self {
+        let mut cache = ALL_LAYER_INFO_OF_BASE.lock();
+        Self(cache.entry(w).or_insert_with(|| {
+            alloc::boxed::Box::leak(alloc::boxed::Box::new(prepare_layer_info(w)))
+        }))
+    }
+
     /// Gets the `LayerInfo` of dimension `v`.
     #[allow(dead_code)]
     fn layer_info_for_dimension(&self, v: usize) -> &LayerInfo {
@@ -173,7 +200,8 @@ pub fn map_to_vertex(w: usize, v: usize, d: usize, x: BigUint) -> Vec<u8> {
         d_curr -= w - 1 - ai;
     }
 
-    // layer_data no longer used beyond this point
+    // layer_data no longer used beyond this point (without std, there is no lock to release)
+    #[cfg(feature = "std")]
     drop(layer_data);
 
     let x_curr = x_curr.to_usize().unwrap();
@@ -245,6 +273,7 @@ pub fn hypercube_find_layer(w: usize, v: usize, x: BigUint) -> (usize, BigUint)
     let d = prefix_sums.partition_point(|p| p <= &x);
 
     // Drop layer_data early to release lock, since it's no longer needed
+    #[cfg(feature = "std")]
     drop(layer_data);
 
     if d == 0 {
@@ -1,6 +1,7 @@
+use alloc::vec::Vec;
+use core::fmt::Debug;
 use rand::Rng;
 use serde::{Serialize, de::DeserializeOwned};
-use std::fmt::Debug;
 
 use crate::MESSAGE_LENGTH;
 
@@ -43,9 +44,15 @@ pub trait IncomparableEncoding {
         parameter: &Self::Parameter,
         message: &[u8; MESSAGE_LENGTH],
         randomness: &Self::Randomness,
-        epoch: u32,
+        epoch: u64,
     ) -> Result<Vec<u8>, Self::Error>;
 
+    /// Appends bytes that identify this encoding and its parameters to `out`.
+    /// Distinct instantiations append distinct bytes, which are never a prefix of one
+    /// another (see `write_identifier`). They identify instantiations of signature
+    /// schemes, e.g., in the header of encoded keys and signatures.
+    fn write_identifier(out: &mut Vec<u8>);
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
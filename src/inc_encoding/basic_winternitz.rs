@@ -1,10 +1,15 @@
 use crate::{
     MESSAGE_LENGTH,
     symmetric::message_hash::{MessageHash, bytes_to_chunks},
+    write_identifier,
 };
+use alloc::vec::Vec;
 
 use super::IncomparableEncoding;
 
+/// Kind of this encoding in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x01;
+
 /// Incomparable Encoding Scheme based on the basic
 /// Winternitz scheme, implemented from a given message hash.
 ///
@@ -25,7 +30,7 @@ pub struct WinternitzEncoding<
     const CHUNK_SIZE: usize,
     const NUM_CHUNKS_CHECKSUM: usize,
 > {
-    _marker_mh: std::marker::PhantomData<MH>,
+    _marker_mh: core::marker::PhantomData<MH>,
 }
 
 impl<MH: MessageHash, const CHUNK_SIZE: usize, const NUM_CHUNKS_CHECKSUM: usize>
@@ -51,7 +56,7 @@ impl<MH: MessageHash, const CHUNK_SIZE: usize, const NUM_CHUNKS_CHECKSUM: usize>
         parameter: &Self::Parameter,
         message: &[u8; MESSAGE_LENGTH],
         randomness: &Self::Randomness,
-        epoch: u32,
+        epoch: u64,
     ) -> Result<Vec<u8>, Self::Error> {
         // apply the message hash to get chunks
         let mut chunks_message = MH::apply(parameter, epoch, randomness, message);
@@ -75,6 +80,11 @@ impl<MH: MessageHash, const CHUNK_SIZE: usize, const NUM_CHUNKS_CHECKSUM: usize>
         Ok(chunks_message)
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(out, IDENTIFIER_KIND, &[CHUNK_SIZE, NUM_CHUNKS_CHECKSUM]);
+        MH::write_identifier(out);
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         // chunk size must be 1, 2, 4, or 8
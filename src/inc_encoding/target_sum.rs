@@ -1,8 +1,12 @@
-use crate::{MESSAGE_LENGTH, symmetric::message_hash::MessageHash};
+use crate::{MESSAGE_LENGTH, symmetric::message_hash::MessageHash, write_identifier};
+use alloc::vec::Vec;
 
 use super::IncomparableEncoding;
 use thiserror::Error;
 
+/// Kind of this encoding in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x02;
+
 /// Specific errors that can occur during target sum encoding.
 #[derive(Debug, Error)]
 pub enum TargetSumError {
@@ -25,7 +29,7 @@ pub enum TargetSumError {
 ///     const EXPECTED_SUM: usize = MH::DIMENSION * MAX_CHUNK_VALUE / 2
 /// ```
 pub struct TargetSumEncoding<MH: MessageHash, const TARGET_SUM: usize> {
-    _marker_mh: std::marker::PhantomData<MH>,
+    _marker_mh: core::marker::PhantomData<MH>,
 }
 
 impl<MH: MessageHash, const TARGET_SUM: usize> IncomparableEncoding
@@ -54,7 +58,7 @@ impl<MH: MessageHash, const TARGET_SUM: usize> IncomparableEncoding
         parameter: &Self::Parameter,
         message: &[u8; MESSAGE_LENGTH],
         randomness: &Self::Randomness,
-        epoch: u32,
+        epoch: u64,
     ) -> Result<Vec<u8>, Self::Error> {
         // apply the message hash first to get chunks
         let chunks = MH::apply(parameter, epoch, randomness, message);
@@ -70,6 +74,11 @@ impl<MH: MessageHash, const TARGET_SUM: usize> IncomparableEncoding
         }
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(out, IDENTIFIER_KIND, &[TARGET_SUM]);
+        MH::write_identifier(out);
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         // base and dimension must not be too large
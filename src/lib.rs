@@ -1,6 +1,15 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
 use p3_koala_bear::{
     KoalaBear, Poseidon2KoalaBear, default_koalabear_poseidon2_16, default_koalabear_poseidon2_24,
 };
+#[cfg(not(feature = "std"))]
+use spin::Once;
+#[cfg(feature = "std")]
 use std::sync::OnceLock;
 
 /// Message length in bytes, for messages that we want to sign.
@@ -10,34 +19,94 @@ pub const TWEAK_SEPARATOR_FOR_MESSAGE_HASH: u8 = 0x02;
 pub const TWEAK_SEPARATOR_FOR_TREE_HASH: u8 = 0x01;
 pub const TWEAK_SEPARATOR_FOR_CHAIN_HASH: u8 = 0x00;
 
+/// Flag in the tweak separator of tweaks whose epoch or position is encoded with eight
+/// bytes, see `tweak_separator`.
+pub const TWEAK_SEPARATOR_WIDE_FLAG: u8 = 0x80;
+
+/// Returns the number of bytes with which an epoch, or a position in a layer of a Merkle
+/// tree, is encoded in the inputs of byte-oriented hash functions.
+///
+/// Values below 2^32 are encoded with four bytes, as when epochs were 32 bits wide, so
+/// that keys and signatures for these epochs do not change. Larger values are encoded
+/// with eight bytes, and tweaks containing them are marked in their separator, see
+/// `tweak_separator`. The PRFs do not have a separator, but their inputs have a fixed
+/// length apart from the epoch, so the length of the input determines its encoding.
+pub(crate) const fn epoch_encoding_len(value: u64) -> usize {
+    if value <= u32::MAX as u64 { 4 } else { 8 }
+}
+
+/// Returns the separator of a tweak with the given epoch or position, which is encoded
+/// with `epoch_encoding_len(value)` bytes.
+///
+/// If the value is encoded with eight bytes, `TWEAK_SEPARATOR_WIDE_FLAG` is set. So the
+/// separator determines the length of the tweak, and tweak encodings are prefix-free.
+pub(crate) const fn tweak_separator(separator: u8, value: u64) -> u8 {
+    if epoch_encoding_len(value) == 4 {
+        separator
+    } else {
+        separator | TWEAK_SEPARATOR_WIDE_FLAG
+    }
+}
+
+/// Appends the identifier of a hash function or an encoding to `out`, given by a kind,
+/// which distinguishes implementations of the same trait, and by its parameters.
+///
+/// Parameters are prefixed with their number and encoded as `u32`, so identifiers are
+/// never a prefix of one another. This allows to concatenate identifiers of components.
+pub(crate) fn write_identifier(out: &mut Vec<u8>, kind: u8, parameters: &[usize]) {
+    out.push(kind);
+    out.push(parameters.len() as u8);
+    for &parameter in parameters {
+        out.extend_from_slice(&(parameter as u32).to_le_bytes());
+    }
+}
+
 type F = KoalaBear;
 
 pub(crate) mod hypercube;
 pub(crate) mod inc_encoding;
+pub mod parallelism;
 pub mod signature;
 pub(crate) mod symmetric;
+#[cfg(test)]
+mod test_allocator;
 
 // Cached Poseidon2 permutations.
 //
-// We cache the default Plonky3 Poseidon2 instances once and return a clone.
-// Returning by value preserves existing call sites that take `&perm`.
+// We cache the default Plonky3 Poseidon2 instances once and return a reference.
+// This way, all hash evaluations (also across threads, e.g., when verifying many
+// signatures in parallel) share the same instances instead of cloning them.
+// Without std, we use a spinning `Once` instead of `OnceLock`.
+
+/// Lazily-initialized, thread-safe cache for a value that is computed once.
+#[cfg(feature = "std")]
+type Cache<T> = OnceLock<T>;
+#[cfg(not(feature = "std"))]
+type Cache<T> = Once<T>;
+
+/// Returns the value in the cache, initializing it with `init` on the first call.
+#[cfg(feature = "std")]
+fn get_cached<T>(cache: &'static Cache<T>, init: fn() -> T) -> &'static T {
+    cache.get_or_init(init)
+}
+
+#[cfg(not(feature = "std"))]
+fn get_cached<T>(cache: &'static Cache<T>, init: fn() -> T) -> &'static T {
+    cache.call_once(init)
+}
 
 /// A lazily-initialized, thread-safe cache for the Poseidon2 permutation with a width of 24.
-static POSEIDON2_24: OnceLock<Poseidon2KoalaBear<24>> = OnceLock::new();
+static POSEIDON2_24: Cache<Poseidon2KoalaBear<24>> = Cache::new();
 
 /// A lazily-initialized, thread-safe cache for the Poseidon2 permutation with a width of 16.
-static POSEIDON2_16: OnceLock<Poseidon2KoalaBear<16>> = OnceLock::new();
+static POSEIDON2_16: Cache<Poseidon2KoalaBear<16>> = Cache::new();
 
 /// Poseidon2 permutation (width 24)
-pub(crate) fn poseidon2_24() -> Poseidon2KoalaBear<24> {
-    POSEIDON2_24
-        .get_or_init(default_koalabear_poseidon2_24)
-        .clone()
+pub(crate) fn poseidon2_24() -> &'static Poseidon2KoalaBear<24> {
+    get_cached(&POSEIDON2_24, default_koalabear_poseidon2_24)
 }
 
 /// Poseidon2 permutation (width 16)
-pub(crate) fn poseidon2_16() -> Poseidon2KoalaBear<16> {
-    POSEIDON2_16
-        .get_or_init(default_koalabear_poseidon2_16)
-        .clone()
+pub(crate) fn poseidon2_16() -> &'static Poseidon2KoalaBear<16> {
+    get_cached(&POSEIDON2_16, default_koalabear_poseidon2_16)
 }
@@ -0,0 +1,200 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+#[cfg(feature = "rayon")]
+use std::sync::Arc;
+
+/// Determines where the computations of key generation, preparation, signing,
+/// and verification are executed.
+///
+/// A setting applies to all computations within `Parallelism::run`, on the calling thread
+/// and on the threads that the computation uses. Outside of `run`, computations use the
+/// default, which is the global rayon thread pool, or sequential execution if the crate
+/// is built without the `rayon` feature.
+///
+/// Example:
+/// ```ignore
+/// let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(4).build()?);
+/// let (pk, sk) = Parallelism::Pool(pool).run(|| Sig::key_gen(&mut rng, 0, 1 << 20));
+/// let sig = Parallelism::Sequential.run(|| Sig::sign(&sk, epoch, &message))?;
+/// ```
+///
+/// Without the `std` feature, all computations are sequential, and this setting does not exist.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub enum Parallelism {
+    /// Uses the global rayon thread pool.
+    #[cfg(feature = "rayon")]
+    #[default]
+    Global,
+    /// Uses the given rayon thread pool.
+    #[cfg(feature = "rayon")]
+    Pool(Arc<rayon::ThreadPool>),
+    /// Runs all computations sequentially on the calling thread.
+    #[cfg_attr(not(feature = "rayon"), default)]
+    Sequential,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    // The setting of the innermost `run` on this thread, if any.
+    static CURRENT: RefCell<Option<Parallelism>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous setting of this thread when dropped, also if `run` panics.
+#[cfg(feature = "std")]
+struct RestoreOnDrop(Option<Parallelism>);
+
+#[cfg(feature = "std")]
+impl Drop for RestoreOnDrop {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[cfg(feature = "std")]
+impl Parallelism {
+    /// Runs `f` with this setting, and returns its result.
+    pub fn run<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        // within the pool, parallel iterators use the pool automatically,
+        // and we only need the setting for `current`
+        #[cfg(feature = "rayon")]
+        if let Self::Pool(pool) = self {
+            return pool.install(|| self.run_on_this_thread(f));
+        }
+        self.run_on_this_thread(f)
+    }
+
+    /// Returns the setting of the innermost `run` on this thread, or the default.
+    /// This is used to hand the setting over to other threads, e.g., background workers.
+    #[must_use]
+    pub fn current() -> Self {
+        CURRENT.with(|current| current.borrow().clone().unwrap_or_default())
+    }
+
+    /// Helper function to run `f` with this setting on the calling thread.
+    fn run_on_this_thread<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.with(|current| current.borrow_mut().replace(self.clone()));
+        let _restore = RestoreOnDrop(previous);
+        f()
+    }
+}
+
+/// Returns `true` if computations on this thread must be sequential.
+#[cfg(feature = "rayon")]
+fn is_sequential() -> bool {
+    CURRENT.with(|current| matches!(*current.borrow(), Some(Parallelism::Sequential)))
+}
+
+/// Applies `f` to all indices in the range, in parallel unless the current setting
+/// is sequential, and returns the results in the order of the indices.
+pub(crate) fn map_range<R, F>(range: Range<usize>, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize) -> R + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    if !is_sequential() {
+        return range.into_par_iter().map(f).collect();
+    }
+    range.map(f).collect()
+}
+
+/// Returns whether `f` holds for all indices in the range. It is evaluated in parallel
+/// unless the current setting is sequential. Stops early if `f` does not hold.
+pub(crate) fn all_range<F>(range: Range<usize>, f: F) -> bool
+where
+    F: Fn(usize) -> bool + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    if !is_sequential() {
+        return range.into_par_iter().all(f);
+    }
+    range.into_iter().all(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn is_default(parallelism: &Parallelism) -> bool {
+        std::mem::discriminant(parallelism) == std::mem::discriminant(&Parallelism::default())
+    }
+
+    #[test]
+    fn test_run_sets_current() {
+        assert!(is_default(&Parallelism::current()));
+
+        Parallelism::Sequential.run(|| {
+            assert!(matches!(Parallelism::current(), Parallelism::Sequential));
+
+            // other threads are not affected
+            thread::spawn(|| assert!(is_default(&Parallelism::current())))
+                .join()
+                .unwrap();
+        });
+
+        // the previous setting is restored, also after a panic
+        let result = std::panic::catch_unwind(|| {
+            Parallelism::Sequential.run(|| panic!("test panic"));
+        });
+        assert!(result.is_err());
+        assert!(is_default(&Parallelism::current()));
+    }
+
+    #[test]
+    fn test_helpers() {
+        let settings = [
+            #[cfg(feature = "rayon")]
+            Parallelism::Global,
+            #[cfg(feature = "rayon")]
+            Parallelism::Pool(Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(2)
+                    .build()
+                    .unwrap(),
+            )),
+            Parallelism::Sequential,
+        ];
+        for parallelism in settings {
+            parallelism.run(|| {
+                assert_eq!(map_range(0..100, |i| i * i)[..4], [0, 1, 4, 9]);
+                assert!(all_range(0..100, |i| i < 100));
+                assert!(!all_range(0..100, |i| i != 50));
+            });
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_pool() {
+        // computations within `run` happen on the threads of the pool
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(2)
+                .thread_name(|i| format!("test-pool-{i}"))
+                .build()
+                .unwrap(),
+        );
+        let names = Parallelism::Pool(pool).run(|| {
+            assert!(matches!(Parallelism::current(), Parallelism::Pool(_)));
+            map_range(0..64, |_| thread::current().name().map(String::from))
+        });
+        assert!(names.iter().all(|name| {
+            name.as_deref()
+                .is_some_and(|name| name.starts_with("test-pool-"))
+        }));
+
+        // sequential computations happen on the calling thread
+        let caller = thread::current().id();
+        let ids = Parallelism::Sequential.run(|| map_range(0..64, |_| thread::current().id()));
+        assert!(ids.iter().all(|id| *id == caller));
+    }
+}
@@ -1,6 +1,8 @@
-use std::ops::Range;
+use alloc::vec::Vec;
+use core::{fmt, ops::Range};
 
 use crate::MESSAGE_LENGTH;
+use metrics::SecretKeyMetrics;
 use rand::Rng;
 use serde::{Serialize, de::DeserializeOwned};
 use thiserror::Error;
@@ -12,6 +14,127 @@ pub enum SigningError {
     /// after the maximum number of attempts.
     #[error("Failed to encode message after {attempts} attempts.")]
     EncodingAttemptsExceeded { attempts: usize },
+
+    /// Occurs when a list of messages to be signed together is empty.
+    #[error("Need at least one message to sign.")]
+    NoMessages,
+
+    /// Occurs when the epochs of a list of messages to be signed together
+    /// are not strictly increasing.
+    #[error(
+        "Epochs must be strictly increasing, but epoch {epoch} follows epoch {previous_epoch}."
+    )]
+    EpochsNotIncreasing { previous_epoch: u64, epoch: u64 },
+
+    /// Occurs when the epoch is not in the activation interval of the key.
+    #[error("Key is not active during epoch {epoch}.")]
+    EpochNotActive { epoch: u64 },
+
+    /// Occurs when the key is not prepared for the epoch, see `advance_preparation`.
+    #[error("Key is not prepared for epoch {epoch}.")]
+    EpochNotPrepared { epoch: u64 },
+}
+
+/// Error enum for the verification process.
+///
+/// Verification is applied to untrusted input, e.g., signatures received
+/// from the network. Malformed signatures are therefore never a reason to
+/// panic, and instead result in one of the following errors.
+#[derive(Debug, Error)]
+pub enum VerificationError {
+    /// Occurs when the epoch is not within the lifetime of the scheme.
+    #[error("Epoch {epoch} is outside of the lifetime {lifetime}.")]
+    EpochOutOfRange { epoch: u64, lifetime: u64 },
+
+    /// Occurs when the Merkle co-path does not have the length
+    /// determined by the lifetime of the scheme.
+    #[error("Merkle co-path has length {actual}, but expected {expected}.")]
+    InvalidPathLength { expected: usize, actual: usize },
+
+    /// Occurs when the number of chain hashes does not match the
+    /// dimension of the encoding.
+    #[error("Signature contains {actual} chain hashes, but expected {expected}.")]
+    InvalidNumberOfHashes { expected: usize, actual: usize },
+
+    /// Occurs when the message encoding fails for the randomness in the signature.
+    #[error("Message encoding rejected the randomness contained in the signature.")]
+    EncodingRejected,
+
+    /// Occurs when the root recomputed from the signature does not match the public key.
+    #[error("Recomputed Merkle root does not match the public key.")]
+    RootMismatch,
+}
+
+/// Error for the verification of a batch of signatures.
+///
+/// It contains all signatures in the batch that have been rejected,
+/// given by their index in the batch, and the reason for rejecting them.
+#[derive(Debug, Error)]
+pub struct BatchVerificationError {
+    /// Indices of the invalid signatures and the reasons for rejecting them, sorted by index.
+    /// This is never empty.
+    pub failures: Vec<(usize, VerificationError)>,
+}
+
+// The failures are never empty when returned by `verify_batch`, but as the field is
+// public, we do not rely on this when formatting.
+impl fmt::Display for BatchVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} signature(s) in the batch are invalid",
+            self.failures.len()
+        )?;
+        if let Some((index, error)) = self.failures.first() {
+            write!(f, ", the first one has index {index}: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl BatchVerificationError {
+    /// Returns the indices of all invalid signatures in the batch, sorted.
+    pub fn failed_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.failures.iter().map(|(index, _)| *index)
+    }
+}
+
+/// Error enum for aggregating signatures.
+#[derive(Debug, Error)]
+pub enum AggregationError {
+    /// Occurs when there are no signatures to aggregate.
+    #[error("Cannot aggregate an empty set of signatures.")]
+    NoSignatures,
+
+    /// Occurs when the epoch is not within the lifetime of the scheme.
+    #[error("Epoch {epoch} is outside of the lifetime {lifetime}.")]
+    EpochOutOfRange { epoch: u64, lifetime: u64 },
+
+    /// Occurs when a signature is malformed, so that it cannot be valid for any public key.
+    #[error("Signature with index {index} is malformed: {error}")]
+    MalformedSignature {
+        index: usize,
+        error: VerificationError,
+    },
+}
+
+/// Error enum for preparing a secret key for an epoch.
+#[derive(Debug, Error)]
+pub enum PreparationError {
+    /// Occurs when the epoch is not in the activation interval of the key.
+    #[error("Epoch {epoch} is outside of the activation interval {activation_interval:?}.")]
+    EpochNotActive {
+        epoch: u64,
+        activation_interval: Range<u64>,
+    },
+
+    /// Occurs when the epoch is before the prepared interval. Preparation only
+    /// moves forward, as bottom trees for past epochs are no longer needed.
+    #[error("Epoch {epoch} is behind the prepared interval {prepared_interval:?}.")]
+    EpochBehindPreparedInterval {
+        epoch: u64,
+        prepared_interval: Range<u64>,
+    },
 }
 
 /// Defines the interface for a synchronized signature scheme secret key.
@@ -30,6 +153,11 @@ pub enum SigningError {
 /// This trait provides an interface to manage this sliding window of prepared intervals.
 /// The `advance_preparation` method allows the user to proactively move this window to
 /// the right, i.e., change the prepared interval to the next one, if possible.
+///
+/// Note: keys with a Merkle tree traversal (see `GeneralizedXMSSTraversalSecretKey`) do not
+/// store bottom trees. Their prepared interval is a single epoch, and `advance_preparation`
+/// moves it to the next epoch. The guarantees for the prepared interval below refer to the
+/// keys with bottom trees.
 pub trait SignatureSchemeSecretKey {
     /// Returns the total interval of epochs for which this key is valid.
     ///
@@ -38,9 +166,12 @@ pub trait SignatureSchemeSecretKey {
     ///
     /// The interval is guaranteed to:
     /// - Be a superset of the lifetime specified during key generation.
-    /// - Start at a multiple of `sqrt(LIFETIME)`.
-    /// - Have a length that is a multiple of `sqrt(LIFETIME)`.
-    /// - Have a minimum length of `2 * sqrt(LIFETIME)`.
+    /// - Start at a multiple of `C`.
+    /// - Have a length that is a multiple of `C`.
+    /// - Have a minimum length of `2 * C`.
+    ///
+    /// Here, `C` is the number of leafs of a bottom tree. By default, this is
+    /// `2^floor(LOG_LIFETIME / 2)`, i.e., about `sqrt(LIFETIME)`.
     fn get_activation_interval(&self) -> Range<u64>;
 
     /// Returns the sub-interval for which the key is currently prepared to sign messages.
@@ -49,10 +180,14 @@ pub trait SignatureSchemeSecretKey {
     ///
     /// It is guaranteed to:
     /// - Be a sub-interval of the `activation_interval`.
-    /// - Start at a multiple of `sqrt(LIFETIME)`.
-    /// - Have a fixed length of exactly `2 * sqrt(LIFETIME)`.
+    /// - Start at a multiple of `C`.
+    /// - Have a fixed length of exactly `2 * C`.
     ///
     /// Note: it can be changed by calling `advance_preparation`.
+    ///
+    /// Note: for keys of the generalized XMSS scheme with a different tree layout (see
+    /// `key_gen_with_layout`), `C` is the number of leafs of their bottom trees, which
+    /// can differ from the default.
     fn get_prepared_interval(&self) -> Range<u64>;
 
     /// Advances the prepared interval to the next one while maintaining an overlap
@@ -60,12 +195,73 @@ pub trait SignatureSchemeSecretKey {
     /// key's total activation interval, this function does nothing.
     ///
     /// ### Example
-    /// If the prepared interval is `[a, a + 2 * sqrt(LIFETIME))`, a call to this
-    /// function will advance it to `[a + sqrt(LIFETIME), a + 3 * sqrt(LIFETIME))`.
+    /// If the prepared interval is `[a, a + 2 * C)`, a call to this
+    /// function will advance it to `[a + C, a + 3 * C)`.
     ///
     /// The caller is responsible for invoking this method only after signing for epochs
-    /// in the first `sqrt(LIFETIME)` part of the current interval is complete.
+    /// in the first `C` part of the current interval is complete.
     fn advance_preparation(&mut self);
+
+    /// Moves the prepared interval forward so that it contains the given epoch.
+    ///
+    /// In contrast to calling `advance_preparation` repeatedly, this skips all
+    /// intermediate intervals, which is useful after a long period without signing.
+    /// If the epoch is already in the prepared interval, this function does nothing.
+    ///
+    /// ### Returns
+    /// A `Result` which is:
+    /// * `Ok(())` if the key is now prepared for the epoch.
+    /// * `Err(PreparationError)` if the epoch is outside of the activation interval,
+    ///   or before the prepared interval. In this case, the key is not modified.
+    fn prepare_for_epoch(&mut self, epoch: u64) -> Result<(), PreparationError>;
+
+    /// Returns the first epoch after the activation interval, i.e., the
+    /// first epoch for which the key can no longer sign.
+    fn expiration_epoch(&self) -> u64 {
+        self.get_activation_interval().end
+    }
+
+    /// Returns the number of epochs in the activation interval that are not before
+    /// `next_epoch`, i.e., the number of signatures that the key can still produce if
+    /// `next_epoch` is the next epoch to sign for.
+    fn remaining_epochs(&self, next_epoch: u64) -> u64 {
+        let activation_interval = self.get_activation_interval();
+        activation_interval
+            .end
+            .saturating_sub(next_epoch.max(activation_interval.start))
+    }
+
+    /// Returns the first epoch for which signing requires a call to `advance_preparation`,
+    /// i.e., the end of the prepared interval, or `None` if the prepared interval already
+    /// reaches the end of the activation interval.
+    fn next_preparation_epoch(&self) -> Option<u64> {
+        let prepared_end = self.get_prepared_interval().end;
+        (prepared_end < self.expiration_epoch()).then_some(prepared_end)
+    }
+
+    /// Returns an estimate of the memory (in bytes) that is used by the
+    /// precomputed data of the key, e.g., Merkle trees.
+    fn tree_memory_bytes(&self) -> usize;
+
+    /// Returns metrics about the key, if `next_epoch` is the next epoch to sign for.
+    /// See `SecretKeyMetrics`.
+    fn metrics(&self, next_epoch: u64) -> SecretKeyMetrics {
+        let activation_interval = self.get_activation_interval();
+        let prepared_interval = self.get_prepared_interval();
+        SecretKeyMetrics {
+            activation_start: activation_interval.start,
+            expiration_epoch: self.expiration_epoch(),
+            prepared_start: prepared_interval.start,
+            prepared_end: prepared_interval.end,
+            next_epoch,
+            remaining_epochs: self.remaining_epochs(next_epoch),
+            remaining_prepared_epochs: prepared_interval
+                .end
+                .saturating_sub(next_epoch.max(prepared_interval.start)),
+            next_preparation_epoch: self.next_preparation_epoch(),
+            tree_memory_bytes: self.tree_memory_bytes() as u64,
+        }
+    }
 }
 
 /// Defines the interface for a **synchronized signature scheme**.
@@ -171,10 +367,11 @@ pub trait SignatureScheme {
     /// ### Returns
     /// A `Result` which is:
     /// * `Ok(Self::Signature)` on success, containing the generated signature.
-    /// * `Err(SigningError)` on failure.
+    /// * `Err(SigningError)` on failure, e.g., `EpochNotActive` or `EpochNotPrepared` if the
+    ///   epoch is not in the activation interval or the prepared interval of the key.
     fn sign(
         sk: &Self::SecretKey,
-        epoch: u32,
+        epoch: u64,
         message: &[u8; MESSAGE_LENGTH],
     ) -> Result<Self::Signature, SigningError>;
 
@@ -191,12 +388,40 @@ pub trait SignatureScheme {
     ///
     /// ### Returns
     /// `true` if the signature is valid according to the scheme's rules, `false` otherwise.
+    ///
+    /// Note: this never panics on malformed input. Use `verify_checked` to learn
+    /// why a signature has been rejected.
     fn verify(
         pk: &Self::PublicKey,
-        epoch: u32,
+        epoch: u64,
         message: &[u8; MESSAGE_LENGTH],
         sig: &Self::Signature,
-    ) -> bool;
+    ) -> bool {
+        Self::verify_checked(pk, epoch, message, sig).is_ok()
+    }
+
+    /// Verifies a digital signature against a public key, message, and epoch,
+    /// and reports the reason if the signature is rejected.
+    ///
+    /// This is the same as `verify`, but suitable for logging why untrusted
+    /// signatures are invalid. It must not panic for any input.
+    ///
+    /// ### Parameters
+    /// * `pk`: A reference to the public key against which to verify the signature.
+    /// * `epoch`: The epoch the signature corresponds to.
+    /// * `message`: The message that was supposedly signed.
+    /// * `sig`: A reference to the signature to be verified.
+    ///
+    /// ### Returns
+    /// A `Result` which is:
+    /// * `Ok(())` if the signature is valid according to the scheme's rules.
+    /// * `Err(VerificationError)` describing the first check that failed otherwise.
+    fn verify_checked(
+        pk: &Self::PublicKey,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &Self::Signature,
+    ) -> Result<(), VerificationError>;
 
     /// A test-only function to assert that all internal parameters chosen for the
     /// signature scheme are valid and compatible.
@@ -207,7 +432,134 @@ pub trait SignatureScheme {
     fn internal_consistency_check();
 }
 
+/// Extension of the `SignatureScheme` trait to sign messages of arbitrary length.
+///
+/// Messages are pre-hashed together with a context string into a message of length
+/// `MESSAGE_LENGTH`, which is then signed. The context string should identify the protocol
+/// (and purpose) for which a signature is produced, e.g., `b"my-protocol/v1/attestation"`.
+/// Then, a signature produced for one context is not valid for any other context.
+///
+/// Note: the restriction of signing at most once per epoch still applies.
+pub trait PreHashSignatureScheme: SignatureScheme {
+    /// Pre-hashes the context string and a message of arbitrary length
+    /// into the message of length `MESSAGE_LENGTH` that is signed.
+    fn pre_hash(context: &[u8], message: &[u8]) -> [u8; MESSAGE_LENGTH];
+
+    /// Signs a message of arbitrary length with respect to a context string.
+    /// See `sign` for the parameters and the restrictions on epochs.
+    fn sign_bytes(
+        sk: &Self::SecretKey,
+        epoch: u64,
+        context: &[u8],
+        message: &[u8],
+    ) -> Result<Self::Signature, SigningError> {
+        Self::sign(sk, epoch, &Self::pre_hash(context, message))
+    }
+
+    /// Verifies a signature for a message of arbitrary length with respect to a
+    /// context string. Returns `false` if the signature was produced for a different
+    /// context. See `verify` for the parameters.
+    fn verify_bytes(
+        pk: &Self::PublicKey,
+        epoch: u64,
+        context: &[u8],
+        message: &[u8],
+        sig: &Self::Signature,
+    ) -> bool {
+        Self::verify(pk, epoch, &Self::pre_hash(context, message), sig)
+    }
+
+    /// Same as `verify_bytes`, but reports the reason if the signature is
+    /// rejected. See `verify_checked`.
+    fn verify_bytes_checked(
+        pk: &Self::PublicKey,
+        epoch: u64,
+        context: &[u8],
+        message: &[u8],
+        sig: &Self::Signature,
+    ) -> Result<(), VerificationError> {
+        Self::verify_checked(pk, epoch, &Self::pre_hash(context, message), sig)
+    }
+}
+
+/// Extension of the `SignatureScheme` trait to aggregate signatures of many signers.
+///
+/// An aggregate signature bundles signatures of different signers (i.e., for different
+/// public keys) on the same message for the same epoch. It stores the epoch and message
+/// only once, and is verified against the list of public keys of all signers at once.
+/// This can be more efficient than verifying all signatures individually, as work that
+/// only depends on the epoch and message is shared among all signers.
+pub trait AggregatableSignatureScheme: SignatureScheme {
+    /// Aggregate of signatures from many signers on the same message for the same epoch.
+    type AggregateSignature: Serialize + DeserializeOwned;
+
+    /// Aggregates signatures on the same message for the same epoch.
+    ///
+    /// The signatures are not verified, as aggregation does not need the public keys.
+    /// However, malformed signatures are rejected, reporting their index.
+    ///
+    /// ### Parameters
+    /// * `epoch`: The epoch for which all signatures were created.
+    /// * `message`: The message that all signatures sign.
+    /// * `signatures`: The signatures, ordered by signer.
+    fn aggregate(
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        signatures: Vec<Self::Signature>,
+    ) -> Result<Self::AggregateSignature, AggregationError>;
+
+    /// Verifies an aggregate signature on a message for an epoch.
+    ///
+    /// ### Parameters
+    /// * `public_keys`: The public keys of the signers, in the order of the signatures
+    ///   given to `aggregate`.
+    /// * `epoch`: The epoch for which the signatures were created.
+    /// * `message`: The message that is signed.
+    /// * `aggregate`: The aggregate signature.
+    ///
+    /// ### Returns
+    /// `true` if the aggregate is for this epoch and message, it contains one signature per
+    /// public key, and every signature is valid for its public key. `false` otherwise.
+    fn verify_aggregate(
+        public_keys: &[Self::PublicKey],
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        aggregate: &Self::AggregateSignature,
+    ) -> bool;
+}
+
 pub mod generalized_xmss;
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod stateful;
+
+/// Instantiation of the generalized XMSS signature scheme with small parameters,
+/// which is shared by the tests of several modules.
+#[cfg(test)]
+mod test_fixtures {
+    use crate::{
+        inc_encoding::target_sum::TargetSumEncoding,
+        symmetric::{
+            message_hash::{MessageHash, sha::ShaMessageHash192x3},
+            prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+    };
+
+    use super::generalized_xmss::GeneralizedXMSSSignatureScheme;
+
+    // Note: do not use these parameters, they are just for testing
+    pub type PRF = ShaPRF<24, 24>;
+    pub type TH = ShaTweak192192;
+    pub type MH = ShaMessageHash192x3;
+    pub const BASE: usize = MH::BASE;
+    pub const NUM_CHUNKS: usize = MH::DIMENSION;
+    pub const MAX_CHUNK_VALUE: usize = BASE - 1;
+    pub const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+    pub type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+    pub const LOG_LIFETIME: usize = 6;
+    pub type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+}
 
 #[cfg(test)]
 mod test_templates {
@@ -219,18 +571,18 @@ mod test_templates {
     /// Tests correctness, i.e., that honest key gen, honest signing, implies
     /// that the verifier accepts the signature. A random message is used.
     pub fn test_signature_scheme_correctness<T: SignatureScheme>(
-        epoch: u32,
+        epoch: u64,
         activation_epoch: usize,
         num_active_epochs: usize,
     ) {
         // The epoch must be in the activation interval
         assert!(
-            activation_epoch as u32 <= epoch
-                && (epoch as u64) < (activation_epoch + num_active_epochs) as u64,
+            activation_epoch as u64 <= epoch
+                && epoch < (activation_epoch + num_active_epochs) as u64,
             "Did not even try signing, epoch {:?} outside of activation interval {:?},{:?}",
             epoch,
             activation_epoch,
-            (activation_epoch + num_active_epochs) as u32
+            activation_epoch + num_active_epochs
         );
 
         let mut rng = rand::rng();
@@ -240,12 +592,12 @@ mod test_templates {
 
         // Advance the secret key until the epoch is in the prepared interval
         let mut iterations = 0;
-        while !sk.get_prepared_interval().contains(&(epoch as u64)) && iterations < epoch {
+        while !sk.get_prepared_interval().contains(&epoch) && iterations < epoch {
             sk.advance_preparation();
             iterations += 1;
         }
         assert!(
-            sk.get_prepared_interval().contains(&(epoch as u64)),
+            sk.get_prepared_interval().contains(&epoch),
             "Did not even try signing, failed to advance key preparation to desired epoch {:?}.",
             epoch
         );
@@ -272,6 +624,13 @@ mod test_templates {
             "Signature verification failed. . Epoch was {:?}",
             epoch
         );
+        let result = T::verify_checked(&pk, epoch, &message, &signature);
+        assert!(
+            result.is_ok(),
+            "Signature verification failed: {:?}. Epoch was {:?}",
+            result.err(),
+            epoch
+        );
 
         test_bincode_round_trip_consistency(&pk);
         test_bincode_round_trip_consistency(&sk);
@@ -1,21 +1,36 @@
-use std::marker::PhantomData;
-
-use rand::Rng;
-use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use alloc::{vec, vec::Vec};
+use core::{borrow::Borrow, fmt, marker::PhantomData};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use sha3::{
+    Shake128,
+    digest::{ExtendableOutput, Update, XofReader},
+};
+use thiserror::Error;
+use zeroize::ZeroizeOnDrop;
 
 use crate::{
     MESSAGE_LENGTH,
     inc_encoding::IncomparableEncoding,
-    signature::SignatureSchemeSecretKey,
+    parallelism::map_range,
+    signature::{PreparationError, SignatureSchemeSecretKey},
     symmetric::{
         prf::Pseudorandom,
-        tweak_hash::{TweakableHash, chain},
-        tweak_hash_tree::{HashSubTree, HashTreeOpening, combined_path, hash_tree_verify},
+        tweak_hash::{
+            MessagePreHash, TweakableHash, chain, chain_constant_time, chain_with_tweaks,
+        },
+        tweak_hash_tree::{
+            HashSubTree, HashTreeOpening, SparseTreePadding, combined_path, hash_tree_verify,
+        },
     },
 };
 
-use super::{SignatureScheme, SigningError};
+use super::{
+    BatchVerificationError, PreHashSignatureScheme, SignatureScheme, SigningError,
+    VerificationError,
+};
 
 /// Implementation of the generalized XMSS signature scheme
 /// from any incomparable encoding scheme and any tweakable hash
@@ -23,16 +38,45 @@ use super::{SignatureScheme, SigningError};
 /// It also uses a PRF for key generation, and one has to specify
 /// the (base 2 log of the) key lifetime.
 ///
-/// Note: lifetimes beyond 2^32 are not supported.
+/// Note: epochs are 64 bits wide, so lifetimes beyond 2^32 are supported, up to 2^63. For
+/// Poseidon-based hashes, tweaks for such epochs may need more field elements than tweaks
+/// for 32-bit epochs (see `PoseidonTweak::to_field_elements`). Also, keys with activation
+/// intervals beyond 2^32 can only be generated on 64-bit platforms.
 pub struct GeneralizedXMSSSignatureScheme<
     PRF: Pseudorandom,
     IE: IncomparableEncoding,
     TH: TweakableHash,
     const LOG_LIFETIME: usize,
 > {
-    _prf: std::marker::PhantomData<PRF>,
-    _ie: std::marker::PhantomData<IE>,
-    _th: std::marker::PhantomData<TH>,
+    _prf: core::marker::PhantomData<PRF>,
+    _ie: core::marker::PhantomData<IE>,
+    _th: core::marker::PhantomData<TH>,
+}
+
+/// Determines how the chains are walked when signing, see `sign_with_mode`.
+///
+/// Side-channel model: the secret values during signing are the PRF key, the starts of
+/// the chains derived from it, and all chain elements beyond the ones in the signature.
+/// In contrast, the codeword `x` that determines how far each chain is walked can be
+/// recomputed from the signature, as can the number of encoding attempts up to the
+/// randomness `rho`. Hashing and field arithmetic are assumed to run in constant time.
+///
+/// In `VariableTime` mode, the running time depends on `x`, and thus on the message. This
+/// is fine as long as timing is only observable together with the signature. Otherwise,
+/// e.g., if the signer aborts before releasing a signature or signs messages that are
+/// secret, the `ConstantTime` mode should be used. It always walks each chain to its end,
+/// and keeps the chain element for `x` via a conditional select, so that the running time
+/// and memory accesses of walking chains do not depend on `x`. This is about twice as slow.
+///
+/// Note: the number of encoding attempts depends on the message in both modes. It only
+/// reveals that the earlier (independent) candidates for `rho` were rejected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SigningMode {
+    /// Walks each chain only as far as needed for the signature.
+    #[default]
+    VariableTime,
+    /// Walks each chain to its end, independent of the message.
+    ConstantTime,
 }
 
 /// Signature for GeneralizedXMSSSignatureScheme
@@ -53,8 +97,62 @@ pub struct GeneralizedXMSSPublicKey<TH: TweakableHash> {
     parameter: TH::Parameter,
 }
 
+/// Item of a batch for `GeneralizedXMSSSignatureScheme::verify_batch`,
+/// given by a public key, an epoch, a message, and a signature.
+pub type GeneralizedXMSSBatchItem<'a, IE, TH> = (
+    &'a GeneralizedXMSSPublicKey<TH>,
+    u64,
+    &'a [u8; MESSAGE_LENGTH],
+    &'a GeneralizedXMSSSignature<IE, TH>,
+);
+
+/// Determines how the Merkle tree of a secret key is split into levels of sub-trees,
+/// see `GeneralizedXMSSSignatureScheme::key_gen_with_layout`.
+///
+/// The bottom trees contain the lowest `log_bottom_tree` layers, and the layers above
+/// them are split as evenly as possible into `num_levels - 1` levels, the highest of
+/// which is the top tree. Activation intervals are aligned to bottom trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeLayout {
+    /// Base 2 logarithm of the number of leafs of each bottom tree.
+    pub log_bottom_tree: usize,
+    /// Number of levels of sub-trees, including the bottom trees and the top tree.
+    pub num_levels: usize,
+}
+
+/// Version of the serialization format of secret keys and compact secret keys.
+/// It is increased whenever the layout of either of them changes.
+pub const SECRET_KEY_FORMAT_VERSION: u8 = 1;
+
+/// Format version of a secret key, which is serialized as `SECRET_KEY_FORMAT_VERSION`
+/// before all other fields. Deserialization fails if the version is different.
+#[derive(Debug, Clone, Copy)]
+struct SecretKeyFormatVersion;
+
+impl Serialize for SecretKeyFormatVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(SECRET_KEY_FORMAT_VERSION)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretKeyFormatVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let version = u8::deserialize(deserializer)?;
+        if version != SECRET_KEY_FORMAT_VERSION {
+            return Err(D::Error::custom(format_args!(
+                "unsupported secret key format version {version}, expected {SECRET_KEY_FORMAT_VERSION}"
+            )));
+        }
+        Ok(Self)
+    }
+}
+
 /// Secret key for GeneralizedXMSSSignatureScheme
-/// It contains a PRF key and a Merkle tree.
+/// It contains a PRF key and parts of a Merkle tree.
+///
+/// The Merkle tree is split into levels of sub-trees as given by its `TreeLayout`.
+/// The key stores the top tree, and for each level below, the sub-trees that contain the
+/// prepared interval. In particular, it stores two bottom trees.
 ///
 /// Note: one may choose to regenerate the tree on the fly, but this
 /// would be costly for signatures.
@@ -66,17 +164,109 @@ pub struct GeneralizedXMSSSecretKey<
     TH: TweakableHash,
     const LOG_LIFETIME: usize,
 > {
+    format_version: SecretKeyFormatVersion,
     prf_key: PRF::Key,
     parameter: TH::Parameter,
     activation_epoch: usize,
     num_active_epochs: usize,
+    tree_layout: TreeLayout,
     top_tree: HashSubTree<TH>,
-    left_bottom_tree_index: usize,
-    left_bottom_tree: HashSubTree<TH>,
-    right_bottom_tree: HashSubTree<TH>,
+    /// Sub-trees of each level below the top tree that contain the prepared interval,
+    /// ordered by their index. The first level contains exactly two bottom trees.
+    prepared_subtrees: Vec<Vec<HashSubTree<TH>>>,
+    top_tree_padding_seed: [u8; 32],
     _encoding_type: PhantomData<IE>,
 }
 
+/// Compact representation of a `GeneralizedXMSSSecretKey`.
+///
+/// It only contains the seeds from which the secret key is derived, i.e., the PRF key
+/// and the seed for the random padding of the top tree, together with the (expanded)
+/// activation interval, the tree layout, and the position of the prepared interval. The
+/// trees are rebuilt in `GeneralizedXMSSSecretKey::from_compact`, which is as expensive as
+/// key generation. This makes it suitable for cold backups and exports, but not for regular use.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct GeneralizedXMSSCompactSecretKey<PRF: Pseudorandom, TH: TweakableHash> {
+    format_version: SecretKeyFormatVersion,
+    prf_key: PRF::Key,
+    parameter: TH::Parameter,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+    tree_layout: TreeLayout,
+    left_bottom_tree_index: usize,
+    top_tree_padding_seed: [u8; 32],
+}
+
+// The secret fields wipe themselves: the PRF key is required to do so by the
+// `Pseudorandom` trait, and the trees overwrite their nodes when dropped. In
+// particular, the sub-trees that are no longer needed are wiped when advancing the preparation.
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    ZeroizeOnDrop for GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>
+{
+}
+
+impl<PRF: Pseudorandom, TH: TweakableHash> ZeroizeOnDrop
+    for GeneralizedXMSSCompactSecretKey<PRF, TH>
+{
+}
+
+/// Redacts the PRF key and the trees, and only shows the intervals of the key.
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    fmt::Debug for GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneralizedXMSSSecretKey")
+            .field("prf_key", &REDACTED)
+            .field("activation_epoch", &self.activation_epoch)
+            .field("num_active_epochs", &self.num_active_epochs)
+            .field("tree_layout", &self.tree_layout)
+            .field("left_bottom_tree_index", &self.left_bottom_tree_index())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Redacts the PRF key, and only shows the intervals of the key.
+impl<PRF: Pseudorandom, TH: TweakableHash> fmt::Debug for GeneralizedXMSSCompactSecretKey<PRF, TH> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneralizedXMSSCompactSecretKey")
+            .field("prf_key", &REDACTED)
+            .field("activation_epoch", &self.activation_epoch)
+            .field("num_active_epochs", &self.num_active_epochs)
+            .field("tree_layout", &self.tree_layout)
+            .field("left_bottom_tree_index", &self.left_bottom_tree_index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Placeholder that is shown instead of secret values in `Debug` output.
+const REDACTED: &str = "<redacted>";
+
+/// Error enum for restoring a secret key from its compact representation.
+#[derive(Debug, Error)]
+pub enum CompactSecretKeyError {
+    /// Occurs when the activation interval is not aligned to bottom trees,
+    /// covers less than two bottom trees, or exceeds the lifetime.
+    #[error(
+        "Invalid activation interval: {num_active_epochs} epochs starting at {activation_epoch}."
+    )]
+    InvalidActivationInterval {
+        activation_epoch: usize,
+        num_active_epochs: usize,
+    },
+
+    /// Occurs when the tree layout is not supported for the lifetime,
+    /// see `GeneralizedXMSSSignatureScheme::key_gen_with_layout`.
+    #[error("Invalid tree layout: {tree_layout:?}.")]
+    InvalidTreeLayout { tree_layout: TreeLayout },
+
+    /// Occurs when the prepared interval is not contained in the activation interval.
+    #[error(
+        "Prepared interval starting at bottom tree {left_bottom_tree_index} is not within the activation interval."
+    )]
+    InvalidPreparedInterval { left_bottom_tree_index: usize },
+}
+
 impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
     SignatureSchemeSecretKey for GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>
 where
@@ -84,66 +274,329 @@ where
     PRF::Randomness: Into<IE::Randomness>,
     TH::Parameter: Into<IE::Parameter>,
 {
-    fn get_activation_interval(&self) -> std::ops::Range<u64> {
+    fn get_activation_interval(&self) -> core::ops::Range<u64> {
         let start = self.activation_epoch as u64;
         let end = start + self.num_active_epochs as u64;
         start..end
     }
 
-    fn get_prepared_interval(&self) -> std::ops::Range<u64> {
+    fn get_prepared_interval(&self) -> core::ops::Range<u64> {
         // the key is prepared for all epochs covered by the left and right bottom tree
-        // and each bottom tree covers exactly 2^{LOG_LIFETIME / 2} leafs
-        let leafs_per_bottom_tree = 1 << (LOG_LIFETIME / 2);
-        let start = (self.left_bottom_tree_index * leafs_per_bottom_tree) as u64;
+        let leafs_per_bottom_tree = self.leafs_per_bottom_tree();
+        let start = (self.left_bottom_tree_index() * leafs_per_bottom_tree) as u64;
         let end = start + (2 * leafs_per_bottom_tree as u64);
         start..end
     }
 
     fn advance_preparation(&mut self) {
         // First, check if advancing is possible by comparing to activation interval.
-        let leafs_per_bottom_tree = 1 << (LOG_LIFETIME / 2);
-        let next_prepared_end_epoch =
-            self.left_bottom_tree_index * leafs_per_bottom_tree + 3 * leafs_per_bottom_tree;
-        if next_prepared_end_epoch as u64 > self.get_activation_interval().end {
+        let Some(next_bottom_tree_index) = self.next_bottom_tree_index() else {
             return;
+        };
+
+        // We compute the new right bottom tree, and the sub-trees of higher
+        // levels that contain it, if they are not stored yet.
+        let new_left_bottom_tree_index = next_bottom_tree_index - 1;
+        let new_subtrees = self.compute_subtrees(new_left_bottom_tree_index);
+        self.install_subtrees(new_left_bottom_tree_index, new_subtrees);
+    }
+
+    fn prepare_for_epoch(&mut self, epoch: u64) -> Result<(), PreparationError> {
+        let activation_interval = self.get_activation_interval();
+        if !activation_interval.contains(&epoch) {
+            return Err(PreparationError::EpochNotActive {
+                epoch,
+                activation_interval,
+            });
+        }
+        let prepared_interval = self.get_prepared_interval();
+        if epoch < prepared_interval.start {
+            return Err(PreparationError::EpochBehindPreparedInterval {
+                epoch,
+                prepared_interval,
+            });
+        }
+        if prepared_interval.contains(&epoch) {
+            return Ok(());
+        }
+
+        // The new left bottom tree is the one containing the epoch, unless this is
+        // the last bottom tree of the activation interval. Then the epoch is covered
+        // by the new right bottom tree.
+        let leafs_per_bottom_tree = self.leafs_per_bottom_tree();
+        let last_bottom_tree_index =
+            (self.activation_epoch + self.num_active_epochs) / leafs_per_bottom_tree - 1;
+        let new_left_bottom_tree_index =
+            (epoch as usize / leafs_per_bottom_tree).min(last_bottom_tree_index - 1);
+
+        // We only compute the sub-trees that are not stored yet. If the current right
+        // bottom tree is still needed, this is just a normal advance. Otherwise, the
+        // two new bottom trees are computed in parallel.
+        let new_subtrees = self.compute_subtrees(new_left_bottom_tree_index);
+        self.install_subtrees(new_left_bottom_tree_index, new_subtrees);
+
+        Ok(())
+    }
+
+    fn tree_memory_bytes(&self) -> usize {
+        // the key stores the top tree and the sub-trees containing the prepared interval
+        let num_nodes = self.top_tree.num_nodes()
+            + self
+                .prepared_subtrees
+                .iter()
+                .flatten()
+                .map(HashSubTree::num_nodes)
+                .sum::<usize>();
+        num_nodes * core::mem::size_of::<TH::Domain>()
+    }
+}
+
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>
+{
+    /// Returns the compact representation of this secret key, which
+    /// only contains the seeds needed to rebuild it.
+    pub fn to_compact(&self) -> GeneralizedXMSSCompactSecretKey<PRF, TH> {
+        GeneralizedXMSSCompactSecretKey {
+            format_version: SecretKeyFormatVersion,
+            prf_key: self.prf_key.clone(),
+            parameter: self.parameter,
+            activation_epoch: self.activation_epoch,
+            num_active_epochs: self.num_active_epochs,
+            tree_layout: self.tree_layout,
+            left_bottom_tree_index: self.left_bottom_tree_index(),
+            top_tree_padding_seed: self.top_tree_padding_seed,
+        }
+    }
+
+    /// Rebuilds a secret key from its compact representation. The result is identical
+    /// to the secret key from which the compact representation has been obtained.
+    ///
+    /// Note: this recomputes all bottom trees, so it is as expensive as key generation.
+    pub fn from_compact(
+        compact: GeneralizedXMSSCompactSecretKey<PRF, TH>,
+    ) -> Result<Self, CompactSecretKeyError>
+    where
+        PRF::Domain: Into<TH::Domain>,
+        PRF::Randomness: Into<IE::Randomness>,
+        TH::Parameter: Into<IE::Parameter>,
+    {
+        if !is_valid_tree_layout::<LOG_LIFETIME>(compact.tree_layout) {
+            return Err(CompactSecretKeyError::InvalidTreeLayout {
+                tree_layout: compact.tree_layout,
+            });
+        }
+
+        // the activation interval must be one that `expand_activation_time` can output
+        let leafs_per_bottom_tree = 1 << compact.tree_layout.log_bottom_tree;
+        let start_bottom_tree_index = compact.activation_epoch / leafs_per_bottom_tree;
+        let end_bottom_tree_index = compact
+            .activation_epoch
+            .saturating_add(compact.num_active_epochs)
+            / leafs_per_bottom_tree;
+        if !compact
+            .activation_epoch
+            .is_multiple_of(leafs_per_bottom_tree)
+            || !compact
+                .num_active_epochs
+                .is_multiple_of(leafs_per_bottom_tree)
+            || end_bottom_tree_index - start_bottom_tree_index < 2
+            || end_bottom_tree_index > 1 << (LOG_LIFETIME - compact.tree_layout.log_bottom_tree)
+        {
+            return Err(CompactSecretKeyError::InvalidActivationInterval {
+                activation_epoch: compact.activation_epoch,
+                num_active_epochs: compact.num_active_epochs,
+            });
+        }
+
+        // the two bottom trees of the prepared interval must be in the activation interval
+        let left_bottom_tree_index = compact.left_bottom_tree_index;
+        if left_bottom_tree_index < start_bottom_tree_index
+            || left_bottom_tree_index + 2 > end_bottom_tree_index
+        {
+            return Err(CompactSecretKeyError::InvalidPreparedInterval {
+                left_bottom_tree_index,
+            });
+        }
+
+        let builder = HypertreeBuilder::<PRF, IE, TH, LOG_LIFETIME>::new(
+            &compact.prf_key,
+            &compact.parameter,
+            compact.tree_layout,
+            start_bottom_tree_index..end_bottom_tree_index,
+            compact.top_tree_padding_seed,
+        );
+        let (top_tree, prepared_subtrees) = builder.top_tree(left_bottom_tree_index);
+
+        Ok(Self {
+            format_version: SecretKeyFormatVersion,
+            prf_key: compact.prf_key,
+            parameter: compact.parameter,
+            activation_epoch: compact.activation_epoch,
+            num_active_epochs: compact.num_active_epochs,
+            tree_layout: compact.tree_layout,
+            top_tree,
+            prepared_subtrees,
+            top_tree_padding_seed: compact.top_tree_padding_seed,
+            _encoding_type: PhantomData,
+        })
+    }
+
+    /// Returns how the Merkle tree of the key is split into levels of sub-trees.
+    #[must_use]
+    pub const fn tree_layout(&self) -> TreeLayout {
+        self.tree_layout
+    }
+
+    /// Returns the number of leafs of each bottom tree, i.e., by how many epochs
+    /// the prepared interval moves when advancing the preparation.
+    pub(super) const fn leafs_per_bottom_tree(&self) -> usize {
+        1 << self.tree_layout.log_bottom_tree
+    }
+
+    /// Returns the index of the left bottom tree of the prepared interval.
+    fn left_bottom_tree_index(&self) -> usize {
+        self.prepared_subtrees[0][0].root_index()
+    }
+
+    /// Returns the index of the bottom tree that has to be computed to advance
+    /// the prepared interval, or `None` if the prepared interval cannot be advanced
+    /// because it already reaches the end of the activation interval.
+    fn next_bottom_tree_index(&self) -> Option<usize> {
+        let leafs_per_bottom_tree = self.leafs_per_bottom_tree();
+        let left_bottom_tree_index = self.left_bottom_tree_index();
+        let next_prepared_end_epoch =
+            left_bottom_tree_index * leafs_per_bottom_tree + 3 * leafs_per_bottom_tree;
+        if next_prepared_end_epoch > self.activation_epoch + self.num_active_epochs {
+            return None;
         }
+        Some(left_bottom_tree_index + 2)
+    }
 
-        // We compute the new right bottom subtree (using the helper function bottom_tree_from_prf_key)
-        let new_right_bottom_tree = bottom_tree_from_prf_key::<PRF, IE, TH, LOG_LIFETIME>(
+    /// Computes the sub-trees that are needed for the prepared interval starting at the
+    /// given bottom tree, but are not stored in the key yet. For each level, the new
+    /// sub-trees are returned in the order of their index.
+    ///
+    /// The sub-trees of each level are computed in parallel (see `Parallelism`). A sub-tree
+    /// of a higher level is computed from its bottom trees, so in this case the new sub-trees
+    /// of the lower levels are obtained as a by-product.
+    fn compute_subtrees(&self, left_bottom_tree_index: usize) -> Vec<Vec<HashSubTree<TH>>>
+    where
+        PRF::Domain: Into<TH::Domain>,
+        PRF::Randomness: Into<IE::Randomness>,
+        TH::Parameter: Into<IE::Parameter>,
+    {
+        let builder = HypertreeBuilder::<PRF, IE, TH, LOG_LIFETIME>::new(
             &self.prf_key,
-            self.left_bottom_tree_index + 2,
             &self.parameter,
+            self.tree_layout,
+            self.activation_epoch / self.leafs_per_bottom_tree()
+                ..(self.activation_epoch + self.num_active_epochs) / self.leafs_per_bottom_tree(),
+            self.top_tree_padding_seed,
         );
+        let keep = left_bottom_tree_index..left_bottom_tree_index + 2;
+
+        let mut new_subtrees: Vec<Vec<HashSubTree<TH>>> = (0..self.tree_layout.num_levels - 1)
+            .map(|_| Vec::new())
+            .collect();
+        for level in (0..self.tree_layout.num_levels - 1).rev() {
+            // we skip sub-trees that are stored, or that have just been computed
+            // as part of a sub-tree of a higher level
+            let missing: Vec<usize> = builder
+                .subtree_indices(level, &keep)
+                .filter(|index| {
+                    !self.prepared_subtrees[level]
+                        .iter()
+                        .chain(&new_subtrees[level])
+                        .any(|subtree| subtree.root_index() == *index)
+                })
+                .collect();
+            let computed = map_range(0..missing.len(), |i| {
+                builder.subtree(level, missing[i], &keep)
+            });
+            for (subtree, descendants) in computed {
+                new_subtrees[level].push(subtree);
+                for (lower_level, subtrees) in descendants.into_iter().enumerate() {
+                    new_subtrees[lower_level].extend(subtrees);
+                }
+            }
+        }
+        for subtrees in &mut new_subtrees {
+            subtrees.sort_by_key(HashSubTree::root_index);
+        }
+        new_subtrees
+    }
 
-        // The bottom tree that was previously right should now be left.
-        // So, we move the right bottom subtree to the left one and update our index.
-        // We also write the new right bottom tree into the right bottom tree field.
-        // Note that once the function terminates, the old left bottom tree is dropped
-        // from memory. So, at any point in time, we have at most 4 trees in memory,
-        // namely, the three bottom trees (two current, one new) and the top tree.
-        self.left_bottom_tree =
-            std::mem::replace(&mut self.right_bottom_tree, new_right_bottom_tree);
-        self.left_bottom_tree_index += 1;
+    /// Moves the prepared interval such that it starts at the given bottom tree, given the
+    /// sub-trees that have been computed by `compute_subtrees` for this bottom tree.
+    fn install_subtrees(
+        &mut self,
+        left_bottom_tree_index: usize,
+        new_subtrees: Vec<Vec<HashSubTree<TH>>>,
+    ) {
+        // We add the new sub-trees to each level, and then drop the sub-trees that do not
+        // contain the new prepared interval. They are wiped from memory when dropped. So, at
+        // any point in time, we have at most one more sub-tree per level in memory. For
+        // two levels, these are the three bottom trees (two current, one new) and the top tree.
+        let keep = left_bottom_tree_index..left_bottom_tree_index + 2;
+        for (level, new_subtrees) in new_subtrees.into_iter().enumerate() {
+            let subtrees = &mut self.prepared_subtrees[level];
+            subtrees.extend(new_subtrees);
+            subtrees.retain(|subtree| {
+                let bottom_tree_indices = bottom_tree_range::<LOG_LIFETIME>(
+                    self.tree_layout,
+                    level,
+                    subtree.root_index(),
+                );
+                bottom_tree_indices.start < keep.end && keep.start < bottom_tree_indices.end
+            });
+            subtrees.sort_by_key(HashSubTree::root_index);
+        }
+        debug_assert_eq!(self.left_bottom_tree_index(), left_bottom_tree_index);
     }
 }
 
+const KEY_GEN_SEED_DOMAIN_SEP: [u8; 16] = [
+    0x5e, 0xed, 0x4b, 0x65, 0x79, 0x47, 0x65, 0x6e, 0x00, 0xa1, 0x37, 0xff, 0x01, 0xc4, 0x0e, 0x00,
+];
+const KEY_GEN_SEED_DOMAIN_SEP_PARAMETER: [u8; 1] = [0x00];
+const KEY_GEN_SEED_DOMAIN_SEP_PRF_KEY: [u8; 1] = [0x01];
+const KEY_GEN_SEED_DOMAIN_SEP_TOP_TREE_PADDING: [u8; 1] = [0x02];
+
+/// Helper function to expand a key generation seed into an independent seed
+/// for one of the components of the key, determined by the domain separator.
+fn expand_seed(seed: &[u8; 32], component_domain_sep: [u8; 1]) -> [u8; 32] {
+    let mut hasher = Shake128::default();
+    hasher.update(&KEY_GEN_SEED_DOMAIN_SEP);
+    hasher.update(&component_domain_sep);
+    hasher.update(seed);
+
+    let mut expanded = [0u8; 32];
+    hasher.finalize_xof().read(&mut expanded);
+    expanded
+}
+
 /// Helper function to take a desired activation time (given by start and duration)
-/// and potentially increase it, so that, for C = 1 << (LOG_LIFETIME/2).
+/// and potentially increase it, so that, for C = 1 << log_bottom_tree.
 ///     1. the new duration is a multiple of C
 ///     2. the new duration is at least 2 * C
 ///     3. the new activation time starts at a multiple of C
-///     4. the new activation interval is contained in [0...C^2) = [0,..LIFETIME).
+///     4. the new activation interval is contained in [0...LIFETIME).
 ///     5. the new interval contains the desired interval.
 ///
 /// The returned result is a pair (start, excl_end) of integers, such that the new
 /// activation interval is given by [start * C , excl_end * C).
+///
+/// Note: C is the number of leafs of a bottom tree, so the activation interval is aligned
+/// to bottom trees. It requires `log_bottom_tree < LOG_LIFETIME`.
 fn expand_activation_time<const LOG_LIFETIME: usize>(
+    log_bottom_tree: usize,
     desired_activation_epoch: usize,
     desired_num_active_epochs: usize,
 ) -> (usize, usize) {
     let lifetime = 1usize << LOG_LIFETIME;
-    let c = 1usize << (LOG_LIFETIME / 2);
-    // c_mask has the form 1...10...0, with LOG_LIFETIME / 2 many 0's.
+    let c = 1usize << log_bottom_tree;
+    // c_mask has the form 1...10...0, with log_bottom_tree many 0's.
     let c_mask = !(c - 1);
 
     let desired_start = desired_activation_epoch;
@@ -176,237 +629,356 @@ fn expand_activation_time<const LOG_LIFETIME: usize>(
     }
 
     // now divide by c to get what we want
-    start >>= LOG_LIFETIME / 2;
-    end >>= LOG_LIFETIME / 2;
+    start >>= log_bottom_tree;
+    end >>= log_bottom_tree;
 
     (start, end)
 }
 
-/// Helper function to compute a bottom tree from the PRF key. The PRF key is used to re-generate
-/// the secret keys, then the public keys are generated and hashed to obtain the leafs of the
-/// bottom tree. Then the bottom tree is computed.
-fn bottom_tree_from_prf_key<
-    PRF: Pseudorandom,
-    IE: IncomparableEncoding,
-    TH: TweakableHash,
-    const LOG_LIFETIME: usize,
->(
+/// Helper function to get the tree layout used by `key_gen`, i.e., the top-bottom tree
+/// approach with bottom trees of `LOG_LIFETIME / 2` layers. For odd LOG_LIFETIME, the
+/// top tree has one layer more than the bottom trees.
+const fn default_tree_layout<const LOG_LIFETIME: usize>() -> TreeLayout {
+    TreeLayout {
+        log_bottom_tree: LOG_LIFETIME / 2,
+        num_levels: 2,
+    }
+}
+
+/// Helper function to get the tree layout used by `key_gen_with_levels`, i.e., with bottom
+/// trees of `LOG_LIFETIME / num_levels` layers. The result is invalid for less than two levels.
+const fn tree_layout_with_levels<const LOG_LIFETIME: usize>(num_levels: usize) -> TreeLayout {
+    TreeLayout {
+        log_bottom_tree: match LOG_LIFETIME.checked_div(num_levels) {
+            Some(log_bottom_tree) => log_bottom_tree,
+            None => 0,
+        },
+        num_levels,
+    }
+}
+
+/// Helper function to check whether a Merkle tree of depth LOG_LIFETIME can be split
+/// as given by the tree layout. We need at least a top level and a bottom level, and
+/// each level must contain at least one layer below its roots.
+const fn is_valid_tree_layout<const LOG_LIFETIME: usize>(tree_layout: TreeLayout) -> bool {
+    tree_layout.num_levels >= 2
+        && tree_layout.log_bottom_tree >= 1
+        && tree_layout.log_bottom_tree + tree_layout.num_levels - 1 <= LOG_LIFETIME
+}
+
+/// Helper function to get the layer that separates the given level of sub-trees from the
+/// level below, if the Merkle tree of depth LOG_LIFETIME is split as given by the tree layout.
+/// That is, the sub-trees of level `j` contain the layers from `level_boundary(j)` up to
+/// their roots in layer `level_boundary(j + 1)`. Level 0 consists of the bottom trees, and
+/// level `num_levels - 1` only of the top tree, whose root is in layer LOG_LIFETIME.
+///
+/// For two levels, this is the top-bottom tree approach, with the boundary in layer
+/// `log_bottom_tree`. For more levels, the layers above the bottom trees are split
+/// as evenly as possible.
+const fn level_boundary<const LOG_LIFETIME: usize>(tree_layout: TreeLayout, level: usize) -> usize {
+    if level == 0 {
+        return 0;
+    }
+    let upper_layers = LOG_LIFETIME - tree_layout.log_bottom_tree;
+    tree_layout.log_bottom_tree + (level - 1) * upper_layers / (tree_layout.num_levels - 1)
+}
+
+/// Helper function to get the range of bottom trees below the sub-tree
+/// with the given index in the given level.
+const fn bottom_tree_range<const LOG_LIFETIME: usize>(
+    tree_layout: TreeLayout,
+    level: usize,
+    index: usize,
+) -> core::ops::Range<usize> {
+    let shift =
+        level_boundary::<LOG_LIFETIME>(tree_layout, level + 1) - tree_layout.log_bottom_tree;
+    (index << shift)..((index + 1) << shift)
+}
+
+/// Helper function to sample the random padding of the sparse Merkle tree of a key with the
+/// given activation interval, from the seed of the top tree padding.
+///
+/// We use a portable RNG for the padding, so that the trees only depend on the seed.
+/// As the activation interval is aligned to bottom trees, there is no padding below
+/// the roots of the bottom trees, and the padding is the same as for the top tree
+/// of the top-bottom tree approach.
+fn tree_padding<TH: TweakableHash, const LOG_LIFETIME: usize>(
+    top_tree_padding_seed: [u8; 32],
+    activation_epoch: usize,
+    num_active_epochs: usize,
+) -> SparseTreePadding<TH> {
+    let mut padding_rng = ChaCha20Rng::from_seed(top_tree_padding_seed);
+    SparseTreePadding::new(
+        &mut padding_rng,
+        0,
+        LOG_LIFETIME,
+        activation_epoch,
+        num_active_epochs,
+    )
+}
+
+/// Helper function to compute the leaf of the Merkle tree for the given epoch from the
+/// PRF key. It re-generates the secret key of the epoch, i.e., the starts of the chains,
+/// walks the chains to their ends, which form the public key of the epoch, and hashes them.
+/// The chains are walked in parallel (see `Parallelism`).
+fn leaf_hash<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash>(
     prf_key: &PRF::Key,
-    bottom_tree_index: usize,
     parameter: &TH::Parameter,
-) -> HashSubTree<TH>
+    epoch: u64,
+) -> TH::Domain
 where
     PRF::Domain: Into<TH::Domain>,
-    PRF::Randomness: Into<IE::Randomness>,
-    TH::Parameter: Into<IE::Parameter>,
 {
-    let leafs_per_bottom_tree = 1 << (LOG_LIFETIME / 2);
     let num_chains = IE::DIMENSION;
     let chain_length = IE::BASE;
 
-    // the range of epochs covered by that bottom tree
-    let epoch_range_start = bottom_tree_index * leafs_per_bottom_tree;
-    let epoch_range_end = epoch_range_start + leafs_per_bottom_tree;
-    let epoch_range = epoch_range_start..epoch_range_end;
-
-    // parallelize the chain ends hash computation for each epoch in the interval for that bottom tree
-    let chain_ends_hashes = epoch_range
-        .into_par_iter()
-        .map(|epoch| {
-            // each epoch has a number of chains
-            // parallelize the chain ends computation for each chain
-            let chain_ends = (0..num_chains)
-                .into_par_iter()
-                .map(|chain_index| {
-                    // each chain start is just a PRF evaluation
-                    let start =
-                        PRF::get_domain_element(prf_key, epoch as u32, chain_index as u64).into();
-                    // walk the chain to get the public chain end
-                    chain::<TH>(
-                        parameter,
-                        epoch as u32,
-                        chain_index as u8,
-                        0,
-                        chain_length - 1,
-                        &start,
-                    )
-                })
-                .collect::<Vec<_>>();
-            // build hash of chain ends / public keys
-            TH::apply(parameter, &TH::tree_tweak(0, epoch as u32), &chain_ends)
-        })
-        .collect();
-
-    // now that we have the hashes of all chain ends (= leafs of our tree), we can compute the bottom tree
-    HashSubTree::new_bottom_tree(
-        LOG_LIFETIME,
-        bottom_tree_index,
-        parameter,
-        chain_ends_hashes,
-    )
+    let chain_ends = map_range(0..num_chains, |chain_index| {
+        // each chain start is just a PRF evaluation
+        let start = PRF::get_domain_element(prf_key, epoch, chain_index as u64).into();
+        // walk the chain to get the public chain end
+        chain::<TH>(
+            parameter,
+            epoch,
+            chain_index as u8,
+            0,
+            chain_length - 1,
+            &start,
+        )
+    });
+    // build hash of chain ends / public keys
+    TH::apply(parameter, &TH::tree_tweak(0, epoch), &chain_ends)
 }
 
-impl<
+/// Helper to compute the sub-trees of a secret key from the PRF key.
+///
+/// The Merkle tree is split into levels of sub-trees (see `level_boundary`). The bottom trees
+/// are computed from the PRF key: it is used to re-generate the secret keys, then the public
+/// keys are generated and hashed to obtain the leafs of the bottom tree. The sub-trees of the
+/// higher levels are computed from the roots of the sub-trees below them.
+///
+/// All sub-trees are cut out of the same sparse tree, which contains the leafs of the
+/// activation interval. Its random padding is derived from the seed of the top tree padding,
+/// so that the root is the same for any tree layout, and also for the full tree.
+struct HypertreeBuilder<
+    'a,
     PRF: Pseudorandom,
-    IE: IncomparableEncoding + Sync + Send,
+    IE: IncomparableEncoding,
     TH: TweakableHash,
     const LOG_LIFETIME: usize,
-> SignatureScheme for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+> {
+    prf_key: &'a PRF::Key,
+    parameter: &'a TH::Parameter,
+    tree_layout: TreeLayout,
+    bottom_tree_indices: core::ops::Range<usize>,
+    padding: SparseTreePadding<TH>,
+    // the builder does not own an encoding, so it is `Sync` independent of the encoding
+    _encoding_type: PhantomData<fn() -> IE>,
+}
+
+impl<'a, PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    HypertreeBuilder<'a, PRF, IE, TH, LOG_LIFETIME>
 where
     PRF::Domain: Into<TH::Domain>,
     PRF::Randomness: Into<IE::Randomness>,
     TH::Parameter: Into<IE::Parameter>,
 {
-    type PublicKey = GeneralizedXMSSPublicKey<TH>;
-
-    type SecretKey = GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>;
-
-    type Signature = GeneralizedXMSSSignature<IE, TH>;
-
-    const LIFETIME: u64 = 1 << LOG_LIFETIME;
-
-    fn key_gen<R: Rng>(
-        rng: &mut R,
-        activation_epoch: usize,
-        num_active_epochs: usize,
-    ) -> (Self::PublicKey, Self::SecretKey) {
-        // checks for `activation_epoch` and `num_active_epochs`
-        assert!(
-            activation_epoch + num_active_epochs <= Self::LIFETIME as usize,
-            "Key gen: `activation_epoch` and `num_active_epochs` are invalid for this lifetime"
+    /// Creates a builder for the sub-trees of a key whose activation
+    /// interval consists of the given range of bottom trees.
+    fn new(
+        prf_key: &'a PRF::Key,
+        parameter: &'a TH::Parameter,
+        tree_layout: TreeLayout,
+        bottom_tree_indices: core::ops::Range<usize>,
+        top_tree_padding_seed: [u8; 32],
+    ) -> Self {
+        let leafs_per_bottom_tree = 1 << tree_layout.log_bottom_tree;
+        let padding = tree_padding::<TH, LOG_LIFETIME>(
+            top_tree_padding_seed,
+            bottom_tree_indices.start * leafs_per_bottom_tree,
+            bottom_tree_indices.len() * leafs_per_bottom_tree,
         );
+        Self {
+            prf_key,
+            parameter,
+            tree_layout,
+            bottom_tree_indices,
+            padding,
+            _encoding_type: PhantomData,
+        }
+    }
 
-        // Note: this implementation uses the top-bottom tree approach, which is as follows:
-        //
-        // We envision that the full Merkle tree into one top tree and `sqrt(LIFETIME)` bottom trees.
-        // The top tree contains the root and the `LOG_LIFETIME/2` layers below it. This top tree has
-        // `sqrt(LIFETIME)` many leafs (but can be sparse and have less). For each leaf that exists,
-        // this leaf is the roof of a bottom tree. Thus, there are at most `sqrt(LIFETIME)` bottom trees,
-        // each having `sqrt(LIFETIME)` leafs. We now restrict increase the activation time to be a
-        // multiple of `sqrt(LIFETIME)` that aligns with these bottom trees, and is at least of length
-        // `2*sqrt(LIFETIME)` so that we have at least two bottom trees.
-        //
-        // Our invariant is that the secret key always stores the full top tree and two consecutive
-        // bottom trees. The secret key can then sign epochs contained in the leafs of these two
-        // consecutive bottom trees, and we provide an update function that re-computes the next bottom
-        // tree and drops the older of the two current ones (function advance_preparation).
-        //
-        // During key generation, we first generate all bottom trees and store their roots, then we
-        // generate the top tree just from their roots.
-
-        // before we do anything, we expand our activation range so that the
-        // top-bottom tree approach can be applied cleanly.
-        let leafs_per_bottom_tree = 1 << (LOG_LIFETIME / 2);
-        let (start_bottom_tree_index, end_bottom_tree_index) =
-            expand_activation_time::<LOG_LIFETIME>(activation_epoch, num_active_epochs);
-        let num_bottom_trees = end_bottom_tree_index - start_bottom_tree_index;
-        assert!(num_bottom_trees >= 2);
-        let activation_epoch = start_bottom_tree_index * leafs_per_bottom_tree;
-        let num_active_epochs = num_bottom_trees * leafs_per_bottom_tree;
+    /// Computes the top tree. Returns it together with the sub-trees of each lower
+    /// level that contain the prepared interval starting at the given bottom tree.
+    fn top_tree(
+        &self,
+        left_bottom_tree_index: usize,
+    ) -> (HashSubTree<TH>, Vec<Vec<HashSubTree<TH>>>) {
+        let keep = left_bottom_tree_index..left_bottom_tree_index + 2;
+        self.subtree(self.tree_layout.num_levels - 1, 0, &keep)
+    }
 
-        // we need a random parameter to be used for the tweakable hash
-        let parameter = TH::rand_parameter(rng);
+    /// Returns the indices of the sub-trees of the given level that contain
+    /// the given range of bottom trees.
+    const fn subtree_indices(
+        &self,
+        level: usize,
+        bottom_tree_indices: &core::ops::Range<usize>,
+    ) -> core::ops::Range<usize> {
+        let shift = level_boundary::<LOG_LIFETIME>(self.tree_layout, level + 1)
+            - self.tree_layout.log_bottom_tree;
+        (bottom_tree_indices.start >> shift)..((bottom_tree_indices.end - 1) >> shift) + 1
+    }
 
-        // we need a PRF key to generate our list of actual secret keys
-        let prf_key = PRF::key_gen(rng);
+    /// Computes the sub-tree with the given index in the given level. Returns it together
+    /// with its descendants in each lower level that intersect the range `keep` of bottom
+    /// trees, ordered by their index. All other descendants are dropped once their root
+    /// is known, so that only few sub-trees are in memory at the same time.
+    fn subtree(
+        &self,
+        level: usize,
+        index: usize,
+        keep: &core::ops::Range<usize>,
+    ) -> (HashSubTree<TH>, Vec<Vec<HashSubTree<TH>>>) {
+        if level == 0 {
+            return (self.bottom_tree(index), Vec::new());
+        }
 
-        // first, we build all bottom trees and keep track of their root. We treat the first two
-        // bottom trees differently, as we want to keep them in our key. While building the bottom
-        // trees, we generate all hash chains using our PRF key, and hash their ends to get the
-        // leafs of our bottom trees. This is done in `bottom_tree_from_prf_key`.
-        let mut roots_of_bottom_trees = Vec::with_capacity(num_bottom_trees);
+        // the children of the sub-tree that are within the activation interval,
+        // which we compute in parallel (see `Parallelism`)
+        let lowest_layer = level_boundary::<LOG_LIFETIME>(self.tree_layout, level);
+        let root_layer = level_boundary::<LOG_LIFETIME>(self.tree_layout, level + 1);
+        let block =
+            (index << (root_layer - lowest_layer))..((index + 1) << (root_layer - lowest_layer));
+        let active = self.subtree_indices(level - 1, &self.bottom_tree_indices);
+        let children = block.start.max(active.start)..block.end.min(active.end);
+        let computed = map_range(children.clone(), |child_index| {
+            let (child, mut descendants) = self.subtree(level - 1, child_index, keep);
+            let root = child.root();
+            let bottom_tree_indices =
+                bottom_tree_range::<LOG_LIFETIME>(self.tree_layout, level - 1, child_index);
+            if bottom_tree_indices.start < keep.end && keep.start < bottom_tree_indices.end {
+                descendants.push(vec![child]);
+            } else {
+                descendants.resize_with(level, Vec::new);
+            }
+            (root, descendants)
+        });
+
+        let mut roots = Vec::with_capacity(computed.len());
+        let mut kept: Vec<Vec<HashSubTree<TH>>> = (0..level).map(|_| Vec::new()).collect();
+        for (root, descendants) in computed {
+            roots.push(root);
+            for (lower_level, subtrees) in descendants.into_iter().enumerate() {
+                kept[lower_level].extend(subtrees);
+            }
+        }
 
-        let left_bottom_tree_index = start_bottom_tree_index;
-        let left_bottom_tree = bottom_tree_from_prf_key::<PRF, IE, TH, LOG_LIFETIME>(
-            &prf_key,
-            left_bottom_tree_index,
-            &parameter,
+        let subtree = HashSubTree::new_subtree_with_padding(
+            &self.padding,
+            LOG_LIFETIME,
+            lowest_layer,
+            root_layer,
+            children.start,
+            self.parameter,
+            roots,
         );
-        roots_of_bottom_trees.push(left_bottom_tree.root());
+        (subtree, kept)
+    }
 
-        let right_bottom_tree_index = start_bottom_tree_index + 1;
-        let right_bottom_tree = bottom_tree_from_prf_key::<PRF, IE, TH, LOG_LIFETIME>(
-            &prf_key,
-            right_bottom_tree_index,
-            &parameter,
-        );
-        roots_of_bottom_trees.push(right_bottom_tree.root());
-
-        // the rest of the bottom trees in parallel
-        roots_of_bottom_trees.extend(
-            (start_bottom_tree_index + 2..end_bottom_tree_index)
-                .into_par_iter()
-                .map(|bottom_tree_index| {
-                    let bottom_tree = bottom_tree_from_prf_key::<PRF, IE, TH, LOG_LIFETIME>(
-                        &prf_key,
-                        bottom_tree_index,
-                        &parameter,
-                    );
-                    bottom_tree.root()
-                })
-                .collect::<Vec<_>>(), // note: roots are in the correct order.
-        );
+    /// Computes the bottom tree with the given index from the PRF key.
+    fn bottom_tree(&self, bottom_tree_index: usize) -> HashSubTree<TH> {
+        let leafs_per_bottom_tree = 1 << self.tree_layout.log_bottom_tree;
 
-        // second, we build the top tree, which has the roots of our bottom trees
-        // as leafs. the root of it will be our public key.
-        let top_tree = HashSubTree::new_top_tree(
-            rng,
-            LOG_LIFETIME,
-            start_bottom_tree_index,
-            &parameter,
-            roots_of_bottom_trees,
-        );
-        let root = top_tree.root();
+        // the range of epochs covered by that bottom tree
+        let epoch_range_start = bottom_tree_index * leafs_per_bottom_tree;
+        let epoch_range_end = epoch_range_start + leafs_per_bottom_tree;
+        let epoch_range = epoch_range_start..epoch_range_end;
 
-        // assemble public key and secret key
-        let pk = GeneralizedXMSSPublicKey { root, parameter };
-        let sk = GeneralizedXMSSSecretKey {
-            prf_key,
-            parameter,
-            activation_epoch,
-            num_active_epochs,
-            top_tree,
-            left_bottom_tree_index,
-            left_bottom_tree,
-            right_bottom_tree,
-            _encoding_type: PhantomData,
-        };
+        // parallelize the chain ends hash computation for each epoch in the interval for that bottom tree
+        let chain_ends_hashes = map_range(epoch_range, |epoch| {
+            leaf_hash::<PRF, IE, TH>(self.prf_key, self.parameter, epoch as u64)
+        });
 
-        (pk, sk)
+        // now that we have the hashes of all chain ends (= leafs of our tree), we can compute the bottom tree
+        HashSubTree::new_subtree_with_padding(
+            &self.padding,
+            LOG_LIFETIME,
+            0,
+            self.tree_layout.log_bottom_tree,
+            epoch_range_start,
+            self.parameter,
+            chain_ends_hashes,
+        )
     }
+}
 
-    fn sign(
-        sk: &Self::SecretKey,
-        epoch: u32,
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    /// Same as `sign`, but the chains are walked as determined by the signing mode.
+    /// See `SigningMode` for the side-channel model.
+    pub fn sign_with_mode(
+        sk: &GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+        epoch: u64,
         message: &[u8; MESSAGE_LENGTH],
-    ) -> Result<Self::Signature, SigningError> {
+        mode: SigningMode,
+    ) -> Result<GeneralizedXMSSSignature<IE, TH>, SigningError> {
         // check that epoch is indeed a valid epoch in the activation range
-
-        assert!(
-            sk.get_activation_interval().contains(&(epoch as u64)),
-            "Signing: key not active during this epoch."
-        );
+        if !sk.get_activation_interval().contains(&epoch) {
+            return Err(SigningError::EpochNotActive { epoch });
+        }
 
         // check that we are already prepared for this epoch
-        assert!(
-            sk.get_prepared_interval().contains(&(epoch as u64)),
-            "Signing: key not yet prepared for this epoch, try calling sk.advance_preparation."
-        );
+        if !sk.get_prepared_interval().contains(&epoch) {
+            return Err(SigningError::EpochNotPrepared { epoch });
+        }
 
         // first component of the signature is the Merkle path that
         // opens the one-time pk for that epoch, where the one-time pk
         // will be recomputed by the verifier from the signature.
-        let leafs_per_bottom_tree = 1 << (LOG_LIFETIME / 2);
-        let boundary_between_bottom_trees =
-            (sk.left_bottom_tree_index * leafs_per_bottom_tree + leafs_per_bottom_tree) as u32;
-        let bottom_tree = if epoch < boundary_between_bottom_trees {
-            &sk.left_bottom_tree
-        } else {
-            &sk.right_bottom_tree
-        };
-        let path = combined_path(&sk.top_tree, bottom_tree, epoch);
-
-        // now, we need to encode our message using the incomparable encoding.
+        // For each level, we take the sub-tree that contains the epoch.
+        let mut subtrees: Vec<&HashSubTree<TH>> = sk
+            .prepared_subtrees
+            .iter()
+            .enumerate()
+            .map(|(level, subtrees)| {
+                let root_layer = level_boundary::<LOG_LIFETIME>(sk.tree_layout, level + 1);
+                let index = (epoch as usize) >> root_layer;
+                subtrees
+                    .iter()
+                    .find(|subtree| subtree.root_index() == index)
+                    .expect("Signing: prepared sub-trees must contain the prepared interval.")
+            })
+            .collect();
+        subtrees.push(&sk.top_tree);
+        let path = combined_path(&subtrees, epoch);
+
+        let (rho, hashes) =
+            Self::encode_and_walk_chains(&sk.prf_key, &sk.parameter, epoch, message, mode)?;
+
+        // assemble the signature: Merkle path, randomness, chain elements
+        Ok(GeneralizedXMSSSignature { path, rho, hashes })
+    }
+
+    /// Helper function to compute the parts of a signature other than the Merkle path:
+    /// the randomness for which the message has a valid codeword, and the hashes of
+    /// the chains for this codeword.
+    fn encode_and_walk_chains(
+        prf_key: &PRF::Key,
+        parameter: &TH::Parameter,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        mode: SigningMode,
+    ) -> Result<(IE::Randomness, Vec<TH::Domain>), SigningError> {
+        // we need to encode our message using the incomparable encoding.
         // we retry until we get a valid codeword, or until we give up.
         let max_tries = IE::MAX_TRIES;
         let mut attempts = 0;
@@ -417,8 +989,8 @@ where
             // which ensures that signing is deterministic. The PRF is applied to the message and the epoch.
             // While the intention is that users of the scheme never call sign twice with the same (epoch, sk) pair,
             // this deterministic approach ensures that calling sign twice is fine, as long as the message stays the same.
-            let curr_rho = PRF::get_randomness(&sk.prf_key, epoch, message, attempts as u64).into();
-            let curr_x = IE::encode(&sk.parameter.into(), message, &curr_rho, epoch);
+            let curr_rho = PRF::get_randomness(prf_key, epoch, message, attempts as u64).into();
+            let curr_x = IE::encode(&(*parameter).into(), message, &curr_rho, epoch);
 
             // check if we have found a valid codeword, and if so, stop searching
             if curr_x.is_ok() {
@@ -449,77 +1021,466 @@ where
             "Encoding is broken: returned too many or too few chunks."
         );
 
-        // In parallel, compute the hash values for each chain based on the codeword `x`.
-        let hashes = (0..num_chains)
-            .into_par_iter()
-            .map(|chain_index| {
-                // get back to the start of the chain from the PRF
-                let start = PRF::get_domain_element(&sk.prf_key, epoch, chain_index as u64).into();
-                // now walk the chain for a number of steps determined by the current chunk of x
-                let steps = x[chain_index] as usize;
-                chain::<TH>(&sk.parameter, epoch, chain_index as u8, 0, steps, &start)
-            })
-            .collect();
+        let hashes = Self::chain_hashes(prf_key, parameter, epoch, &x, mode);
 
-        // assemble the signature: Merkle path, randomness, chain elements
-        Ok(GeneralizedXMSSSignature { path, rho, hashes })
+        Ok((rho, hashes))
     }
 
-    fn verify(
-        pk: &Self::PublicKey,
-        epoch: u32,
+    /// Helper function to compute the hashes of a signature for the given codeword,
+    /// by walking each chain from its start (derived from the PRF key) as determined
+    /// by the codeword. The chains are walked in parallel (see `Parallelism`).
+    fn chain_hashes(
+        prf_key: &PRF::Key,
+        parameter: &TH::Parameter,
+        epoch: u64,
+        x: &[u8],
+        mode: SigningMode,
+    ) -> Vec<TH::Domain> {
+        map_range(0..x.len(), |chain_index| {
+            // get back to the start of the chain from the PRF
+            let start = PRF::get_domain_element(prf_key, epoch, chain_index as u64).into();
+            // now walk the chain for a number of steps determined by the current chunk of x
+            let steps = x[chain_index] as usize;
+            match mode {
+                SigningMode::VariableTime => {
+                    chain::<TH>(parameter, epoch, chain_index as u8, 0, steps, &start)
+                }
+                SigningMode::ConstantTime => chain_constant_time::<TH>(
+                    parameter,
+                    epoch,
+                    chain_index as u8,
+                    0,
+                    steps,
+                    IE::BASE - 1,
+                    &start,
+                ),
+            }
+        })
+    }
+
+    /// Generates a key pair deterministically from a 32-byte seed.
+    ///
+    /// This is the same as `key_gen`, but the parameter of the tweakable hash, the PRF
+    /// key, and the padding of the top tree are derived from the seed, using domain-separated
+    /// expansion with SHAKE128. So a key pair can be recovered from a backup of the seed.
+    ///
+    /// Warning: the seed must be sampled uniformly at random and kept secret, just like
+    /// the secret key. The same seed must not be used for more than one key pair, i.e.,
+    /// also not with a different activation interval.
+    #[must_use]
+    pub fn key_gen_from_seed(
+        seed: &[u8; 32],
+        activation_epoch: usize,
+        num_active_epochs: usize,
+    ) -> (
+        GeneralizedXMSSPublicKey<TH>,
+        GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+    ) {
+        // each component is sampled from its own RNG, seeded with an independent
+        // expansion of the seed. This way, the components do not depend on how many
+        // random bytes the other components consume.
+        let mut parameter_rng =
+            ChaCha20Rng::from_seed(expand_seed(seed, KEY_GEN_SEED_DOMAIN_SEP_PARAMETER));
+        let parameter = TH::rand_parameter(&mut parameter_rng);
+
+        let mut prf_key_rng =
+            ChaCha20Rng::from_seed(expand_seed(seed, KEY_GEN_SEED_DOMAIN_SEP_PRF_KEY));
+        let prf_key = PRF::key_gen(&mut prf_key_rng);
+
+        let top_tree_padding_seed = expand_seed(seed, KEY_GEN_SEED_DOMAIN_SEP_TOP_TREE_PADDING);
+
+        Self::key_gen_from_parts(
+            parameter,
+            prf_key,
+            top_tree_padding_seed,
+            activation_epoch,
+            num_active_epochs,
+            default_tree_layout::<LOG_LIFETIME>(),
+        )
+    }
+
+    /// Verifies a batch of signatures, given as tuples `(pk, epoch, message, sig)`.
+    /// The public keys may be the same or different for each signature.
+    ///
+    /// Signatures are verified in parallel, and so are the chains within each signature
+    /// (see `Parallelism`).
+    ///
+    /// ### Returns
+    /// A `Result` which is:
+    /// * `Ok(())` if all signatures are valid.
+    /// * `Err(BatchVerificationError)` containing the indices of all invalid signatures,
+    ///   together with the reason why each of them has been rejected.
+    pub fn verify_batch(
+        batch: &[GeneralizedXMSSBatchItem<'_, IE, TH>],
+    ) -> Result<(), BatchVerificationError>
+    where
+        IE::Randomness: Sync,
+    {
+        // Note: the results are in the order of the batch, so the failures are sorted by index.
+        let failures: Vec<_> = map_range(0..batch.len(), |index| {
+            let (pk, epoch, message, sig) = &batch[index];
+            Self::verify_checked_impl(pk, *epoch, message, sig, true)
+                .err()
+                .map(|error| (index, error))
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(BatchVerificationError { failures })
+        }
+    }
+
+    /// Verifies a signature, see `SignatureScheme::verify_checked`.
+    /// If `parallel_chains` is set, the chains are walked in parallel.
+    fn verify_checked_impl(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u64,
         message: &[u8; MESSAGE_LENGTH],
-        sig: &Self::Signature,
-    ) -> bool {
-        assert!(
-            (epoch as u64) < Self::LIFETIME,
-            "Generalized XMSS - Verify: Epoch too large."
-        );
+        sig: &GeneralizedXMSSSignature<IE, TH>,
+        parallel_chains: bool,
+    ) -> Result<(), VerificationError> {
+        if epoch >= (1 << LOG_LIFETIME) {
+            return Err(VerificationError::EpochOutOfRange {
+                epoch,
+                lifetime: (1 << LOG_LIFETIME),
+            });
+        }
+
+        // the signature comes from an untrusted source, so we check that it is
+        // well-formed before we use it.
+        Self::check_well_formed(sig)?;
+
+        let chain_ends =
+            Self::chain_ends(pk, epoch, message, &sig.rho, &sig.hashes, parallel_chains)?;
+
+        // this set of chain ends should be a leaf in the Merkle tree
+        // we verify that by checking the Merkle authentication path
+        // Note: the checks above ensure that hash_tree_verify does not panic.
+        if hash_tree_verify(
+            &pk.parameter,
+            &pk.root,
+            epoch,
+            chain_ends.as_slice(),
+            &sig.path,
+        ) {
+            Ok(())
+        } else {
+            Err(VerificationError::RootMismatch)
+        }
+    }
+
+    /// Helper function to recompute the one-time public key of an epoch, i.e., the
+    /// ends of the chains, from the randomness and the chain hashes of a signature.
+    /// There must be one hash per chain. If `parallel_chains` is set, the chains are
+    /// walked in parallel.
+    fn chain_ends(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        rho: &IE::Randomness,
+        hashes: &[TH::Domain],
+        parallel_chains: bool,
+    ) -> Result<Vec<TH::Domain>, VerificationError> {
+        Self::chain_ends_with_tweaks(
+            pk,
+            epoch,
+            message,
+            rho,
+            hashes,
+            parallel_chains,
+            |chain_index, pos_in_chain| TH::chain_tweak(epoch, chain_index, pos_in_chain),
+        )
+    }
+
+    /// Same as `chain_ends`, but the tweaks are given by the caller, e.g., if they
+    /// have been precomputed. The tweak of the step that leads to position
+    /// `pos_in_chain` in the chain with index `chain_index` is
+    /// `chain_tweak(chain_index, pos_in_chain)`.
+    fn chain_ends_with_tweaks<T: Borrow<TH::Tweak>>(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        rho: &IE::Randomness,
+        hashes: &[TH::Domain],
+        parallel_chains: bool,
+        chain_tweak: impl Fn(u8, u8) -> T + Sync,
+    ) -> Result<Vec<TH::Domain>, VerificationError> {
+        let num_chains = IE::DIMENSION;
 
         // first get back the codeword and make sure
         // encoding succeeded with the given randomness.
-        let Ok(x) = IE::encode(&pk.parameter.into(), message, &sig.rho, epoch) else {
-            return false;
+        let Ok(x) = IE::encode(&pk.parameter.into(), message, rho, epoch) else {
+            return Err(VerificationError::EncodingRejected);
         };
 
         // now, we recompute the epoch's one-time public key
         // from the hashes by walking hash chains.
         let chain_length = IE::BASE;
-        let num_chains = IE::DIMENSION;
         assert!(
             x.len() == num_chains,
             "Encoding is broken: returned too many or too few chunks."
         );
-        let mut chain_ends = Vec::with_capacity(num_chains);
-        for (chain_index, xi) in x.iter().enumerate() {
-            // If the signer has already walked x[i] steps, then we need
-            // to walk chain_length - 1 - x[i] steps to reach the end of the chain
-            // Note: by our consistency checks, we have chain_length <= 2^8, so chain_length - 1 fits into u8
-            let steps = (chain_length - 1) as u8 - xi;
-            let start_pos_in_chain = *xi;
-            let start = &sig.hashes[chain_index];
-            let end = chain::<TH>(
-                &pk.parameter,
-                epoch,
-                chain_index as u8,
-                start_pos_in_chain,
-                steps as usize,
-                start,
-            );
-            chain_ends.push(end);
+        let walk_chain = |(chain_index, xi): (usize, &u8)| {
+            // If the signer has already walked x[i] steps, then we need to walk
+            // from position x[i] + 1 up to the end of the chain, i.e., chain_length - 1
+            // Note: by our consistency checks, we have chain_length <= 2^8, so all
+            // positions fit into u8
+            let tweaks = (*xi as usize + 1..chain_length)
+                .map(|pos_in_chain| chain_tweak(chain_index as u8, pos_in_chain as u8));
+            chain_with_tweaks::<TH, _>(&pk.parameter, tweaks, &hashes[chain_index])
+        };
+        let chain_ends = if parallel_chains {
+            map_range(0..x.len(), |chain_index| {
+                walk_chain((chain_index, &x[chain_index]))
+            })
+        } else {
+            x.iter().enumerate().map(walk_chain).collect()
+        };
+        Ok(chain_ends)
+    }
+
+    /// Checks that a signature is well-formed: the Merkle path must have one node
+    /// per layer, and we need one hash per chain.
+    const fn check_well_formed(
+        sig: &GeneralizedXMSSSignature<IE, TH>,
+    ) -> Result<(), VerificationError> {
+        if sig.path.co_path.len() != LOG_LIFETIME {
+            return Err(VerificationError::InvalidPathLength {
+                expected: LOG_LIFETIME,
+                actual: sig.path.co_path.len(),
+            });
         }
+        if sig.hashes.len() != IE::DIMENSION {
+            return Err(VerificationError::InvalidNumberOfHashes {
+                expected: IE::DIMENSION,
+                actual: sig.hashes.len(),
+            });
+        }
+        Ok(())
+    }
 
-        // this set of chain ends should be a leaf in the Merkle tree
-        // we verify that by checking the Merkle authentication path
-        hash_tree_verify(
-            &pk.parameter,
-            &pk.root,
-            epoch,
-            chain_ends.as_slice(),
-            &sig.path,
+    /// Same as `key_gen`, but the Merkle tree of the key is split into `num_levels` levels
+    /// of sub-trees, instead of a top tree and bottom trees. This allows to trade the memory
+    /// of the secret key and the cost of most advances of its preparation against the cost
+    /// of occasional advances, as follows.
+    ///
+    /// The tree is split into levels as evenly as possible: the bottom trees have
+    /// `LOG_LIFETIME / num_levels` layers, and the layers above them are split as evenly as
+    /// possible among the higher levels. The key stores the top tree, two bottom trees, and
+    /// for each level in between, the (at most two) sub-trees that contain the bottom trees.
+    /// Advancing the preparation computes the next bottom tree, and once it is the first
+    /// bottom tree in a sub-tree of a higher level, it computes this sub-tree, which requires
+    /// computing all bottom trees below it. So keys with more levels need less memory and
+    /// shorter bottom trees, but compute most bottom trees twice, and occasionally take longer
+    /// to advance.
+    ///
+    /// The public key and all signatures are the same for any number of levels, given the same
+    /// randomness. Note however that the activation interval is aligned to the (smaller) bottom
+    /// trees, see `key_gen_with_layout`. In particular, `key_gen` is the same as using two levels.
+    ///
+    /// Panics if `num_levels` is less than two, or more than LOG_LIFETIME.
+    pub fn key_gen_with_levels<R: Rng>(
+        rng: &mut R,
+        activation_epoch: usize,
+        num_active_epochs: usize,
+        num_levels: usize,
+    ) -> (
+        GeneralizedXMSSPublicKey<TH>,
+        GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+    ) {
+        let tree_layout = tree_layout_with_levels::<LOG_LIFETIME>(num_levels);
+        Self::key_gen_with_layout(rng, activation_epoch, num_active_epochs, tree_layout)
+    }
+
+    /// Same as `key_gen`, but the Merkle tree of the key is split into levels of sub-trees
+    /// as given by `tree_layout`. The bottom trees have `tree_layout.log_bottom_tree` layers,
+    /// and the remaining layers are split as evenly as possible into the higher levels (see
+    /// `key_gen_with_levels` for the trade-offs of using more than two levels).
+    ///
+    /// The activation interval is expanded to a multiple of the size `C` of a bottom tree,
+    /// aligned to bottom trees, and of length at least `2 * C`. So smaller bottom trees allow
+    /// for a more fine-grained activation interval, and need less memory in the secret key,
+    /// but lead to a larger top tree. The public key and all signatures are the same for any
+    /// tree layout, given the same randomness and the same expanded activation interval.
+    ///
+    /// Panics if the tree layout is invalid, i.e., if there are less than two levels, if
+    /// bottom trees have no layers, or if not every level has at least one layer.
+    pub fn key_gen_with_layout<R: Rng>(
+        rng: &mut R,
+        activation_epoch: usize,
+        num_active_epochs: usize,
+        tree_layout: TreeLayout,
+    ) -> (
+        GeneralizedXMSSPublicKey<TH>,
+        GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+    ) {
+        // we need a random parameter to be used for the tweakable hash
+        let parameter = TH::rand_parameter(rng);
+
+        // we need a PRF key to generate our list of actual secret keys
+        let prf_key = PRF::key_gen(rng);
+
+        // we need a seed for the random padding of the top tree. We keep it in the secret key,
+        // so that the secret key can be rebuilt from its compact representation.
+        let top_tree_padding_seed = rng.random();
+
+        Self::key_gen_from_parts(
+            parameter,
+            prf_key,
+            top_tree_padding_seed,
+            activation_epoch,
+            num_active_epochs,
+            tree_layout,
+        )
+    }
+
+    /// Generates a key pair from the parameter of the tweakable hash, the PRF key, and the
+    /// seed for the padding of the top tree. These determine the key pair completely.
+    fn key_gen_from_parts(
+        parameter: TH::Parameter,
+        prf_key: PRF::Key,
+        top_tree_padding_seed: [u8; 32],
+        activation_epoch: usize,
+        num_active_epochs: usize,
+        tree_layout: TreeLayout,
+    ) -> (
+        GeneralizedXMSSPublicKey<TH>,
+        GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+    ) {
+        // checks for `activation_epoch` and `num_active_epochs`
+        assert!(
+            activation_epoch + num_active_epochs <= 1 << LOG_LIFETIME,
+            "Key gen: `activation_epoch` and `num_active_epochs` are invalid for this lifetime"
+        );
+        assert!(
+            is_valid_tree_layout::<LOG_LIFETIME>(tree_layout),
+            "Key gen: `tree_layout` is invalid for this lifetime"
+        );
+
+        // Note: this implementation generalizes the top-bottom tree approach, which is as follows:
+        //
+        // We envision that the full Merkle tree into one top tree and `C = 2^log_bottom_tree` bottom
+        // trees. The top tree contains the root and the `LOG_LIFETIME - log_bottom_tree` layers below
+        // it. This top tree has `LIFETIME / C` many leafs (but can be sparse and have less). For each
+        // leaf that exists, this leaf is the roof of a bottom tree. Thus, there are at most
+        // `LIFETIME / C` bottom trees, each having `C` leafs. We now restrict increase the activation
+        // time to be a multiple of `C` that aligns with these bottom trees, and is at least of length
+        // `2*C` so that we have at least two bottom trees. By default, `log_bottom_tree` is
+        // `LOG_LIFETIME / 2`, so that `C` is about `sqrt(LIFETIME)`.
+        //
+        // Our invariant is that the secret key always stores the full top tree and two consecutive
+        // bottom trees. The secret key can then sign epochs contained in the leafs of these two
+        // consecutive bottom trees, and we provide an update function that re-computes the next bottom
+        // tree and drops the older of the two current ones (function advance_preparation).
+        //
+        // With more than two levels, the layers below the top tree are split further into levels of
+        // sub-trees (see `level_boundary`), such that the roots of the sub-trees of one level are the
+        // leafs of the sub-trees of the next level. The bottom trees are smaller, and there are more
+        // of them. In addition to the top tree and two bottom trees, the secret key stores the
+        // sub-trees of each level in between that contain the two bottom trees.
+        //
+        // During key generation, we first generate all bottom trees and store their roots, then we
+        // generate the sub-trees of the next level just from their roots, and so on up to the top tree.
+
+        // before we do anything, we expand our activation range so that the
+        // top-bottom tree approach can be applied cleanly.
+        let leafs_per_bottom_tree = 1 << tree_layout.log_bottom_tree;
+        let (start_bottom_tree_index, end_bottom_tree_index) = expand_activation_time::<LOG_LIFETIME>(
+            tree_layout.log_bottom_tree,
+            activation_epoch,
+            num_active_epochs,
+        );
+        assert!(end_bottom_tree_index - start_bottom_tree_index >= 2);
+        let activation_epoch = start_bottom_tree_index * leafs_per_bottom_tree;
+        let num_active_epochs =
+            (end_bottom_tree_index - start_bottom_tree_index) * leafs_per_bottom_tree;
+
+        // we build all bottom trees, and then the sub-trees of the higher levels from their roots.
+        // the root of the top tree will be our public key.
+        let builder = HypertreeBuilder::<PRF, IE, TH, LOG_LIFETIME>::new(
+            &prf_key,
+            &parameter,
+            tree_layout,
+            start_bottom_tree_index..end_bottom_tree_index,
+            top_tree_padding_seed,
+        );
+        let (top_tree, prepared_subtrees) = builder.top_tree(start_bottom_tree_index);
+        let root = top_tree.root();
+
+        // assemble public key and secret key
+        let pk = GeneralizedXMSSPublicKey { root, parameter };
+        let sk = GeneralizedXMSSSecretKey {
+            format_version: SecretKeyFormatVersion,
+            prf_key,
+            parameter,
+            activation_epoch,
+            num_active_epochs,
+            tree_layout,
+            top_tree,
+            prepared_subtrees,
+            top_tree_padding_seed,
+            _encoding_type: PhantomData,
+        };
+
+        (pk, sk)
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> SignatureScheme for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    type PublicKey = GeneralizedXMSSPublicKey<TH>;
+
+    type SecretKey = GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>;
+
+    type Signature = GeneralizedXMSSSignature<IE, TH>;
+
+    const LIFETIME: u64 = 1 << LOG_LIFETIME;
+
+    fn key_gen<R: Rng>(
+        rng: &mut R,
+        activation_epoch: usize,
+        num_active_epochs: usize,
+    ) -> (Self::PublicKey, Self::SecretKey) {
+        // this is the top-bottom tree approach, i.e., two levels
+        Self::key_gen_with_layout(
+            rng,
+            activation_epoch,
+            num_active_epochs,
+            default_tree_layout::<LOG_LIFETIME>(),
         )
     }
 
+    fn sign(
+        sk: &Self::SecretKey,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Self::Signature, SigningError> {
+        Self::sign_with_mode(sk, epoch, message, SigningMode::VariableTime)
+    }
+
+    fn verify_checked(
+        pk: &Self::PublicKey,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &Self::Signature,
+    ) -> Result<(), VerificationError> {
+        // for a single signature, walking the chains sequentially is faster
+        Self::verify_checked_impl(pk, epoch, message, sig, false)
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         // we check consistency of all internally used components
@@ -540,14 +1501,69 @@ where
             "Generalized XMSS: Encoding dimension too large, must be at most 2^8"
         );
 
-        // LOG_LIFETIME needs to be even, so that we can use the top-bottom tree approach
+        // epochs and the lifetime are given as u64
         assert!(
-            LOG_LIFETIME.is_multiple_of(2),
-            "Generalized XMSS: LOG_LIFETIME must be multiple of two"
+            LOG_LIFETIME < 64,
+            "Generalized XMSS: LOG_LIFETIME must be less than 64"
+        );
+
+        // LOG_LIFETIME needs to be at least 2, so that we can use the top-bottom tree approach
+        assert!(
+            is_valid_tree_layout::<LOG_LIFETIME>(default_tree_layout::<LOG_LIFETIME>()),
+            "Generalized XMSS: LOG_LIFETIME must be at least two"
+        );
+
+        // the tweakable hash must be able to encode the tweaks for all epochs of the lifetime,
+        // e.g., they must fit into the field elements of the tweak. Hashing encodes the tweak,
+        // so we hash once with the largest chain tweak, and with the largest position of each
+        // level of the tree.
+        let mut rng = rand::rng();
+        let parameter = TH::rand_parameter(&mut rng);
+        let domain = TH::rand_domain(&mut rng);
+        let last_epoch = Self::LIFETIME - 1;
+        TH::apply(
+            &parameter,
+            &TH::chain_tweak(last_epoch, (IE::DIMENSION - 1) as u8, (IE::BASE - 1) as u8),
+            &[domain],
         );
+        for level in 0..=LOG_LIFETIME {
+            TH::apply(
+                &parameter,
+                &TH::tree_tweak(level as u8, last_epoch >> level),
+                &[domain, domain],
+            );
+        }
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash + MessagePreHash,
+    const LOG_LIFETIME: usize,
+> PreHashSignatureScheme for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    fn pre_hash(context: &[u8], message: &[u8]) -> [u8; MESSAGE_LENGTH] {
+        // we pre-hash with the same primitive as the tweakable hash
+        TH::pre_hash(context, message)
     }
 }
 
+/// Aggregation of signatures from many signers for the same epoch and message
+pub mod aggregation;
+/// Constraints of verification for arithmetic circuits, with a reference constraint checker
+pub mod circuit;
+/// Derivation of many key pairs from a single master seed
+pub mod derivation;
+// Timing test for walking the chains in the signing modes, see README
+#[cfg(all(test, feature = "dudect"))]
+mod dudect;
+/// Canonical, versioned byte encoding of public keys and signatures
+pub mod encoding;
 /// Instantiations of the generalized XMSS signature scheme based on Poseidon2
 pub mod instantiations_poseidon;
 /// Instantiations of the generalized XMSS signature scheme based on the
@@ -555,20 +1571,40 @@ pub mod instantiations_poseidon;
 pub mod instantiations_poseidon_top_level;
 /// Instantiations of the generalized XMSS signature scheme based on SHA
 pub mod instantiations_sha;
+/// Secret key handle that advances the prepared interval in the background
+#[cfg(feature = "std")]
+pub mod managed;
+/// Rotation to successor keys with consecutive activation intervals, via handover certificates
+#[cfg(feature = "std")]
+pub mod rotation;
+/// Signatures of a single signer for several epochs, sharing one Merkle multi-opening
+pub mod signature_log;
+/// SSZ serialization and `hash_tree_root` of public keys and signatures
+#[cfg(feature = "std")]
+pub mod ssz;
+/// Traces of verification, used as witnesses for proofs of verification
+#[cfg(feature = "trace")]
+pub mod trace;
+/// Secret keys with a Merkle tree traversal, which store logarithmically many nodes
+pub mod traversal;
 
 #[cfg(test)]
 mod tests {
     use crate::{
         inc_encoding::{basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding},
+        parallelism::Parallelism,
         signature::test_templates::test_signature_scheme_correctness,
         symmetric::{
             message_hash::{
                 MessageHash,
-                poseidon::PoseidonMessageHashW1,
+                poseidon::{PoseidonMessageHash, PoseidonMessageHashW1},
                 sha::{ShaMessageHash, ShaMessageHash192x3},
             },
             prf::{sha::ShaPRF, shake_to_field::ShakePRFtoF},
-            tweak_hash::{poseidon::PoseidonTweakW1L5, sha::ShaTweak192192},
+            tweak_hash::{
+                poseidon::{PoseidonTweakHash, PoseidonTweakW1L5},
+                sha::ShaTweak192192,
+            },
         },
     };
 
@@ -644,73 +1680,321 @@ mod tests {
     }
 
     #[test]
-    pub fn test_target_sum_poseidon() {
+    pub fn test_odd_lifetime() {
         // Note: do not use these parameters, they are just for testing
-        type PRF = ShakePRFtoF<7, 5>;
-        type TH = PoseidonTweakW1L5;
-        type MH = PoseidonMessageHashW1;
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
         const BASE: usize = MH::BASE;
         const NUM_CHUNKS: usize = MH::DIMENSION;
         const MAX_CHUNK_VALUE: usize = BASE - 1;
         const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
         type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
-        const LOG_LIFETIME: usize = 6;
+        const LOG_LIFETIME: usize = 7;
         type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        type SK = <Sig as SignatureScheme>::SecretKey;
 
         Sig::internal_consistency_check();
 
-        test_signature_scheme_correctness::<Sig>(2, 0, Sig::LIFETIME as usize);
-        test_signature_scheme_correctness::<Sig>(19, 0, Sig::LIFETIME as usize);
+        // by default, bottom trees have 3 layers and the top tree has 4 layers
         test_signature_scheme_correctness::<Sig>(0, 0, Sig::LIFETIME as usize);
-        test_signature_scheme_correctness::<Sig>(11, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(77, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(19, 13, 20);
+        let (_, sk) = Sig::key_gen(&mut rand::rng(), 13, 20);
+        assert_eq!(sk.get_activation_interval(), 8..40);
+        assert_eq!(sk.get_prepared_interval(), 8..24);
+
+        // keys with other tree layouts have the same public key and signatures, if they are
+        // generated from the same randomness and for the same (expanded) activation interval
+        let message = [0x42; MESSAGE_LENGTH];
+        let seed = rand::rng().random();
+        let key_gen = |log_bottom_tree, num_levels| {
+            let tree_layout = TreeLayout {
+                log_bottom_tree,
+                num_levels,
+            };
+            let mut rng = ChaCha20Rng::from_seed(seed);
+            Sig::key_gen_with_layout(&mut rng, 32, 64, tree_layout)
+        };
+        let (pk, sk) = key_gen(LOG_LIFETIME / 2, 2);
+        for (log_bottom_tree, num_levels) in [(4, 2), (1, 2), (2, 3), (1, 7)] {
+            let (pk_layout, mut sk_layout) = key_gen(log_bottom_tree, num_levels);
+            assert_eq!(pk_layout.root, pk.root);
+            assert_eq!(sk_layout.get_activation_interval(), 32..96);
+            let prepared_interval = sk_layout.get_prepared_interval();
+            assert_eq!(prepared_interval.start, 32);
+            assert_eq!(prepared_interval.end, 32 + (2 << log_bottom_tree));
+
+            let mut sk = SK::from_compact(sk.to_compact()).unwrap();
+            for epoch in [32, 33, 50, 71, 95] {
+                sk.prepare_for_epoch(epoch).unwrap();
+                sk_layout.prepare_for_epoch(epoch).unwrap();
+                let signature = Sig::sign(&sk, epoch, &message).unwrap();
+                let signature_layout = Sig::sign(&sk_layout, epoch, &message).unwrap();
+                assert!(Sig::verify(&pk, epoch, &message, &signature_layout));
+                assert_eq!(signature.path.co_path, signature_layout.path.co_path);
+            }
+        }
     }
 
     #[test]
-    pub fn test_deterministic() {
+    pub fn test_lifetime_beyond_32_bits() {
         // Note: do not use these parameters, they are just for testing
-        type PRF = ShakePRFtoF<7, 5>;
-        type TH = PoseidonTweakW1L5;
-        type MH = PoseidonMessageHashW1;
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
         const BASE: usize = MH::BASE;
         const NUM_CHUNKS: usize = MH::DIMENSION;
         const MAX_CHUNK_VALUE: usize = BASE - 1;
         const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
         type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
-        const LOG_LIFETIME: usize = 6;
+        const LOG_LIFETIME: usize = 40;
         type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
 
         Sig::internal_consistency_check();
 
-        // we sign the same (epoch, message) pair twice (which users of this code should not do)
-        // and ensure that it produces the same randomness for the signature.
-        let mut rng = rand::rng();
-        let (_pk, mut sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
-        let message = rng.random();
-        let epoch = 29;
-
-        // prepare key for epoch
-        let mut iterations = 0;
-        while !sk.get_prepared_interval().contains(&(epoch as u64)) && iterations < epoch {
-            sk.advance_preparation();
-            iterations += 1;
-        }
-        assert!(
-            sk.get_prepared_interval().contains(&(epoch as u64)),
-            "Did not even try signing, failed to advance key preparation to desired epoch {:?}.",
-            epoch
+        // we use small bottom trees, so that we only compute a few leafs at the end of the lifetime
+        let tree_layout = TreeLayout {
+            log_bottom_tree: 3,
+            num_levels: 2,
+        };
+        let (pk, mut sk) =
+            Sig::key_gen_with_layout(&mut rand::rng(), (1 << LOG_LIFETIME) - 37, 20, tree_layout);
+        let activation_interval = sk.get_activation_interval();
+        assert_eq!(
+            activation_interval,
+            (1 << LOG_LIFETIME) - 40..(1 << LOG_LIFETIME) - 16
         );
 
-        let sig1 = Sig::sign(&sk, epoch, &message).unwrap();
-        let sig2 = Sig::sign(&sk, epoch, &message).unwrap();
-        let rho1 = sig1.rho;
-        let rho2 = sig2.rho;
-        assert_eq!(rho1, rho2);
+        let message = [0x42; MESSAGE_LENGTH];
+        for epoch in activation_interval {
+            sk.prepare_for_epoch(epoch).unwrap();
+            let signature = Sig::sign(&sk, epoch, &message).unwrap();
+            assert!(Sig::verify(&pk, epoch, &message, &signature));
+
+            // the signature is bound to all bits of the epoch
+            assert!(!Sig::verify(&pk, epoch - (1 << 32), &message, &signature));
+            assert!(!Sig::verify(
+                &pk,
+                epoch + (1 << LOG_LIFETIME),
+                &message,
+                &signature
+            ));
+        }
     }
 
     #[test]
-    pub fn test_large_base_sha() {
+    pub fn test_lifetime_beyond_32_bits_poseidon() {
         // Note: do not use these parameters, they are just for testing
-        type PRF = ShaPRF<24, 8>;
+        type PRF = ShakePRFtoF<7, 5>;
+        // three field elements for tweaks, so that tweaks for large epochs fit
+        type TH = PoseidonTweakHash<5, 7, 3, 9, 163>;
+        type MH = PoseidonMessageHash<5, 5, 5, 163, 2, 3, 9>;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 40;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        Sig::internal_consistency_check();
+
+        let tree_layout = TreeLayout {
+            log_bottom_tree: 3,
+            num_levels: 3,
+        };
+        let activation_epoch = (1 << 36) + 5;
+        let (pk, mut sk) =
+            Sig::key_gen_with_layout(&mut rand::rng(), activation_epoch, 16, tree_layout);
+        let activation_interval = sk.get_activation_interval();
+        assert_eq!(activation_interval, 1 << 36..(1 << 36) + 24);
+
+        let message = [0x42; MESSAGE_LENGTH];
+        for epoch in activation_interval {
+            sk.prepare_for_epoch(epoch).unwrap();
+            let signature = Sig::sign(&sk, epoch, &message).unwrap();
+            assert!(Sig::verify(&pk, epoch, &message, &signature));
+            assert!(!Sig::verify(&pk, epoch - (1 << 32), &message, &signature));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough field elements to encode the tweak")]
+    pub fn test_lifetime_beyond_32_bits_poseidon_tweak_too_short() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShakePRFtoF<7, 5>;
+        // two field elements for tweaks do not fit chain tweaks for epochs of 40 bits
+        type TH = PoseidonTweakHash<5, 7, 2, 9, 163>;
+        type MH = PoseidonMessageHash<5, 5, 5, 163, 2, 3, 9>;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 40;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        Sig::internal_consistency_check();
+    }
+
+    #[test]
+    pub fn test_target_sum_poseidon() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShakePRFtoF<7, 5>;
+        type TH = PoseidonTweakW1L5;
+        type MH = PoseidonMessageHashW1;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        Sig::internal_consistency_check();
+
+        test_signature_scheme_correctness::<Sig>(2, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(19, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(0, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(11, 0, Sig::LIFETIME as usize);
+    }
+
+    #[test]
+    fn test_sign_constant_time() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShakePRFtoF<7, 5>;
+        type TH = PoseidonTweakW1L5;
+        type MH = PoseidonMessageHashW1;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        // 48 chunks of base 16
+        type ShaSig = GeneralizedXMSSSignatureScheme<
+            ShaPRF<24, 24>,
+            TargetSumEncoding<ShaMessageHash192x3, 360>,
+            ShaTweak192192,
+            LOG_LIFETIME,
+        >;
+
+        // both modes produce the same signature, as signing is deterministic
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 16);
+        for epoch in [0, 7, 15] {
+            let message = rng.random();
+            let sig = Sig::sign_with_mode(&sk, epoch, &message, SigningMode::ConstantTime).unwrap();
+            assert!(Sig::verify(&pk, epoch, &message, &sig));
+
+            let expected = Sig::sign(&sk, epoch, &message).unwrap();
+            assert_eq!(sig.hashes, expected.hashes);
+            assert_eq!(sig.rho, expected.rho);
+            assert_eq!(sig.path.co_path, expected.path.co_path);
+        }
+
+        // the same holds for hashes given by bytes
+        let (pk, sk) = ShaSig::key_gen(&mut rng, 0, 16);
+        let message = rng.random();
+        let sig = ShaSig::sign_with_mode(&sk, 3, &message, SigningMode::ConstantTime).unwrap();
+        assert!(ShaSig::verify(&pk, 3, &message, &sig));
+        assert_eq!(sig.hashes, ShaSig::sign(&sk, 3, &message).unwrap().hashes);
+    }
+
+    #[test]
+    fn test_parallelism_settings() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const EXPECTED_SUM: usize = MH::DIMENSION * (MH::BASE - 1) / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let settings = [
+            #[cfg(feature = "rayon")]
+            Parallelism::Global,
+            #[cfg(feature = "rayon")]
+            Parallelism::Pool(std::sync::Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(2)
+                    .build()
+                    .unwrap(),
+            )),
+            Parallelism::Sequential,
+        ];
+
+        // all settings give the same keys and signatures
+        let seed = [7; 32];
+        let message = [3; MESSAGE_LENGTH];
+        let (expected_pk, expected_sk) = Sig::key_gen_from_seed(&seed, 0, 32);
+        let expected_sig = Sig::sign(&expected_sk, 5, &message).unwrap();
+        for parallelism in settings {
+            parallelism.run(|| {
+                let (pk, mut sk) = Sig::key_gen_from_seed(&seed, 0, 32);
+                assert_eq!(pk.root, expected_pk.root);
+
+                let sig = Sig::sign(&sk, 5, &message).unwrap();
+                assert_eq!(sig.hashes, expected_sig.hashes);
+                assert_eq!(sig.path.co_path, expected_sig.path.co_path);
+
+                sk.prepare_for_epoch(20).unwrap();
+                let sig_20 = Sig::sign(&sk, 20, &message).unwrap();
+                let batch = [(&pk, 5, &message, &sig), (&pk, 20, &message, &sig_20)];
+                assert!(Sig::verify_batch(&batch).is_ok());
+            });
+        }
+    }
+
+    #[test]
+    pub fn test_deterministic() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShakePRFtoF<7, 5>;
+        type TH = PoseidonTweakW1L5;
+        type MH = PoseidonMessageHashW1;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        Sig::internal_consistency_check();
+
+        // we sign the same (epoch, message) pair twice (which users of this code should not do)
+        // and ensure that it produces the same randomness for the signature.
+        let mut rng = rand::rng();
+        let (_pk, mut sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let message = rng.random();
+        let epoch = 29;
+
+        // prepare key for epoch
+        let mut iterations = 0;
+        while !sk.get_prepared_interval().contains(&epoch) && iterations < epoch {
+            sk.advance_preparation();
+            iterations += 1;
+        }
+        assert!(
+            sk.get_prepared_interval().contains(&epoch),
+            "Did not even try signing, failed to advance key preparation to desired epoch {:?}.",
+            epoch
+        );
+
+        let sig1 = Sig::sign(&sk, epoch, &message).unwrap();
+        let sig2 = Sig::sign(&sk, epoch, &message).unwrap();
+        let rho1 = sig1.rho;
+        let rho2 = sig2.rho;
+        assert_eq!(rho1, rho2);
+    }
+
+    #[test]
+    pub fn test_large_base_sha() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 8>;
         type TH = ShaTweak192192;
 
         // use chunk size 8
@@ -745,36 +2029,758 @@ mod tests {
         test_signature_scheme_correctness::<Sig>(19, 0, Sig::LIFETIME as usize);
     }
 
+    #[test]
+    pub fn test_verify_rejects_malformed_signatures() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let message = rng.random();
+        let epoch = 5;
+        let mut sig = Sig::sign(&sk, epoch, &message).unwrap();
+        assert!(Sig::verify_checked(&pk, epoch, &message, &sig).is_ok());
+
+        // epoch beyond the lifetime must not panic
+        assert!(matches!(
+            Sig::verify_checked(&pk, Sig::LIFETIME, &message, &sig),
+            Err(VerificationError::EpochOutOfRange { .. })
+        ));
+        assert!(!Sig::verify(&pk, u64::MAX, &message, &sig));
+
+        // a different message is either rejected by the encoding, or leads to a different root
+        let other_message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| message[i] ^ 0x01);
+        let encoding_accepts = IE::encode(&pk.parameter, &other_message, &sig.rho, epoch).is_ok();
+        let result = Sig::verify_checked(&pk, epoch, &other_message, &sig);
+        if encoding_accepts {
+            assert!(matches!(result, Err(VerificationError::RootMismatch)));
+        } else {
+            assert!(matches!(result, Err(VerificationError::EncodingRejected)));
+        }
+
+        // a different epoch leads to a different root
+        let other_epoch = epoch + 1;
+        if IE::encode(&pk.parameter, &message, &sig.rho, other_epoch).is_ok() {
+            assert!(matches!(
+                Sig::verify_checked(&pk, other_epoch, &message, &sig),
+                Err(VerificationError::RootMismatch)
+            ));
+        }
+
+        // truncated chain hashes must not panic
+        let last_hash = sig.hashes.pop().unwrap();
+        assert!(matches!(
+            Sig::verify_checked(&pk, epoch, &message, &sig),
+            Err(VerificationError::InvalidNumberOfHashes { expected, actual })
+                if expected == IE::DIMENSION && actual == IE::DIMENSION - 1
+        ));
+        sig.hashes.push(last_hash);
+
+        // truncated and extended co-paths must not panic
+        let last_node = sig.path.co_path.pop().unwrap();
+        assert!(matches!(
+            Sig::verify_checked(&pk, epoch, &message, &sig),
+            Err(VerificationError::InvalidPathLength { expected, actual })
+                if expected == LOG_LIFETIME && actual == LOG_LIFETIME - 1
+        ));
+        sig.path.co_path.extend([last_node, last_node]);
+        assert!(matches!(
+            Sig::verify_checked(&pk, epoch, &message, &sig),
+            Err(VerificationError::InvalidPathLength { .. })
+        ));
+        sig.path.co_path.pop();
+
+        // a modified chain hash leads to a different root
+        sig.hashes[0][0] ^= 0x01;
+        assert!(matches!(
+            Sig::verify_checked(&pk, epoch, &message, &sig),
+            Err(VerificationError::RootMismatch)
+        ));
+        sig.hashes[0][0] ^= 0x01;
+
+        // and undoing all modifications gives a valid signature again
+        assert!(Sig::verify_checked(&pk, epoch, &message, &sig).is_ok());
+    }
+
+    #[test]
+    pub fn test_prepare_for_epoch() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        // copies a secret key via serialization, so that we can
+        // prepare the same key in two different ways.
+        fn copy_key(
+            sk: &<Sig as SignatureScheme>::SecretKey,
+        ) -> <Sig as SignatureScheme>::SecretKey {
+            use bincode::serde::{decode_from_slice, encode_to_vec};
+            let config = bincode::config::standard();
+            let bytes = encode_to_vec(sk, config).unwrap();
+            decode_from_slice(&bytes, config).unwrap().0
+        }
+
+        let mut rng = rand::rng();
+        let message = rng.random();
+
+        // bottom trees have 8 leafs. For each target epoch, we expect the prepared interval to
+        // start at the bottom tree of the epoch if possible, and signatures to be identical
+        // to the ones obtained when advancing step by step.
+        for (num_active_epochs, epochs_and_expected_intervals) in [
+            (
+                64,
+                vec![(3, 0..16), (20, 16..32), (35, 32..48), (60, 48..64)],
+            ),
+            // here, the epoch is in the last bottom tree, so one tree can be kept
+            (24, vec![(20, 8..24)]),
+        ] {
+            let (pk, mut sk) = Sig::key_gen(&mut rng, 0, num_active_epochs);
+            let mut stepwise_sk = copy_key(&sk);
+            for (epoch, expected_interval) in epochs_and_expected_intervals {
+                sk.prepare_for_epoch(epoch).unwrap();
+                assert_eq!(sk.get_prepared_interval(), expected_interval);
+
+                while !stepwise_sk.get_prepared_interval().contains(&epoch) {
+                    stepwise_sk.advance_preparation();
+                }
+
+                let signature = Sig::sign(&sk, epoch, &message).unwrap();
+                let stepwise_signature = Sig::sign(&stepwise_sk, epoch, &message).unwrap();
+                assert!(Sig::verify(&pk, epoch, &message, &signature));
+                assert_eq!(signature.path.co_path, stepwise_signature.path.co_path);
+                assert_eq!(signature.hashes, stepwise_signature.hashes);
+            }
+        }
+
+        // epochs outside the activation interval or behind the prepared interval are rejected,
+        // and the key is not modified.
+        let (_pk, mut sk) = Sig::key_gen(&mut rng, 16, 32);
+        sk.prepare_for_epoch(40).unwrap();
+        assert_eq!(sk.get_prepared_interval(), 32..48);
+        assert!(matches!(
+            sk.prepare_for_epoch(48),
+            Err(PreparationError::EpochNotActive { epoch: 48, .. })
+        ));
+        assert!(matches!(
+            sk.prepare_for_epoch(8),
+            Err(PreparationError::EpochNotActive { epoch: 8, .. })
+        ));
+        assert!(matches!(
+            sk.prepare_for_epoch(20),
+            Err(PreparationError::EpochBehindPreparedInterval { epoch: 20, .. })
+        ));
+        assert_eq!(sk.get_prepared_interval(), 32..48);
+
+        // signing for such epochs fails
+        assert!(matches!(
+            Sig::sign(&sk, 8, &message),
+            Err(SigningError::EpochNotActive { epoch: 8 })
+        ));
+        assert!(matches!(
+            Sig::sign(&sk, 20, &message),
+            Err(SigningError::EpochNotPrepared { epoch: 20 })
+        ));
+    }
+
+    #[test]
+    fn test_secret_key_zeroized() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let mut rng = rand::rng();
+        let (_pk, sk) = Sig::key_gen(&mut rng, 0, 32);
+        let mut sk = Box::new(sk);
+
+        // the Debug output does not contain the PRF key
+        let debug = format!("{sk:?}");
+        assert!(debug.contains("prf_key: \"<redacted>\""));
+        assert!(!debug.contains(&format!("{:?}", *sk.prf_key)));
+        let debug = format!("{:?}", sk.to_compact());
+        assert!(debug.contains("prf_key: \"<redacted>\""));
+        assert!(!debug.contains(&format!("{:?}", *sk.prf_key)));
+
+        // advancing the preparation wipes the old left bottom tree
+        let buffers = sk.prepared_subtrees[0][0].node_buffers();
+        let wiped = crate::test_allocator::wiped_on_free(&buffers, || sk.advance_preparation());
+        assert_eq!(sk.get_prepared_interval(), 8..24);
+        assert!(wiped.iter().all(|wiped| *wiped == Some(true)));
+
+        // dropping the key wipes the PRF key and all trees
+        let mut buffers = vec![(sk.prf_key.as_ptr(), sk.prf_key.len())];
+        buffers.extend(sk.top_tree.node_buffers());
+        for subtree in sk.prepared_subtrees.iter().flatten() {
+            buffers.extend(subtree.node_buffers());
+        }
+        let wiped = crate::test_allocator::wiped_on_free(&buffers, || drop(sk));
+        assert!(wiped.iter().all(|wiped| *wiped == Some(true)));
+    }
+
+    #[test]
+    pub fn test_secret_key_introspection() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        // bottom trees have 8 leafs, so the activation interval is expanded to [8, 32)
+        let mut rng = rand::rng();
+        let (_pk, mut sk) = Sig::key_gen(&mut rng, 10, 20);
+        assert_eq!(sk.get_activation_interval(), 8..32);
+        assert_eq!(sk.expiration_epoch(), 32);
+        assert_eq!(sk.remaining_epochs(0), 24);
+        assert_eq!(sk.remaining_epochs(13), 19);
+        assert_eq!(sk.remaining_epochs(32), 0);
+        assert_eq!(sk.remaining_epochs(40), 0);
+        assert_eq!(sk.next_preparation_epoch(), Some(24));
+
+        // the key stores at least all leafs of the two bottom trees and of the top tree
+        let domain_size = std::mem::size_of::<<TH as TweakableHash>::Domain>();
+        let tree_memory_bytes = sk.tree_memory_bytes();
+        assert!(tree_memory_bytes >= (2 * 8 + 3) * domain_size);
+        assert!(tree_memory_bytes.is_multiple_of(domain_size));
+
+        let metrics = sk.metrics(13);
+        assert_eq!(metrics.activation_start, 8);
+        assert_eq!(metrics.expiration_epoch, 32);
+        assert_eq!((metrics.prepared_start, metrics.prepared_end), (8, 24));
+        assert_eq!(metrics.remaining_epochs, 19);
+        assert_eq!(metrics.remaining_prepared_epochs, 11);
+        assert_eq!(metrics.next_preparation_epoch, Some(24));
+        assert_eq!(metrics.tree_memory_bytes, tree_memory_bytes as u64);
+
+        // after advancing, the prepared interval reaches the end of the activation interval
+        sk.advance_preparation();
+        assert_eq!(sk.get_prepared_interval(), 16..32);
+        assert_eq!(sk.next_preparation_epoch(), None);
+        assert_eq!(sk.tree_memory_bytes(), tree_memory_bytes);
+        assert_eq!(sk.metrics(20).remaining_prepared_epochs, 12);
+    }
+
+    #[test]
+    pub fn test_compact_secret_key() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        type SK = <Sig as SignatureScheme>::SecretKey;
+
+        use bincode::serde::encode_to_vec;
+        let config = bincode::config::standard();
+
+        // we use a sparse activation interval and move the prepared interval,
+        // so that the top tree needs padding and the window is not at the start.
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::key_gen(&mut rng, 13, 30);
+        sk.prepare_for_epoch(30).unwrap();
+
+        let compact = sk.to_compact();
+        let compact_bytes = encode_to_vec(&compact, config).unwrap();
+        let full_bytes = encode_to_vec(&sk, config).unwrap();
+        assert!(compact_bytes.len() < 100);
+        assert!(compact_bytes.len() < full_bytes.len());
+
+        // both formats start with the version, and other versions are rejected
+        assert_eq!(compact_bytes[0], SECRET_KEY_FORMAT_VERSION);
+        assert_eq!(full_bytes[0], SECRET_KEY_FORMAT_VERSION);
+        let mut other_version = compact_bytes;
+        other_version[0] += 1;
+        assert!(
+            bincode::serde::decode_from_slice::<GeneralizedXMSSCompactSecretKey<PRF, TH>, _>(
+                &other_version,
+                config
+            )
+            .is_err()
+        );
+        let mut other_version = full_bytes.clone();
+        other_version[0] += 1;
+        assert!(bincode::serde::decode_from_slice::<SK, _>(&other_version, config).is_err());
+
+        // the restored key is identical to the original one
+        let restored = SK::from_compact(compact).unwrap();
+        assert_eq!(encode_to_vec(&restored, config).unwrap(), full_bytes);
+
+        let message = rng.random();
+        let signature = Sig::sign(&restored, 30, &message).unwrap();
+        assert!(Sig::verify(&pk, 30, &message, &signature));
+
+        // invalid activation intervals and prepared intervals are rejected
+        let mut compact = sk.to_compact();
+        compact.activation_epoch += 1;
+        assert!(matches!(
+            SK::from_compact(compact),
+            Err(CompactSecretKeyError::InvalidActivationInterval { .. })
+        ));
+        let mut compact = sk.to_compact();
+        compact.num_active_epochs = 8;
+        assert!(matches!(
+            SK::from_compact(compact),
+            Err(CompactSecretKeyError::InvalidActivationInterval { .. })
+        ));
+        let mut compact = sk.to_compact();
+        compact.num_active_epochs = 1 << LOG_LIFETIME;
+        assert!(matches!(
+            SK::from_compact(compact),
+            Err(CompactSecretKeyError::InvalidActivationInterval { .. })
+        ));
+        let mut compact = sk.to_compact();
+        compact.tree_layout.num_levels = 1;
+        assert!(matches!(
+            SK::from_compact(compact),
+            Err(CompactSecretKeyError::InvalidTreeLayout { .. })
+        ));
+        let mut compact = sk.to_compact();
+        compact.tree_layout.log_bottom_tree = LOG_LIFETIME;
+        assert!(matches!(
+            SK::from_compact(compact),
+            Err(CompactSecretKeyError::InvalidTreeLayout { .. })
+        ));
+        let mut compact = sk.to_compact();
+        compact.left_bottom_tree_index = 5;
+        assert!(matches!(
+            SK::from_compact(compact),
+            Err(CompactSecretKeyError::InvalidPreparedInterval {
+                left_bottom_tree_index: 5
+            })
+        ));
+    }
+
+    #[test]
+    pub fn test_key_gen_from_seed() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+
+        // known answers, these must never change as keys are recovered from seeds
+        let (pk, sk) = Sig::key_gen_from_seed(&seed, 0, 1 << LOG_LIFETIME);
+        assert_eq!(
+            pk.parameter,
+            [
+                0x0d, 0xc6, 0xdf, 0xfe, 0x78, 0x88, 0xa6, 0x88, 0x7d, 0x00, 0x70, 0x93, 0xd7, 0x38,
+                0x63, 0x6d, 0x02, 0xba, 0x89, 0x96, 0x5f, 0x47, 0x06, 0x28
+            ]
+        );
+        assert_eq!(
+            *sk.prf_key,
+            [
+                0x7c, 0x1a, 0xbb, 0x41, 0x0a, 0x41, 0x5d, 0x82, 0x72, 0x90, 0x35, 0x81, 0x18, 0x9a,
+                0x2c, 0xbd, 0x1a, 0x09, 0x61, 0x6c, 0x16, 0x67, 0x5d, 0xcd, 0x6f, 0xaa, 0x46, 0x93,
+                0x9d, 0x3b, 0xfa, 0x4f
+            ]
+        );
+        assert_eq!(
+            sk.top_tree_padding_seed,
+            [
+                0x0b, 0x95, 0x51, 0xdb, 0x8a, 0x4b, 0x74, 0x21, 0x89, 0x5f, 0x82, 0x9b, 0x6a, 0x08,
+                0x78, 0x22, 0x62, 0xa8, 0xfe, 0x54, 0x8e, 0xcf, 0x05, 0x04, 0x7c, 0xde, 0x09, 0x75,
+                0x2e, 0x79, 0x20, 0x38
+            ]
+        );
+        assert_eq!(
+            pk.root,
+            [
+                0x3d, 0x58, 0x10, 0xac, 0x34, 0x7a, 0x79, 0x00, 0x55, 0x34, 0x4e, 0x4f, 0x7d, 0xdb,
+                0xb0, 0xe4, 0xe7, 0x2c, 0xdd, 0x06, 0xab, 0x76, 0x96, 0x47
+            ]
+        );
+
+        // a sparse activation interval uses the same parameter and
+        // PRF key, but leads to a different top tree and root
+        let (pk_sparse, sk_sparse) = Sig::key_gen_from_seed(&seed, 8, 20);
+        assert_eq!(pk_sparse.parameter, pk.parameter);
+        assert_eq!(sk_sparse.prf_key, sk.prf_key);
+        assert_eq!(
+            pk_sparse.root,
+            [
+                0xad, 0xd4, 0xe9, 0xc8, 0x6e, 0x74, 0x2d, 0x39, 0x09, 0x0b, 0x56, 0x78, 0x78, 0x86,
+                0x14, 0xa9, 0x92, 0x8a, 0xd3, 0x16, 0xea, 0x2b, 0xc2, 0xd9
+            ]
+        );
+
+        // regenerating gives the same signatures, and a different seed gives a different key
+        let message = [0x42; MESSAGE_LENGTH];
+        let (_, sk_again) = Sig::key_gen_from_seed(&seed, 0, 1 << LOG_LIFETIME);
+        let signature = Sig::sign(&sk, 3, &message).unwrap();
+        let signature_again = Sig::sign(&sk_again, 3, &message).unwrap();
+        assert!(Sig::verify(&pk, 3, &message, &signature));
+        assert_eq!(signature.hashes, signature_again.hashes);
+        assert_eq!(signature.path.co_path, signature_again.path.co_path);
+
+        let mut other_seed = seed;
+        other_seed[0] ^= 1;
+        let (pk_other, _) = Sig::key_gen_from_seed(&other_seed, 0, 1 << LOG_LIFETIME);
+        assert_ne!(pk_other.parameter, pk.parameter);
+        assert_ne!(pk_other.root, pk.root);
+    }
+
+    #[test]
+    pub fn test_multi_level_keys() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 8;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        type SK = <Sig as SignatureScheme>::SecretKey;
+
+        use bincode::serde::encode_to_vec;
+        let config = bincode::config::standard();
+        let message = [0x42; MESSAGE_LENGTH];
+
+        // for a full and a sparse activation interval, keys with any number of levels have
+        // the same public key and signatures as the key with two levels, if they are generated
+        // from the same randomness and have bottom trees of the same size. They must also match
+        // the full tree.
+        for (activation_epoch, num_active_epochs) in [(0, 1 << LOG_LIFETIME), (37, 150)] {
+            let seed = rand::rng().random();
+            let key_gen = |num_levels| {
+                let mut rng = ChaCha20Rng::from_seed(seed);
+                let tree_layout = TreeLayout {
+                    log_bottom_tree: 2,
+                    num_levels,
+                };
+                Sig::key_gen_with_layout(&mut rng, activation_epoch, num_active_epochs, tree_layout)
+            };
+            let (pk, sk) = key_gen(2);
+
+            // the full tree with the same padding has the same root
+            let activation_interval = sk.get_activation_interval();
+            let leafs: Vec<_> = activation_interval
+                .clone()
+                .map(|epoch| {
+                    let chain_ends: Vec<_> = (0..NUM_CHUNKS)
+                        .map(|chain_index| {
+                            let start =
+                                PRF::get_domain_element(&sk.prf_key, epoch, chain_index as u64);
+                            chain::<TH>(
+                                &pk.parameter,
+                                epoch,
+                                chain_index as u8,
+                                0,
+                                BASE - 1,
+                                &start,
+                            )
+                        })
+                        .collect();
+                    TH::apply(&pk.parameter, &TH::tree_tweak(0, epoch), &chain_ends)
+                })
+                .collect();
+            let full_tree = HashSubTree::<TH>::new_subtree(
+                &mut ChaCha20Rng::from_seed(sk.top_tree_padding_seed),
+                0,
+                LOG_LIFETIME,
+                activation_interval.start as usize,
+                &pk.parameter,
+                leafs,
+            );
+            assert_eq!(full_tree.root(), pk.root);
+
+            for num_levels in [3, 4, LOG_LIFETIME - 1] {
+                let (pk_levels, mut sk_levels) = key_gen(num_levels);
+                assert_eq!(pk_levels.root, pk.root);
+                assert_eq!(sk_levels.tree_layout().num_levels, num_levels);
+                assert_eq!(sk_levels.get_activation_interval(), activation_interval);
+                assert!(sk_levels.tree_memory_bytes() < sk.tree_memory_bytes());
+
+                // we advance step by step, and sign each epoch with both keys
+                let mut sk_two_levels = SK::from_compact(sk.to_compact()).unwrap();
+                for epoch in activation_interval.clone() {
+                    while !sk_levels.get_prepared_interval().contains(&epoch) {
+                        sk_levels.advance_preparation();
+                    }
+                    sk_two_levels.prepare_for_epoch(epoch).unwrap();
+
+                    let signature = Sig::sign(&sk_levels, epoch, &message).unwrap();
+                    let signature_two_levels = Sig::sign(&sk_two_levels, epoch, &message).unwrap();
+                    assert!(Sig::verify(&pk, epoch, &message, &signature));
+                    assert_eq!(
+                        encode_to_vec(&signature, config).unwrap(),
+                        encode_to_vec(&signature_two_levels, config).unwrap()
+                    );
+                    assert_eq!(signature.path.co_path, full_tree.path(epoch).co_path);
+                }
+                assert_eq!(sk_levels.next_preparation_epoch(), None);
+
+                // skipping to later epochs gives the same key as advancing
+                // step by step, also when restored from the compact representation
+                let (_, mut sk_skipping) = key_gen(num_levels);
+                let mut sk_stepwise = SK::from_compact(sk_skipping.to_compact()).unwrap();
+                for epoch in [
+                    activation_interval.start + 21,
+                    activation_interval.start + 90,
+                    activation_interval.end - 1,
+                ] {
+                    sk_skipping.prepare_for_epoch(epoch).unwrap();
+                    while sk_stepwise.get_prepared_interval() != sk_skipping.get_prepared_interval()
+                    {
+                        sk_stepwise.advance_preparation();
+                    }
+                    let bytes = encode_to_vec(&sk_skipping, config).unwrap();
+                    assert_eq!(encode_to_vec(&sk_stepwise, config).unwrap(), bytes);
+                    let restored = SK::from_compact(sk_skipping.to_compact()).unwrap();
+                    assert_eq!(encode_to_vec(&restored, config).unwrap(), bytes);
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_key_gen_with_invalid_layout() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        // the number of levels must be between 2 and LOG_LIFETIME
+        for num_levels in [0, 1, LOG_LIFETIME + 1] {
+            let result = std::panic::catch_unwind(|| {
+                Sig::key_gen_with_levels(&mut rand::rng(), 0, 1 << LOG_LIFETIME, num_levels)
+            });
+            assert!(result.is_err());
+        }
+
+        // bottom trees must have at least one layer, and each level above them as well
+        for (log_bottom_tree, num_levels) in [(0, 2), (LOG_LIFETIME, 2), (3, 5)] {
+            let tree_layout = TreeLayout {
+                log_bottom_tree,
+                num_levels,
+            };
+            let result = std::panic::catch_unwind(|| {
+                Sig::key_gen_with_layout(&mut rand::rng(), 0, 1 << LOG_LIFETIME, tree_layout)
+            });
+            assert!(result.is_err());
+        }
+
+        // the number of levels determines the size of the bottom trees
+        let (_, sk) = Sig::key_gen_with_levels(&mut rand::rng(), 0, 1 << LOG_LIFETIME, 4);
+        assert_eq!(
+            sk.tree_layout(),
+            TreeLayout {
+                log_bottom_tree: 1,
+                num_levels: 4
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_sign_bytes() {
+        fn sign_and_verify_bytes<Sig: PreHashSignatureScheme>() {
+            let mut rng = rand::rng();
+            let (pk, sk) = Sig::key_gen(&mut rng, 0, Sig::LIFETIME as usize);
+            let epoch = 5;
+            let context = b"hashsig-test/v1/sign-bytes";
+            let message =
+                b"a message that is much longer than thirty-two bytes, so it is pre-hashed";
+
+            let signature = Sig::sign_bytes(&sk, epoch, context, message).unwrap();
+            assert!(Sig::verify_bytes(&pk, epoch, context, message, &signature));
+            assert!(Sig::verify_bytes_checked(&pk, epoch, context, message, &signature).is_ok());
+
+            // it is a signature for the pre-hashed message
+            let pre_hashed_message = Sig::pre_hash(context, message);
+            assert!(Sig::verify(&pk, epoch, &pre_hashed_message, &signature));
+
+            // it is not valid for another context or message
+            assert!(!Sig::verify_bytes(
+                &pk,
+                epoch,
+                b"hashsig-test/v1/other",
+                message,
+                &signature
+            ));
+            assert!(!Sig::verify_bytes(
+                &pk,
+                epoch,
+                context,
+                b"other message",
+                &signature
+            ));
+        }
+
+        // Note: do not use these parameters, they are just for testing
+        type PRFSha = ShaPRF<24, 24>;
+        type THSha = ShaTweak192192;
+        type MHSha = ShaMessageHash192x3;
+        type IESha = TargetSumEncoding<MHSha, { MHSha::DIMENSION * (MHSha::BASE - 1) / 2 }>;
+        type PRFPoseidon = ShakePRFtoF<7, 5>;
+        type THPoseidon = PoseidonTweakW1L5;
+        type MHPoseidon = PoseidonMessageHashW1;
+        type IEPoseidon =
+            TargetSumEncoding<MHPoseidon, { MHPoseidon::DIMENSION * (MHPoseidon::BASE - 1) / 2 }>;
+
+        sign_and_verify_bytes::<GeneralizedXMSSSignatureScheme<PRFSha, IESha, THSha, 6>>();
+        sign_and_verify_bytes::<
+            GeneralizedXMSSSignatureScheme<PRFPoseidon, IEPoseidon, THPoseidon, 6>,
+        >();
+    }
+
+    #[test]
+    pub fn test_verify_batch() {
+        // Note: do not use these parameters, they are just for testing
+        type PRF = ShaPRF<24, 24>;
+        type TH = ShaTweak192192;
+        type MH = ShaMessageHash192x3;
+        const BASE: usize = MH::BASE;
+        const NUM_CHUNKS: usize = MH::DIMENSION;
+        const MAX_CHUNK_VALUE: usize = BASE - 1;
+        const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+        type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let mut rng = rand::rng();
+
+        // signatures of two different keys, for different epochs and messages
+        let keys: Vec<_> = (0..2)
+            .map(|_| Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME))
+            .collect();
+        let messages: Vec<[u8; MESSAGE_LENGTH]> = (0..16).map(|_| rng.random()).collect();
+        let signed: Vec<_> = (0..16)
+            .map(|i| {
+                let (pk, sk) = &keys[i % 2];
+                let epoch = i as u64;
+                let signature = Sig::sign(sk, epoch, &messages[i]).unwrap();
+                (pk, epoch, signature)
+            })
+            .collect();
+
+        let mut batch: Vec<GeneralizedXMSSBatchItem<'_, IE, TH>> = signed
+            .iter()
+            .zip(&messages)
+            .map(|((pk, epoch, signature), message)| (*pk, *epoch, message, signature))
+            .collect();
+        assert!(Sig::verify_batch(&batch).is_ok());
+        assert!(Sig::verify_batch(&[]).is_ok());
+
+        // now we break some of the signatures: wrong message, wrong key, epoch out of range
+        let other_message = rng.random();
+        batch[3].2 = &other_message;
+        batch[7].0 = &keys[0].0;
+        batch[12].1 = 1 << LOG_LIFETIME;
+
+        let error = Sig::verify_batch(&batch).unwrap_err();
+        assert_eq!(error.failed_indices().collect::<Vec<_>>(), vec![3, 7, 12]);
+        assert!(matches!(
+            error.failures[2].1,
+            VerificationError::EpochOutOfRange { .. }
+        ));
+
+        // the results agree with verifying one by one
+        for (index, (pk, epoch, message, signature)) in batch.iter().enumerate() {
+            let failed = error
+                .failed_indices()
+                .any(|failed_index| failed_index == index);
+            assert_eq!(Sig::verify(pk, *epoch, message, signature), !failed);
+        }
+
+        // the error names the first failure, and formatting does not panic without failures
+        assert!(
+            error
+                .to_string()
+                .starts_with("3 signature(s) in the batch are invalid, the first one has index 3")
+        );
+        let empty = BatchVerificationError { failures: vec![] };
+        assert_eq!(empty.to_string(), "0 signature(s) in the batch are invalid");
+    }
+
     #[test]
     pub fn test_expand_activation_time() {
         const LOG_LIFETIME: usize = 4;
 
         // no padding needed
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(0, 8);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 0, 8);
         assert!((start == 0) && (end_excl == 2));
 
         // no padding needed in principle, but is extended to minimum duration of two bottom trees
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(0, 4);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 0, 4);
         assert!((start == 0) && (end_excl == 2));
 
         // simple padding needed
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(0, 7);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 0, 7);
         assert!((start == 0) && (end_excl == 2));
 
         // simple padding needed, and extended to minimum duration of two bottom trees
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(0, 3);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 0, 3);
         assert!((start == 0) && (end_excl == 2));
 
         // padding on both sides needed
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(1, 8);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 1, 8);
         assert!((start == 0) && (end_excl == 3));
 
         // padding only in the end needed
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(8, 5);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 8, 5);
         assert!((start == 2) && (end_excl == 4));
 
         // large padding to the left needed because of two bottom trees constraint
-        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(12, 2);
+        let (start, end_excl) = expand_activation_time::<LOG_LIFETIME>(LOG_LIFETIME / 2, 12, 2);
+        assert!((start == 2) && (end_excl == 4));
+
+        // bottom trees of a different size, for an odd lifetime
+        let (start, end_excl) = expand_activation_time::<5>(3, 9, 12);
+        assert!((start == 1) && (end_excl == 3));
+        let (start, end_excl) = expand_activation_time::<5>(1, 9, 1);
+        assert!((start == 4) && (end_excl == 6));
+        let (start, end_excl) = expand_activation_time::<5>(3, 30, 1);
         assert!((start == 2) && (end_excl == 4));
     }
 }
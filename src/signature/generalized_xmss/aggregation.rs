@@ -0,0 +1,332 @@
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MESSAGE_LENGTH,
+    inc_encoding::IncomparableEncoding,
+    parallelism::all_range,
+    signature::{AggregatableSignatureScheme, AggregationError},
+    symmetric::{
+        prf::Pseudorandom, tweak_hash::TweakableHash, tweak_hash_tree::hash_tree_verify_with_tweaks,
+    },
+};
+
+use super::{GeneralizedXMSSPublicKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme};
+
+/// Aggregate signature for GeneralizedXMSSSignatureScheme.
+/// It contains the epoch and the message, and the signatures of all signers.
+///
+/// Note: this is a baseline, which is as large as the individual signatures. The codeword
+/// of each signature depends on the parameter of the signer, so the chains cannot be shared.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct GeneralizedXMSSAggregateSignature<IE: IncomparableEncoding, TH: TweakableHash> {
+    epoch: u64,
+    message: [u8; MESSAGE_LENGTH],
+    signatures: Vec<GeneralizedXMSSSignature<IE, TH>>,
+}
+
+impl<IE: IncomparableEncoding, TH: TweakableHash> GeneralizedXMSSAggregateSignature<IE, TH> {
+    /// Returns the epoch for which the signatures were created.
+    #[must_use]
+    pub const fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the message that is signed.
+    #[must_use]
+    pub const fn message(&self) -> &[u8; MESSAGE_LENGTH] {
+        &self.message
+    }
+
+    /// Returns the signatures, ordered by signer.
+    #[must_use]
+    pub fn signatures(&self) -> &[GeneralizedXMSSSignature<IE, TH>] {
+        &self.signatures
+    }
+}
+
+/// Tweaks of all hashes that are evaluated when verifying a signature for a fixed epoch.
+/// They do not depend on the signer, so they are computed once per aggregate.
+struct EpochTweaks<TH: TweakableHash> {
+    /// `chain_tweaks[chain_index][pos_in_chain - 1]` is the tweak of the step
+    /// that leads to position `pos_in_chain` in the chain with index `chain_index`.
+    chain_tweaks: Vec<Vec<TH::Tweak>>,
+    /// `tree_tweaks[level]` is the tweak of the node on the path from the leaf
+    /// of the epoch to the root in the given level.
+    tree_tweaks: Vec<TH::Tweak>,
+}
+
+impl<TH: TweakableHash> EpochTweaks<TH> {
+    fn new(epoch: u64, num_chains: usize, chain_length: usize, depth: usize) -> Self {
+        let chain_tweaks = (0..num_chains)
+            .map(|chain_index| {
+                (1..chain_length)
+                    .map(|pos_in_chain| {
+                        TH::chain_tweak(epoch, chain_index as u8, pos_in_chain as u8)
+                    })
+                    .collect()
+            })
+            .collect();
+        let tree_tweaks = (0..=depth)
+            .map(|level| TH::tree_tweak(level as u8, epoch >> level))
+            .collect();
+        Self {
+            chain_tweaks,
+            tree_tweaks,
+        }
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+    TH::Tweak: Sync,
+{
+    /// Verifies a well-formed signature, using the precomputed tweaks for its epoch.
+    /// This is the same as `verify`, except that no tweaks are computed.
+    fn verify_with_tweaks(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &GeneralizedXMSSSignature<IE, TH>,
+        tweaks: &EpochTweaks<TH>,
+    ) -> bool {
+        let Ok(chain_ends) = Self::chain_ends_with_tweaks(
+            pk,
+            epoch,
+            message,
+            &sig.rho,
+            &sig.hashes,
+            false,
+            |chain_index, pos_in_chain| {
+                &tweaks.chain_tweaks[chain_index as usize][pos_in_chain as usize - 1]
+            },
+        ) else {
+            return false;
+        };
+        hash_tree_verify_with_tweaks(
+            &pk.parameter,
+            &pk.root,
+            epoch,
+            &chain_ends,
+            &sig.path,
+            |level, _| &tweaks.tree_tweaks[level as usize],
+        )
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> AggregatableSignatureScheme for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+    TH::Tweak: Sync,
+    IE::Randomness: Sync,
+{
+    type AggregateSignature = GeneralizedXMSSAggregateSignature<IE, TH>;
+
+    fn aggregate(
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        signatures: Vec<Self::Signature>,
+    ) -> Result<Self::AggregateSignature, AggregationError> {
+        if signatures.is_empty() {
+            return Err(AggregationError::NoSignatures);
+        }
+        if epoch >= (1 << LOG_LIFETIME) {
+            return Err(AggregationError::EpochOutOfRange {
+                epoch,
+                lifetime: 1 << LOG_LIFETIME,
+            });
+        }
+        for (index, sig) in signatures.iter().enumerate() {
+            Self::check_well_formed(sig)
+                .map_err(|error| AggregationError::MalformedSignature { index, error })?;
+        }
+
+        Ok(GeneralizedXMSSAggregateSignature {
+            epoch,
+            message: *message,
+            signatures,
+        })
+    }
+
+    fn verify_aggregate(
+        public_keys: &[Self::PublicKey],
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        aggregate: &Self::AggregateSignature,
+    ) -> bool {
+        // the aggregate must be for this epoch and message, and for these signers
+        if aggregate.epoch != epoch
+            || aggregate.message != *message
+            || aggregate.signatures.is_empty()
+            || aggregate.signatures.len() != public_keys.len()
+            || epoch >= (1 << LOG_LIFETIME)
+        {
+            return false;
+        }
+
+        // the aggregate may come from an untrusted source
+        if aggregate
+            .signatures
+            .iter()
+            .any(|sig| Self::check_well_formed(sig).is_err())
+        {
+            return false;
+        }
+
+        // all signatures share the tweaks, and each one is verified with them
+        let tweaks = EpochTweaks::<TH>::new(epoch, IE::DIMENSION, IE::BASE, LOG_LIFETIME);
+        all_range(0..public_keys.len(), |i| {
+            Self::verify_with_tweaks(
+                &public_keys[i],
+                epoch,
+                message,
+                &aggregate.signatures[i],
+                &tweaks,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signature::{
+        SignatureScheme, SignatureSchemeSecretKey, VerificationError,
+        test_fixtures::{LOG_LIFETIME, Sig},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let mut rng = rand::rng();
+        let epoch = 21;
+        let message = [0x42; MESSAGE_LENGTH];
+        let other_message = [0x43; MESSAGE_LENGTH];
+
+        // signers with different activation intervals, all containing the epoch
+        let mut public_keys = Vec::new();
+        let mut signatures = Vec::new();
+        for activation_epoch in [0, 8, 16, 20] {
+            let (pk, mut sk) = Sig::key_gen(&mut rng, activation_epoch, 32);
+            sk.prepare_for_epoch(epoch).unwrap();
+            signatures.push(Sig::sign(&sk, epoch, &message).unwrap());
+            public_keys.push(pk);
+        }
+        let (other_pk, mut other_sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        other_sk.prepare_for_epoch(epoch).unwrap();
+        let mut other_signatures = Vec::new();
+        for m in [&message, &other_message] {
+            other_signatures.push(Sig::sign(&other_sk, epoch, m).unwrap());
+        }
+
+        let aggregate = Sig::aggregate(epoch, &message, signatures).unwrap();
+        assert_eq!(aggregate.epoch(), epoch);
+        assert_eq!(aggregate.message(), &message);
+        assert_eq!(aggregate.signatures().len(), public_keys.len());
+        assert!(Sig::verify_aggregate(
+            &public_keys,
+            epoch,
+            &message,
+            &aggregate
+        ));
+
+        // the aggregate agrees with the individual signatures
+        for (pk, sig) in public_keys.iter().zip(aggregate.signatures()) {
+            assert!(Sig::verify(pk, epoch, &message, sig));
+        }
+
+        // it is only valid for the epoch, the message, and the signers in the right order
+        assert!(!Sig::verify_aggregate(
+            &public_keys,
+            epoch + 1,
+            &message,
+            &aggregate
+        ));
+        assert!(!Sig::verify_aggregate(
+            &public_keys,
+            epoch,
+            &other_message,
+            &aggregate
+        ));
+        public_keys.swap(0, 1);
+        assert!(!Sig::verify_aggregate(
+            &public_keys,
+            epoch,
+            &message,
+            &aggregate
+        ));
+        public_keys.swap(0, 1);
+        assert!(!Sig::verify_aggregate(
+            &public_keys[1..],
+            epoch,
+            &message,
+            &aggregate
+        ));
+
+        // a single invalid signature invalidates the aggregate
+        let mut signatures = aggregate.signatures;
+        let last_signer = signatures.len() - 1;
+        signatures[last_signer] = other_signatures.pop().unwrap();
+        public_keys[last_signer] = other_pk;
+        let aggregate = Sig::aggregate(epoch, &message, signatures).unwrap();
+        assert!(!Sig::verify_aggregate(
+            &public_keys,
+            epoch,
+            &message,
+            &aggregate
+        ));
+
+        // replacing it with a valid signature of the same signer fixes the aggregate
+        let mut signatures = aggregate.signatures;
+        signatures[last_signer] = other_signatures.pop().unwrap();
+        let aggregate = Sig::aggregate(epoch, &message, signatures).unwrap();
+        assert!(Sig::verify_aggregate(
+            &public_keys,
+            epoch,
+            &message,
+            &aggregate
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_malformed() {
+        let mut rng = rand::rng();
+        let epoch = 5;
+        let message = [0x42; MESSAGE_LENGTH];
+        let (_, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+
+        assert!(matches!(
+            Sig::aggregate(epoch, &message, Vec::new()),
+            Err(AggregationError::NoSignatures)
+        ));
+
+        let mut signatures = Vec::new();
+        for _ in 0..3 {
+            signatures.push(Sig::sign(&sk, epoch, &message).unwrap());
+        }
+        signatures[2].hashes.pop();
+        assert!(matches!(
+            Sig::aggregate(epoch, &message, signatures),
+            Err(AggregationError::MalformedSignature {
+                index: 2,
+                error: VerificationError::InvalidNumberOfHashes { .. }
+            })
+        ));
+    }
+}
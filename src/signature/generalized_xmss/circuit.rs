@@ -0,0 +1,1362 @@
+use alloc::{vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use p3_field::{PrimeCharacteristicRing, PrimeField32};
+use p3_symmetric::Permutation;
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::{
+    F, MESSAGE_LENGTH,
+    hypercube::{hypercube_find_layer, hypercube_part_size, map_to_vertex},
+    inc_encoding::target_sum::TargetSumEncoding,
+    poseidon2_16, poseidon2_24,
+    signature::VerificationError,
+    symmetric::{
+        message_hash::{
+            poseidon::{encode_epoch, encode_message},
+            top_level_poseidon::TopLevelPoseidonMessageHash,
+        },
+        prf::Pseudorandom,
+        tweak_hash::poseidon::{
+            CHAIN_COMPRESSION_WIDTH, DOMAIN_PARAMETERS_LENGTH, MERGE_COMPRESSION_WIDTH,
+            PoseidonTweak, PoseidonTweakHash, poseidon_safe_domain_separator,
+        },
+    },
+};
+
+use super::{GeneralizedXMSSPublicKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme};
+
+/// Number of bits of a canonical field element.
+const FIELD_BITS: usize = (u32::BITS - F::ORDER_U32.leading_zeros()) as usize;
+
+/// Number of bits per limb when comparing big integers in the circuit.
+const LIMB_BITS: usize = 16;
+
+/// A builder of constraints over the field, together with an assignment of all variables.
+///
+/// Gadgets allocate variables together with their values, i.e., witness generation and
+/// the description of the constraints go hand in hand. Besides linear constraints and
+/// products, the Poseidon2 permutation is a primitive of the builder: proof systems usually
+/// prove it with a dedicated table (e.g., of an AIR), independently of the scheme.
+pub trait ConstraintBuilder {
+    /// A variable of the constraint system.
+    type Var: Copy;
+
+    /// Allocates a public input with the given value.
+    fn public_input(&mut self, value: F) -> Self::Var;
+
+    /// Allocates a witness with the given value, without constraining it.
+    fn witness(&mut self, value: F) -> Self::Var;
+
+    /// Returns the value assigned to a variable.
+    fn value(&self, var: Self::Var) -> F;
+
+    /// Enforces that `sum_i c_i * x_i + constant = 0`, where `terms` contains the pairs `(c_i, x_i)`.
+    fn assert_linear(&mut self, terms: &[(F, Self::Var)], constant: F);
+
+    /// Enforces that `a * b = c`.
+    fn assert_product(&mut self, a: Self::Var, b: Self::Var, c: Self::Var);
+
+    /// Applies the Poseidon2 permutation of width `input.len()`, which must be 16 or 24,
+    /// and returns the constrained output.
+    fn permutation(&mut self, input: &[Self::Var]) -> Vec<Self::Var>;
+
+    /// Allocates a variable that is constrained to be equal to `value`.
+    fn constant(&mut self, value: F) -> Self::Var {
+        let var = self.witness(value);
+        self.assert_linear(&[(F::ONE, var)], -value);
+        var
+    }
+
+    /// Allocates a variable that is constrained to be equal to
+    /// `sum_i c_i * x_i + constant`, see `assert_linear`.
+    fn linear_combination(&mut self, terms: &[(F, Self::Var)], constant: F) -> Self::Var {
+        let value = terms
+            .iter()
+            .fold(constant, |acc, &(c, x)| acc + c * self.value(x));
+        let var = self.witness(value);
+        let mut terms = terms.to_vec();
+        terms.push((-F::ONE, var));
+        self.assert_linear(&terms, constant);
+        var
+    }
+
+    /// Returns a variable constrained to be `a + b`.
+    fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        self.linear_combination(&[(F::ONE, a), (F::ONE, b)], F::ZERO)
+    }
+
+    /// Returns a variable constrained to be `a - b`.
+    fn sub(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        self.linear_combination(&[(F::ONE, a), (-F::ONE, b)], F::ZERO)
+    }
+
+    /// Returns a variable constrained to be `a * b`.
+    fn mul(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        let c = self.witness(self.value(a) * self.value(b));
+        self.assert_product(a, b, c);
+        c
+    }
+
+    /// Enforces that `a = b`.
+    fn assert_equal(&mut self, a: Self::Var, b: Self::Var) {
+        self.assert_linear(&[(F::ONE, a), (-F::ONE, b)], F::ZERO);
+    }
+
+    /// Allocates a variable that is constrained to be a bit.
+    fn bit(&mut self, value: bool) -> Self::Var {
+        let bit = self.witness(F::from_bool(value));
+        self.assert_product(bit, bit, bit);
+        bit
+    }
+
+    /// Returns a variable constrained to be `if_true` if `bit` is one,
+    /// and `if_false` if `bit` is zero. The caller must ensure that `bit` is a bit.
+    fn select(&mut self, bit: Self::Var, if_true: Self::Var, if_false: Self::Var) -> Self::Var {
+        let difference = self.sub(if_true, if_false);
+        let product = self.mul(bit, difference);
+        self.add(if_false, product)
+    }
+}
+
+/// A gadget, i.e., a part of a circuit that constrains its output with respect to its input.
+///
+/// Gadgets hold everything that is fixed in the circuit, e.g., tweaks for a given epoch,
+/// while the input is given by variables of the builder.
+pub trait Gadget<B: ConstraintBuilder> {
+    type Input;
+    type Output;
+
+    /// Adds the constraints of the gadget to the builder, and returns its output.
+    fn synthesize(&self, builder: &mut B, input: Self::Input) -> Self::Output;
+}
+
+/// Variable of a `ConstraintSystem`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Variable(usize);
+
+/// Constraint of a `ConstraintSystem`.
+#[derive(Debug, Clone)]
+enum Constraint {
+    Linear {
+        terms: Vec<(F, Variable)>,
+        constant: F,
+    },
+    Product {
+        a: Variable,
+        b: Variable,
+        c: Variable,
+    },
+    Permutation {
+        input: Vec<Variable>,
+        output: Vec<Variable>,
+    },
+}
+
+/// Error enum for constraints that are not satisfied by the assignment of a `ConstraintSystem`.
+/// Constraints are indexed in the order in which they were added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum UnsatisfiedConstraint {
+    #[error("Linear constraint with index {index} is not satisfied.")]
+    Linear { index: usize },
+    #[error("Product constraint with index {index} is not satisfied.")]
+    Product { index: usize },
+    #[error("Permutation constraint with index {index} is not satisfied.")]
+    Permutation { index: usize },
+}
+
+/// In-memory constraint system, which stores all constraints and the assignment,
+/// and checks whether the assignment satisfies the constraints.
+///
+/// It is meant as a reference for tests, e.g., to check that gadgets are complete
+/// (honest witnesses satisfy them) and sound (tampered witnesses do not).
+#[derive(Debug, Clone, Default)]
+pub struct ConstraintSystem {
+    values: Vec<F>,
+    public_inputs: Vec<Variable>,
+    constraints: Vec<Constraint>,
+}
+
+impl ConstraintSystem {
+    /// Creates an empty constraint system.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of variables, including public inputs.
+    #[must_use]
+    pub const fn num_variables(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the number of constraints, where each permutation counts as one constraint.
+    #[must_use]
+    pub const fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Returns the number of applications of the Poseidon2 permutation.
+    #[must_use]
+    pub fn num_permutations(&self) -> usize {
+        self.constraints
+            .iter()
+            .filter(|constraint| matches!(constraint, Constraint::Permutation { .. }))
+            .count()
+    }
+
+    /// Returns the public inputs, in order of allocation.
+    #[must_use]
+    pub fn public_inputs(&self) -> &[Variable] {
+        &self.public_inputs
+    }
+
+    /// Returns all variables, in order of allocation.
+    pub fn variables(&self) -> impl Iterator<Item = Variable> + use<> {
+        (0..self.values.len()).map(Variable)
+    }
+
+    /// Changes the value assigned to a variable, e.g., to tamper with a witness.
+    pub fn set_value(&mut self, var: Variable, value: F) {
+        self.values[var.0] = value;
+    }
+
+    /// Checks that the assignment satisfies all constraints, and
+    /// returns the first unsatisfied constraint otherwise.
+    pub fn check(&self) -> Result<(), UnsatisfiedConstraint> {
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            match constraint {
+                Constraint::Linear { terms, constant } => {
+                    let sum = terms
+                        .iter()
+                        .fold(*constant, |acc, &(c, x)| acc + c * self.value(x));
+                    if sum != F::ZERO {
+                        return Err(UnsatisfiedConstraint::Linear { index });
+                    }
+                }
+                Constraint::Product { a, b, c } => {
+                    if self.value(*a) * self.value(*b) != self.value(*c) {
+                        return Err(UnsatisfiedConstraint::Product { index });
+                    }
+                }
+                Constraint::Permutation { input, output } => {
+                    let input: Vec<F> = input.iter().map(|&x| self.value(x)).collect();
+                    let output: Vec<F> = output.iter().map(|&x| self.value(x)).collect();
+                    if permute(&input) != output {
+                        return Err(UnsatisfiedConstraint::Permutation { index });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ConstraintBuilder for ConstraintSystem {
+    type Var = Variable;
+
+    fn public_input(&mut self, value: F) -> Variable {
+        let var = self.witness(value);
+        self.public_inputs.push(var);
+        var
+    }
+
+    fn witness(&mut self, value: F) -> Variable {
+        self.values.push(value);
+        Variable(self.values.len() - 1)
+    }
+
+    fn value(&self, var: Variable) -> F {
+        self.values[var.0]
+    }
+
+    fn assert_linear(&mut self, terms: &[(F, Variable)], constant: F) {
+        self.constraints.push(Constraint::Linear {
+            terms: terms.to_vec(),
+            constant,
+        });
+    }
+
+    fn assert_product(&mut self, a: Variable, b: Variable, c: Variable) {
+        self.constraints.push(Constraint::Product { a, b, c });
+    }
+
+    fn permutation(&mut self, input: &[Variable]) -> Vec<Variable> {
+        let values: Vec<F> = input.iter().map(|&x| self.value(x)).collect();
+        let output: Vec<Variable> = permute(&values)
+            .into_iter()
+            .map(|value| self.witness(value))
+            .collect();
+        self.constraints.push(Constraint::Permutation {
+            input: input.to_vec(),
+            output: output.clone(),
+        });
+        output
+    }
+}
+
+/// Helper function to apply the Poseidon2 permutation of width 16 or 24.
+fn permute(input: &[F]) -> Vec<F> {
+    match input.len() {
+        CHAIN_COMPRESSION_WIDTH => {
+            let mut state: [F; CHAIN_COMPRESSION_WIDTH] = input.try_into().unwrap();
+            poseidon2_16().permute_mut(&mut state);
+            state.to_vec()
+        }
+        MERGE_COMPRESSION_WIDTH => {
+            let mut state: [F; MERGE_COMPRESSION_WIDTH] = input.try_into().unwrap();
+            poseidon2_24().permute_mut(&mut state);
+            state.to_vec()
+        }
+        width => panic!("Poseidon2 permutation of width {width} is not supported."),
+    }
+}
+
+/// Gadget for `poseidon_compress`, i.e., the truncation of the permutation of the
+/// zero-padded input, plus the input (feed-forward).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoseidonCompressGadget {
+    pub width: usize,
+    pub output_len: usize,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for PoseidonCompressGadget {
+    type Input = Vec<B::Var>;
+    type Output = Vec<B::Var>;
+
+    fn synthesize(&self, builder: &mut B, input: Vec<B::Var>) -> Vec<B::Var> {
+        assert!(
+            self.output_len <= input.len() && input.len() <= self.width,
+            "Poseidon Compression Gadget: Input length must be between output length and width."
+        );
+        let zero = builder.constant(F::ZERO);
+        let mut state = input;
+        state.resize(self.width, zero);
+        let permuted = builder.permutation(&state);
+        (0..self.output_len)
+            .map(|i| builder.add(permuted[i], state[i]))
+            .collect()
+    }
+}
+
+/// Gadget for the Poseidon2 sponge of width 24, with a fixed value of the capacity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoseidonSpongeGadget {
+    pub capacity_value: Vec<F>,
+    pub output_len: usize,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for PoseidonSpongeGadget {
+    type Input = Vec<B::Var>;
+    type Output = Vec<B::Var>;
+
+    fn synthesize(&self, builder: &mut B, input: Vec<B::Var>) -> Vec<B::Var> {
+        assert!(
+            self.capacity_value.len() < MERGE_COMPRESSION_WIDTH,
+            "Poseidon Sponge Gadget: Capacity length must be smaller than the state width."
+        );
+        let rate = MERGE_COMPRESSION_WIDTH - self.capacity_value.len();
+
+        // the input is zero-padded to a multiple of the rate, as for the native sponge
+        let zero = builder.constant(F::ZERO);
+        let mut input = input;
+        input.resize(input.len().div_ceil(rate) * rate, zero);
+
+        let mut state = vec![zero; rate];
+        for &value in &self.capacity_value {
+            state.push(builder.constant(value));
+        }
+
+        // absorb
+        for chunk in input.chunks(rate) {
+            for (element, &value) in state.iter_mut().zip(chunk) {
+                *element = builder.add(*element, value);
+            }
+            state = builder.permutation(&state);
+        }
+
+        // squeeze
+        let mut output = vec![];
+        while output.len() < self.output_len {
+            output.extend_from_slice(&state[..rate]);
+            state = builder.permutation(&state);
+        }
+        output.truncate(self.output_len);
+        output
+    }
+}
+
+/// Gadget for the tweakable hash `PoseidonTweakHash`, for a fixed tweak.
+///
+/// As for the native hash, one input is compressed with width 16, two inputs are
+/// compressed with width 24, and more inputs are hashed with the sponge, for which
+/// `capacity_value` is used. The tweak is given by its field elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TweakHashGadget {
+    pub tweak: Vec<F>,
+    pub hash_len: usize,
+    pub capacity_value: Vec<F>,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for TweakHashGadget {
+    /// The parameter and the inputs.
+    type Input = (Vec<B::Var>, Vec<Vec<B::Var>>);
+    type Output = Vec<B::Var>;
+
+    fn synthesize(
+        &self,
+        builder: &mut B,
+        (parameter, inputs): (Vec<B::Var>, Vec<Vec<B::Var>>),
+    ) -> Vec<B::Var> {
+        let mut combined_input = parameter;
+        for &value in &self.tweak {
+            combined_input.push(builder.constant(value));
+        }
+        let num_inputs = inputs.len();
+        combined_input.extend(inputs.into_iter().flatten());
+
+        match num_inputs {
+            0 => panic!("Tweak Hash Gadget: There must be at least one input."),
+            1 | 2 => {
+                let width = if num_inputs == 1 {
+                    CHAIN_COMPRESSION_WIDTH
+                } else {
+                    MERGE_COMPRESSION_WIDTH
+                };
+                PoseidonCompressGadget {
+                    width,
+                    output_len: self.hash_len,
+                }
+                .synthesize(builder, combined_input)
+            }
+            _ => PoseidonSpongeGadget {
+                capacity_value: self.capacity_value.clone(),
+                output_len: self.hash_len,
+            }
+            .synthesize(builder, combined_input),
+        }
+    }
+}
+
+/// Gadget for walking a hash chain from the position given by a chunk of the
+/// codeword to the end of the chain.
+///
+/// A circuit cannot depend on the codeword, so all steps are computed, and the
+/// chunk selects which of them are taken. It is given by one bit per step, which is
+/// one if and only if the step is taken, i.e., if the chunk is smaller than the position
+/// the step leads to. These bits are constrained by `HypercubeGadget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainGadget {
+    /// Tweak hashes for the steps to positions 1, ..., BASE - 1.
+    pub steps: Vec<TweakHashGadget>,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for ChainGadget {
+    /// The parameter, the hash at the start position, and one bit per step.
+    type Input = (Vec<B::Var>, Vec<B::Var>, Vec<B::Var>);
+    type Output = Vec<B::Var>;
+
+    fn synthesize(
+        &self,
+        builder: &mut B,
+        (parameter, start, taken): (Vec<B::Var>, Vec<B::Var>, Vec<B::Var>),
+    ) -> Vec<B::Var> {
+        assert_eq!(taken.len(), self.steps.len());
+        let mut current = start;
+        for (step, &is_taken) in self.steps.iter().zip(&taken) {
+            let next = step.synthesize(builder, (parameter.clone(), vec![current.clone()]));
+            current = next
+                .into_iter()
+                .zip(current)
+                .map(|(next, current)| builder.select(is_taken, next, current))
+                .collect();
+        }
+        current
+    }
+}
+
+/// One step of `MerklePathGadget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleStepGadget {
+    /// Whether the current node is the left child. This is fixed by the epoch.
+    pub is_left_child: bool,
+    /// Tweak hash computing the parent.
+    pub hash: TweakHashGadget,
+}
+
+/// Gadget for `hash_tree_verify`, i.e., for walking up the Merkle path of a fixed epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerklePathGadget {
+    /// The steps, ordered by level.
+    pub steps: Vec<MerkleStepGadget>,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for MerklePathGadget {
+    /// The parameter, the leaf, and the co-path.
+    type Input = (Vec<B::Var>, Vec<B::Var>, Vec<Vec<B::Var>>);
+    /// The root.
+    type Output = Vec<B::Var>;
+
+    fn synthesize(
+        &self,
+        builder: &mut B,
+        (parameter, leaf, co_path): (Vec<B::Var>, Vec<B::Var>, Vec<Vec<B::Var>>),
+    ) -> Vec<B::Var> {
+        assert_eq!(co_path.len(), self.steps.len());
+        let mut current = leaf;
+        for (step, sibling) in self.steps.iter().zip(co_path) {
+            let children = if step.is_left_child {
+                vec![current, sibling]
+            } else {
+                vec![sibling, current]
+            };
+            current = step.hash.synthesize(builder, (parameter.clone(), children));
+        }
+        current
+    }
+}
+
+/// Gadget for the Poseidon2 invocations of `TopLevelPoseidonMessageHash`, for a fixed
+/// epoch and message. It outputs the field elements that are then mapped into the
+/// hypercube, see `HypercubeGadget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageHashGadget {
+    /// The epoch, encoded as field elements.
+    pub epoch: Vec<F>,
+    /// The message, encoded as field elements.
+    pub message: Vec<F>,
+    pub invocations: usize,
+    pub output_len_per_invocation: usize,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for MessageHashGadget {
+    /// The parameter and the randomness.
+    type Input = (Vec<B::Var>, Vec<B::Var>);
+    type Output = Vec<B::Var>;
+
+    fn synthesize(
+        &self,
+        builder: &mut B,
+        (parameter, randomness): (Vec<B::Var>, Vec<B::Var>),
+    ) -> Vec<B::Var> {
+        let compress = PoseidonCompressGadget {
+            width: MERGE_COMPRESSION_WIDTH,
+            output_len: self.output_len_per_invocation,
+        };
+        let mut outputs = Vec::with_capacity(self.invocations * self.output_len_per_invocation);
+        for i in 0..self.invocations {
+            let mut combined_input: Vec<B::Var> =
+                randomness.iter().chain(&parameter).copied().collect();
+            for &value in self.epoch.iter().chain(&self.message) {
+                combined_input.push(builder.constant(value));
+            }
+            combined_input.push(builder.constant(F::from_u8(i as u8)));
+            outputs.extend(compress.synthesize(builder, combined_input));
+        }
+        outputs
+    }
+}
+
+/// Gadget for mapping field elements into the top layers of the hypercube.
+///
+/// It mirrors `TopLevelPoseidonMessageHash`, together with the check of `TargetSumEncoding`,
+/// i.e., that the codeword is in the layer given by the target sum.
+///
+/// The codeword is a witness, which is constrained as follows. The field elements are
+/// decomposed into canonical bits, giving the integer `A` that they represent in base p.
+/// For the size `N` of the top layers, the witness contains the quotient `q`, and the
+/// circuit computes the remainder `r = A - q * N` from the codeword, as the index of the
+/// codeword in the top layers. This index is a sum over the chunks of sizes of
+/// sub-hypercubes, which depend on the layers of the suffixes of the codeword (see
+/// `map_to_integer`). The layers of the suffixes are witnessed by one-hot vectors.
+/// Finally, `A = q * N + r` is checked on limbs of `LIMB_BITS` bits.
+///
+/// The output contains, for each chunk, one bit per chain step, see `ChainGadget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HypercubeGadget {
+    pub base: usize,
+    pub dimension: usize,
+    pub final_layer: usize,
+    pub target_sum: usize,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for HypercubeGadget {
+    /// The field elements.
+    type Input = Vec<B::Var>;
+    /// For each chunk, one bit per chain step.
+    type Output = Vec<Vec<B::Var>>;
+
+    fn synthesize(&self, builder: &mut B, field_elements: Vec<B::Var>) -> Vec<Vec<B::Var>> {
+        let (w, v) = (self.base, self.dimension);
+        assert!(
+            self.target_sum <= (w - 1) * v,
+            "Hypercube Gadget: Target sum is too large"
+        );
+        let layer = (w - 1) * v - self.target_sum;
+        assert!(
+            layer <= self.final_layer,
+            "Hypercube Gadget: Target sum is not in the top layers"
+        );
+        let dom_size = hypercube_part_size(w, v, self.final_layer);
+
+        // first, compute the hints natively. If the field elements are not mapped
+        // to the layer of the target sum, then the constraints are not satisfied.
+        let modulus = BigUint::from(F::ORDER_U32);
+        let acc = field_elements.iter().fold(BigUint::ZERO, |acc, &fe| {
+            acc * &modulus + builder.value(fe).as_canonical_u32()
+        });
+        let quotient = &acc / &dom_size;
+        let (hint_layer, offset) = hypercube_find_layer(w, v, acc % &dom_size);
+        let codeword = map_to_vertex(w, v, hint_layer, offset);
+        let mut suffix_layers = vec![0; v + 1];
+        for i in (0..v).rev() {
+            suffix_layers[i] = suffix_layers[i + 1] + (w - 1 - codeword[i] as usize);
+        }
+
+        // lhs and rhs of the equation A + (subtracted sizes) = q * N + r
+        let mut lhs = Limbs::default();
+        let mut rhs = Limbs::default();
+
+        // decompose the field elements into canonical bits
+        let num_fe = field_elements.len();
+        for (i, &fe) in field_elements.iter().enumerate() {
+            let bits = canonical_bits(builder, fe);
+            lhs.add_bits(&bits, &modulus.pow((num_fe - 1 - i) as u32));
+        }
+
+        // quotient, which is at most (p^num_fe - 1) / N
+        let max_quotient = (modulus.pow(num_fe as u32) - 1u32) / &dom_size;
+        let quotient_bits: Vec<B::Var> = (0..max_quotient.bits())
+            .map(|b| builder.bit(quotient.bit(b)))
+            .collect();
+        rhs.add_bits(&quotient_bits, &dom_size);
+
+        // the chunks, given by one bit per step. If a step is taken, all later steps
+        // are taken as well, so that the number of steps determines the chunk.
+        let steps: Vec<Vec<B::Var>> = codeword
+            .iter()
+            .map(|&chunk| {
+                let taken: Vec<B::Var> = (1..w)
+                    .map(|pos| builder.bit(pos > chunk as usize))
+                    .collect();
+                for pair in taken.windows(2) {
+                    builder.assert_product(pair[0], pair[1], pair[0]);
+                }
+                taken
+            })
+            .collect();
+
+        // r is the size of the layers below plus the index within the layer. The index is
+        // sum_i (S_{v-1-i}(min(D_i, (w-1)(v-1-i))) - S_{v-1-i}(D_{i+1})) for i < v - 1, where D_i is
+        // the layer of the suffix starting at i, and S_m(d) is the size of layers 0 to d
+        // of the hypercube of dimension m. Note that D_0 is given by the target sum.
+        let part_size = |m: usize, d: usize| hypercube_part_size(w, m, d.min((w - 1) * m));
+        if layer > 0 {
+            rhs.add_constant(&hypercube_part_size(w, v, layer - 1));
+        }
+        rhs.add_constant(&part_size(v - 1, layer));
+
+        // D_{i-1} - D_i is the number of steps taken in chain i - 1
+        let mut previous_layer: Vec<(F, B::Var)> = vec![];
+        let mut previous_layer_constant = F::from_usize(layer);
+        for i in 1..v {
+            let max_layer = layer.min((w - 1) * (v - i));
+            let selectors: Vec<B::Var> = (0..=max_layer)
+                .map(|d| builder.bit(d == suffix_layers[i]))
+                .collect();
+            let ones: Vec<(F, B::Var)> = selectors.iter().map(|&s| (F::ONE, s)).collect();
+            builder.assert_linear(&ones, -F::ONE);
+            let current_layer: Vec<(F, B::Var)> = selectors
+                .iter()
+                .enumerate()
+                .map(|(d, &s)| (F::from_usize(d), s))
+                .collect();
+            assert_layer_difference(
+                builder,
+                &previous_layer,
+                previous_layer_constant,
+                &current_layer,
+                &steps[i - 1],
+            );
+
+            let sizes: Vec<BigUint> = (0..=max_layer).map(|d| part_size(v - i, d)).collect();
+            lhs.add_one_hot(&selectors, &sizes);
+            if i < v - 1 {
+                let sizes: Vec<BigUint> =
+                    (0..=max_layer).map(|d| part_size(v - 1 - i, d)).collect();
+                rhs.add_one_hot(&selectors, &sizes);
+            }
+
+            previous_layer = current_layer;
+            previous_layer_constant = F::ZERO;
+        }
+        assert_layer_difference(
+            builder,
+            &previous_layer,
+            previous_layer_constant,
+            &[],
+            &steps[v - 1],
+        );
+
+        assert_equal_integers(builder, &lhs, &rhs);
+        steps
+    }
+}
+
+/// Helper function to decompose a field element into bits, such that
+/// the bits represent its canonical value, i.e., a value smaller than p.
+fn canonical_bits<B: ConstraintBuilder>(builder: &mut B, fe: B::Var) -> Vec<B::Var> {
+    let value = builder.value(fe).as_canonical_u32();
+    let bits: Vec<B::Var> = (0..FIELD_BITS)
+        .map(|b| builder.bit((value >> b) & 1 == 1))
+        .collect();
+    let mut terms: Vec<(F, B::Var)> = bits
+        .iter()
+        .enumerate()
+        .map(|(b, &bit)| (F::from_u32(1 << b), bit))
+        .collect();
+    terms.push((-F::ONE, fe));
+    builder.assert_linear(&terms, F::ZERO);
+
+    // compare with p - 1, starting with the most significant bit. As long as the bits
+    // are equal to those of p - 1, no bit may be one where p - 1 has a zero.
+    let max = F::ORDER_U32 - 1;
+    let mut equal_prefix: Option<B::Var> = None;
+    for b in (0..FIELD_BITS).rev() {
+        // the bit, if all more significant bits are equal to those of p - 1
+        let masked = equal_prefix.map_or(bits[b], |equal| builder.mul(equal, bits[b]));
+        if (max >> b) & 1 == 1 {
+            equal_prefix = Some(masked);
+        } else {
+            builder.assert_linear(&[(F::ONE, masked)], F::ZERO);
+        }
+    }
+    bits
+}
+
+/// Helper function to enforce that the difference of the layers of two suffixes,
+/// given as linear combinations, is the number of steps taken in a chain.
+fn assert_layer_difference<B: ConstraintBuilder>(
+    builder: &mut B,
+    previous_layer: &[(F, B::Var)],
+    previous_layer_constant: F,
+    current_layer: &[(F, B::Var)],
+    steps: &[B::Var],
+) {
+    let mut terms = previous_layer.to_vec();
+    terms.extend(current_layer.iter().map(|&(c, x)| (-c, x)));
+    terms.extend(steps.iter().map(|&s| (-F::ONE, s)));
+    builder.assert_linear(&terms, previous_layer_constant);
+}
+
+/// One limb of `Limbs`, which is a linear combination with non-negative integer
+/// coefficients, together with a bound on its value.
+#[derive(Debug, Clone)]
+struct Limb<V> {
+    terms: Vec<(u64, V)>,
+    constant: u64,
+    bound: u64,
+}
+
+impl<V> Default for Limb<V> {
+    fn default() -> Self {
+        Self {
+            terms: vec![],
+            constant: 0,
+            bound: 0,
+        }
+    }
+}
+
+/// A non-negative integer in the circuit, given by limbs in base 2^LIMB_BITS. The
+/// limbs are not normalized, i.e., they may be larger than 2^LIMB_BITS. Variables in the
+/// limbs must be bits, so that the bounds of the limbs hold.
+#[derive(Debug, Clone)]
+struct Limbs<V> {
+    limbs: Vec<Limb<V>>,
+}
+
+impl<V> Default for Limbs<V> {
+    fn default() -> Self {
+        Self { limbs: vec![] }
+    }
+}
+
+impl<V: Copy> Limbs<V> {
+    fn limb_mut(&mut self, k: usize) -> &mut Limb<V> {
+        if self.limbs.len() <= k {
+            self.limbs.resize_with(k + 1, Limb::default);
+        }
+        &mut self.limbs[k]
+    }
+
+    /// Adds a constant.
+    fn add_constant(&mut self, value: &BigUint) {
+        for (k, digit) in to_limbs(value).into_iter().enumerate() {
+            let limb = self.limb_mut(k);
+            limb.constant += digit;
+            limb.bound += digit;
+        }
+    }
+
+    /// Adds `weight * sum_b 2^b * bits[b]`.
+    fn add_bits(&mut self, bits: &[V], weight: &BigUint) {
+        for (b, &bit) in bits.iter().enumerate() {
+            for (k, digit) in to_limbs(&(weight << b)).into_iter().enumerate() {
+                let limb = self.limb_mut(k);
+                limb.terms.push((digit, bit));
+                limb.bound += digit;
+            }
+        }
+    }
+
+    /// Adds `values[t]`, where `t` is the index of the selector that is one.
+    /// At most one selector may be one.
+    fn add_one_hot(&mut self, selectors: &[V], values: &[BigUint]) {
+        for (&selector, value) in selectors.iter().zip(values) {
+            for (k, digit) in to_limbs(value).into_iter().enumerate() {
+                self.limb_mut(k).terms.push((digit, selector));
+            }
+        }
+        let limbs: Vec<Vec<u64>> = values.iter().map(to_limbs).collect();
+        for k in 0..self.limbs.len() {
+            let max_digit = limbs
+                .iter()
+                .map(|digits| digits.get(k).copied().unwrap_or(0))
+                .max()
+                .unwrap_or(0);
+            self.limbs[k].bound += max_digit;
+        }
+    }
+}
+
+/// Helper function to split an integer into limbs of `LIMB_BITS` bits.
+fn to_limbs(value: &BigUint) -> Vec<u64> {
+    let mut value = value.clone();
+    let mut limbs = vec![];
+    while !value.is_zero() {
+        limbs.push((&value % (1u64 << LIMB_BITS)).to_u64().unwrap());
+        value >>= LIMB_BITS;
+    }
+    limbs
+}
+
+/// Helper function to enforce that two integers are equal. The limbs are compared
+/// from the least significant one, with carries that are witnessed and range checked.
+///
+/// The bounds of the limbs ensure that no constraint wraps around the modulus,
+/// so that the limbs are equal in the integers.
+fn assert_equal_integers<B: ConstraintBuilder>(
+    builder: &mut B,
+    lhs: &Limbs<B::Var>,
+    rhs: &Limbs<B::Var>,
+) {
+    let empty = Limb::default();
+    let num_limbs = lhs.limbs.len().max(rhs.limbs.len());
+    let value = |builder: &B, limb: &Limb<B::Var>| {
+        limb.terms
+            .iter()
+            .fold(limb.constant as i128, |acc, &(c, x)| {
+                acc + c as i128 * builder.value(x).as_canonical_u32() as i128
+            })
+    };
+
+    // the carry from the previous limb, as bits and an offset to make it non-negative
+    let mut carry_bits: Vec<B::Var> = vec![];
+    let mut carry_offset = 0u64;
+    let mut carry = 0i128;
+    for k in 0..num_limbs {
+        let l = lhs.limbs.get(k).unwrap_or(&empty);
+        let r = rhs.limbs.get(k).unwrap_or(&empty);
+
+        let mut terms: Vec<(F, B::Var)> =
+            l.terms.iter().map(|&(c, x)| (F::from_u64(c), x)).collect();
+        terms.extend(r.terms.iter().map(|&(c, x)| (-F::from_u64(c), x)));
+        terms.extend(
+            carry_bits
+                .iter()
+                .enumerate()
+                .map(|(b, &bit)| (F::from_u64(1 << b), bit)),
+        );
+        let mut constant =
+            F::from_u64(l.constant) - F::from_u64(r.constant) - F::from_u64(carry_offset);
+
+        // bound on the absolute value of the limb difference plus the carry
+        let bound = l.bound as u128 + r.bound as u128 + (1u128 << carry_bits.len());
+        let total = value(builder, l) - value(builder, r) + carry;
+
+        if k + 1 < num_limbs {
+            // total = 2^LIMB_BITS * carry, where |carry| <= bound / 2^LIMB_BITS
+            let offset = (bound >> LIMB_BITS) as u64;
+            let num_carry_bits = (u64::BITS - (2 * offset).leading_zeros()) as usize;
+            assert!(
+                bound + (1u128 << (LIMB_BITS + num_carry_bits)) < (F::ORDER_U32 / 2) as u128,
+                "Integer equality: limbs are too large for the field"
+            );
+            carry = total >> LIMB_BITS;
+            let shifted = (carry + offset as i128).max(0) as u128;
+            carry_bits = (0..num_carry_bits)
+                .map(|b| builder.bit((shifted >> b) & 1 == 1))
+                .collect();
+            carry_offset = offset;
+            terms.extend(
+                carry_bits
+                    .iter()
+                    .enumerate()
+                    .map(|(b, &bit)| (-F::from_u64(1 << (LIMB_BITS + b)), bit)),
+            );
+            constant += F::from_u64(offset << LIMB_BITS);
+        } else {
+            assert!(
+                bound < (F::ORDER_U32 / 2) as u128,
+                "Integer equality: limbs are too large for the field"
+            );
+        }
+        builder.assert_linear(&terms, constant);
+    }
+}
+
+/// Public inputs and witness of `VerifierGadget`, as variables.
+#[derive(Debug, Clone)]
+pub struct VerifierInput<V> {
+    /// The root from the public key.
+    pub root: Vec<V>,
+    /// The parameter from the public key.
+    pub parameter: Vec<V>,
+    /// The randomness from the signature.
+    pub rho: Vec<V>,
+    /// The Merkle co-path from the signature.
+    pub co_path: Vec<Vec<V>>,
+    /// The chain hashes from the signature.
+    pub hashes: Vec<Vec<V>>,
+}
+
+/// Gadget for verification of the generalized XMSS scheme for a fixed epoch and message.
+///
+/// It is given for the top level target sum encoding with Poseidon2, and mirrors `verify`:
+/// message hash, mapping into the hypercube, chains, hash of the chain ends, and Merkle path.
+///
+/// Use `GeneralizedXMSSSignatureScheme::verifier_gadget` to obtain it for an instantiation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierGadget {
+    pub message_hash: MessageHashGadget,
+    pub hypercube: HypercubeGadget,
+    pub chains: Vec<ChainGadget>,
+    pub leaf: TweakHashGadget,
+    pub merkle_path: MerklePathGadget,
+}
+
+impl<B: ConstraintBuilder> Gadget<B> for VerifierGadget {
+    type Input = VerifierInput<B::Var>;
+    type Output = ();
+
+    fn synthesize(&self, builder: &mut B, input: VerifierInput<B::Var>) {
+        assert_eq!(input.hashes.len(), self.chains.len());
+
+        let field_elements = self
+            .message_hash
+            .synthesize(builder, (input.parameter.clone(), input.rho));
+        let steps = self.hypercube.synthesize(builder, field_elements);
+
+        let chain_ends = self
+            .chains
+            .iter()
+            .zip(input.hashes)
+            .zip(steps)
+            .map(|((chain, start), taken)| {
+                chain.synthesize(builder, (input.parameter.clone(), start, taken))
+            })
+            .collect();
+        let leaf = self
+            .leaf
+            .synthesize(builder, (input.parameter.clone(), chain_ends));
+
+        let root = self
+            .merkle_path
+            .synthesize(builder, (input.parameter, leaf, input.co_path));
+        assert_eq!(root.len(), input.root.len());
+        for (computed, expected) in root.into_iter().zip(input.root) {
+            builder.assert_equal(computed, expected);
+        }
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    const POS_OUTPUT_LEN_PER_INV_FE: usize,
+    const POS_INVOCATIONS: usize,
+    const POS_OUTPUT_LEN_FE: usize,
+    const DIMENSION: usize,
+    const BASE: usize,
+    const FINAL_LAYER: usize,
+    const TWEAK_LEN_FE: usize,
+    const MSG_LEN_FE: usize,
+    const PARAMETER_LEN: usize,
+    const RAND_LEN: usize,
+    const TARGET_SUM: usize,
+    const HASH_LEN: usize,
+    const TWEAK_LEN: usize,
+    const CAPACITY: usize,
+    const NUM_CHUNKS: usize,
+    const LOG_LIFETIME: usize,
+>
+    GeneralizedXMSSSignatureScheme<
+        PRF,
+        TargetSumEncoding<
+            TopLevelPoseidonMessageHash<
+                POS_OUTPUT_LEN_PER_INV_FE,
+                POS_INVOCATIONS,
+                POS_OUTPUT_LEN_FE,
+                DIMENSION,
+                BASE,
+                FINAL_LAYER,
+                TWEAK_LEN_FE,
+                MSG_LEN_FE,
+                PARAMETER_LEN,
+                RAND_LEN,
+            >,
+            TARGET_SUM,
+        >,
+        PoseidonTweakHash<PARAMETER_LEN, HASH_LEN, TWEAK_LEN, CAPACITY, NUM_CHUNKS>,
+        LOG_LIFETIME,
+    >
+where
+    PRF::Domain: Into<[F; HASH_LEN]>,
+    PRF::Randomness: Into<[F; RAND_LEN]>,
+    [F; PARAMETER_LEN]: Serialize + DeserializeOwned,
+    [F; RAND_LEN]: Serialize + DeserializeOwned,
+    [F; HASH_LEN]: Serialize + DeserializeOwned,
+{
+    /// Returns the gadget constraining verification for the given epoch and message.
+    ///
+    /// Panics if the epoch is outside of the lifetime.
+    #[must_use]
+    pub fn verifier_gadget(epoch: u64, message: &[u8; MESSAGE_LENGTH]) -> VerifierGadget {
+        assert!(
+            epoch < (1 << LOG_LIFETIME),
+            "Verifier Gadget: Epoch is outside of the lifetime"
+        );
+        let tweak_hash = |tweak: PoseidonTweak| TweakHashGadget {
+            tweak: tweak.to_field_elements::<TWEAK_LEN>().to_vec(),
+            hash_len: HASH_LEN,
+            capacity_value: vec![],
+        };
+
+        let chains = (0..DIMENSION)
+            .map(|chain_index| ChainGadget {
+                steps: (1..BASE)
+                    .map(|pos_in_chain| {
+                        tweak_hash(PoseidonTweak::ChainTweak {
+                            epoch,
+                            chain_index: chain_index as u8,
+                            pos_in_chain: pos_in_chain as u8,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let lengths: [u32; DOMAIN_PARAMETERS_LENGTH] = [
+            PARAMETER_LEN as u32,
+            TWEAK_LEN as u32,
+            NUM_CHUNKS as u32,
+            HASH_LEN as u32,
+        ];
+        let leaf = TweakHashGadget {
+            capacity_value: poseidon_safe_domain_separator::<_, MERGE_COMPRESSION_WIDTH, CAPACITY>(
+                poseidon2_24(),
+                &lengths,
+            )
+            .to_vec(),
+            ..tweak_hash(PoseidonTweak::TreeTweak {
+                level: 0,
+                pos_in_level: epoch,
+            })
+        };
+
+        let merkle_path = MerklePathGadget {
+            steps: (0..LOG_LIFETIME)
+                .map(|l| MerkleStepGadget {
+                    is_left_child: (epoch >> l).is_multiple_of(2),
+                    hash: tweak_hash(PoseidonTweak::TreeTweak {
+                        level: (l + 1) as u8,
+                        pos_in_level: epoch >> (l + 1),
+                    }),
+                })
+                .collect(),
+        };
+
+        VerifierGadget {
+            message_hash: MessageHashGadget {
+                epoch: encode_epoch::<TWEAK_LEN_FE>(epoch).to_vec(),
+                message: encode_message::<MSG_LEN_FE>(message).to_vec(),
+                invocations: POS_INVOCATIONS,
+                output_len_per_invocation: POS_OUTPUT_LEN_PER_INV_FE,
+            },
+            hypercube: HypercubeGadget {
+                base: BASE,
+                dimension: DIMENSION,
+                final_layer: FINAL_LAYER,
+                target_sum: TARGET_SUM,
+            },
+            chains,
+            leaf,
+            merkle_path,
+        }
+    }
+
+    /// Adds the constraints of verifying `sig` for the given epoch and message to the builder.
+    /// The public key is allocated as public inputs (root, then parameter), and the
+    /// signature as witness. The constraints are satisfied if and only if `verify` accepts.
+    ///
+    /// As the shape of the circuit depends on them, this fails if the epoch is outside
+    /// of the lifetime or if the signature is malformed.
+    pub fn synthesize_verification<B: ConstraintBuilder>(
+        builder: &mut B,
+        pk: &GeneralizedXMSSPublicKey<
+            PoseidonTweakHash<PARAMETER_LEN, HASH_LEN, TWEAK_LEN, CAPACITY, NUM_CHUNKS>,
+        >,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &GeneralizedXMSSSignature<
+            TargetSumEncoding<
+                TopLevelPoseidonMessageHash<
+                    POS_OUTPUT_LEN_PER_INV_FE,
+                    POS_INVOCATIONS,
+                    POS_OUTPUT_LEN_FE,
+                    DIMENSION,
+                    BASE,
+                    FINAL_LAYER,
+                    TWEAK_LEN_FE,
+                    MSG_LEN_FE,
+                    PARAMETER_LEN,
+                    RAND_LEN,
+                >,
+                TARGET_SUM,
+            >,
+            PoseidonTweakHash<PARAMETER_LEN, HASH_LEN, TWEAK_LEN, CAPACITY, NUM_CHUNKS>,
+        >,
+    ) -> Result<(), VerificationError> {
+        if epoch >= (1 << LOG_LIFETIME) {
+            return Err(VerificationError::EpochOutOfRange {
+                epoch,
+                lifetime: (1 << LOG_LIFETIME),
+            });
+        }
+        Self::check_well_formed(sig)?;
+
+        let input = VerifierInput {
+            root: pk.root.iter().map(|&fe| builder.public_input(fe)).collect(),
+            parameter: pk
+                .parameter
+                .iter()
+                .map(|&fe| builder.public_input(fe))
+                .collect(),
+            rho: sig.rho.iter().map(|&fe| builder.witness(fe)).collect(),
+            co_path: sig
+                .path
+                .co_path
+                .iter()
+                .map(|node| node.iter().map(|&fe| builder.witness(fe)).collect())
+                .collect(),
+            hashes: sig
+                .hashes
+                .iter()
+                .map(|hash| hash.iter().map(|&fe| builder.witness(fe)).collect())
+                .collect(),
+        };
+        Self::verifier_gadget(epoch, message).synthesize(builder, input);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::{
+        signature::{SignatureScheme, SignatureSchemeSecretKey},
+        symmetric::prf::shake_to_field::ShakePRFtoF,
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing. They are
+    // those of the lifetime 2^18 instantiation, with a shorter lifetime.
+    const DIMENSION: usize = 64;
+    const BASE: usize = 8;
+    const FINAL_LAYER: usize = 77;
+    const TARGET_SUM: usize = 375;
+    const POS_OUTPUT_LEN_FE: usize = 15;
+    type MH = TopLevelPoseidonMessageHash<
+        POS_OUTPUT_LEN_FE,
+        1,
+        POS_OUTPUT_LEN_FE,
+        DIMENSION,
+        BASE,
+        FINAL_LAYER,
+        2,
+        9,
+        5,
+        6,
+    >;
+    type TH = PoseidonTweakHash<5, 7, 2, 9, DIMENSION>;
+    type PRF = ShakePRFtoF<7, 6>;
+    type IE = TargetSumEncoding<MH, TARGET_SUM>;
+    const LOG_LIFETIME: usize = 6;
+    type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+    /// Synthesizes verification into a new constraint system.
+    fn synthesize(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &GeneralizedXMSSSignature<IE, TH>,
+    ) -> ConstraintSystem {
+        let mut cs = ConstraintSystem::new();
+        Sig::synthesize_verification(&mut cs, pk, epoch, message, sig).unwrap();
+        cs
+    }
+
+    #[test]
+    fn test_verification_circuit() {
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let epoch = 45;
+        let message = [0x42; MESSAGE_LENGTH];
+        sk.prepare_for_epoch(epoch).unwrap();
+        let sig = Sig::sign(&sk, epoch, &message).unwrap();
+        assert!(Sig::verify(&pk, epoch, &message, &sig));
+
+        let cs = synthesize(&pk, epoch, &message, &sig);
+        assert_eq!(cs.check(), Ok(()));
+        assert_eq!(cs.public_inputs().len(), 7 + 5);
+        // message hash, all chain steps, sponge of the leaf (absorbing
+        // 5 + 2 + 64 * 7 elements with rate 15, then one squeeze), and the Merkle path
+        assert_eq!(
+            cs.num_permutations(),
+            1 + DIMENSION * (BASE - 1) + 31 + 1 + LOG_LIFETIME
+        );
+
+        // other message or epoch
+        let other_message = [0x43; MESSAGE_LENGTH];
+        assert!(!Sig::verify(&pk, epoch, &other_message, &sig));
+        assert!(
+            synthesize(&pk, epoch, &other_message, &sig)
+                .check()
+                .is_err()
+        );
+        assert!(!Sig::verify(&pk, epoch + 1, &message, &sig));
+        assert!(synthesize(&pk, epoch + 1, &message, &sig).check().is_err());
+
+        // other public key
+        let (other_pk, _) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        assert!(!Sig::verify(&other_pk, epoch, &message, &sig));
+        assert!(
+            synthesize(&other_pk, epoch, &message, &sig)
+                .check()
+                .is_err()
+        );
+
+        // tampered signatures
+        let mut tampered = Sig::sign(&sk, epoch, &message).unwrap();
+        tampered.hashes[3][0] += F::ONE;
+        assert!(!Sig::verify(&pk, epoch, &message, &tampered));
+        assert!(synthesize(&pk, epoch, &message, &tampered).check().is_err());
+
+        let mut tampered = Sig::sign(&sk, epoch, &message).unwrap();
+        tampered.path.co_path[2][1] += F::ONE;
+        assert!(!Sig::verify(&pk, epoch, &message, &tampered));
+        assert!(synthesize(&pk, epoch, &message, &tampered).check().is_err());
+
+        let mut tampered = Sig::sign(&sk, epoch, &message).unwrap();
+        tampered.rho[0] += F::ONE;
+        assert!(!Sig::verify(&pk, epoch, &message, &tampered));
+        assert!(synthesize(&pk, epoch, &message, &tampered).check().is_err());
+
+        // malformed signatures
+        let mut malformed = Sig::sign(&sk, epoch, &message).unwrap();
+        malformed.hashes.pop();
+        let mut builder = ConstraintSystem::new();
+        assert!(matches!(
+            Sig::synthesize_verification(&mut builder, &pk, epoch, &message, &malformed),
+            Err(VerificationError::InvalidNumberOfHashes { .. })
+        ));
+        assert!(matches!(
+            Sig::synthesize_verification(&mut builder, &pk, 1 << LOG_LIFETIME, &message, &sig),
+            Err(VerificationError::EpochOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verification_circuit_tampered_witness() {
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let epoch = 12;
+        let message = rng.random();
+        sk.prepare_for_epoch(epoch).unwrap();
+        let sig = Sig::sign(&sk, epoch, &message).unwrap();
+        let cs = synthesize(&pk, epoch, &message, &sig);
+        assert_eq!(cs.check(), Ok(()));
+
+        // every variable is determined by the constraints, so changing any of them
+        // must violate a constraint. We check a sample of the variables.
+        for var in cs.variables().step_by(53) {
+            let mut tampered = cs.clone();
+            tampered.set_value(var, cs.value(var) + F::ONE);
+            assert!(tampered.check().is_err(), "{var:?} is not constrained");
+        }
+    }
+
+    #[test]
+    fn test_hypercube_gadget() {
+        let mut rng = rand::rng();
+        for _ in 0..10 {
+            let field_elements: [F; POS_OUTPUT_LEN_FE] = rng.random();
+
+            // compute the codeword natively, as done by the message hash
+            let acc = field_elements.iter().fold(BigUint::ZERO, |acc, fe| {
+                acc * F::ORDER_U32 + fe.as_canonical_u32()
+            });
+            let dom_size = hypercube_part_size(BASE, DIMENSION, FINAL_LAYER);
+            let (layer, offset) = hypercube_find_layer(BASE, DIMENSION, acc % dom_size);
+            let codeword = map_to_vertex(BASE, DIMENSION, layer, offset);
+            let sum: usize = codeword.iter().map(|&x| x as usize).sum();
+
+            let mut cs = ConstraintSystem::new();
+            let inputs = field_elements.iter().map(|&fe| cs.witness(fe)).collect();
+            let gadget = HypercubeGadget {
+                base: BASE,
+                dimension: DIMENSION,
+                final_layer: FINAL_LAYER,
+                target_sum: sum,
+            };
+            let steps = gadget.synthesize(&mut cs, inputs);
+            assert_eq!(cs.check(), Ok(()));
+
+            // the steps determine the codeword
+            let chunks: Vec<u8> = steps
+                .iter()
+                .map(|taken| {
+                    let num_steps = taken.iter().filter(|&&s| cs.value(s) == F::ONE).count();
+                    (BASE - 1 - num_steps) as u8
+                })
+                .collect();
+            assert_eq!(chunks, codeword);
+
+            // a different target sum is not satisfied
+            let max_sum = (BASE - 1) * DIMENSION;
+            if sum < max_sum {
+                let mut cs = ConstraintSystem::new();
+                let inputs = field_elements.iter().map(|&fe| cs.witness(fe)).collect();
+                let gadget = HypercubeGadget {
+                    target_sum: sum + 1,
+                    ..gadget
+                };
+                gadget.synthesize(&mut cs, inputs);
+                assert!(cs.check().is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use alloc::{string::String, string::ToString, vec, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use sha3::{
+    Shake128,
+    digest::{ExtendableOutput, Update, XofReader},
+};
+use thiserror::Error;
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+};
+
+use super::{GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignatureScheme};
+
+const DERIVATION_DOMAIN_SEP: [u8; 16] = [
+    0xde, 0x71, 0x0a, 0x7e, 0x00, 0x5e, 0xed, 0xff, 0x01, 0x3c, 0xa2, 0x00, 0xfe, 0x12, 0x9b, 0x00,
+];
+const DERIVATION_DOMAIN_SEP_MASTER: [u8; 1] = [0x00];
+const DERIVATION_DOMAIN_SEP_CHILD: [u8; 1] = [0x01];
+
+/// Path used to derive a key from a master seed, given as a sequence of indices.
+///
+/// It is written as `m/i_1/i_2/...`, e.g., `m/17/0` for the key with rotation
+/// counter 0 of the validator with index 17, see `DerivationPath::validator`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Creates a path from a sequence of indices.
+    #[must_use]
+    pub const fn new(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+
+    /// The path `m/validator_index/rotation` for the key of a validator.
+    /// The rotation counter is increased whenever the validator needs a new key.
+    #[must_use]
+    pub fn validator(validator_index: u32, rotation: u32) -> Self {
+        Self(vec![validator_index, rotation])
+    }
+
+    /// Returns the path that extends this path by the given index.
+    #[must_use]
+    pub fn child(&self, index: u32) -> Self {
+        let mut indices = self.0.clone();
+        indices.push(index);
+        Self(indices)
+    }
+
+    /// Returns the indices of the path.
+    #[must_use]
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{index}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error enum for parsing a `DerivationPath`.
+#[derive(Debug, Error)]
+pub enum DerivationPathError {
+    /// Occurs when the path does not start with `m`.
+    #[error("Derivation path must start with `m`.")]
+    MissingMaster,
+
+    /// Occurs when a component of the path is not a valid `u32`.
+    #[error("Invalid index `{0}` in derivation path.")]
+    InvalidIndex(String),
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = s.split('/');
+        if components.next() != Some("m") {
+            return Err(DerivationPathError::MissingMaster);
+        }
+        components
+            .map(|component| {
+                component
+                    .parse()
+                    .map_err(|_| DerivationPathError::InvalidIndex(component.to_string()))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Derives the seed for the given path from a master seed.
+///
+/// The derivation is hierarchical: the seed for a path is derived from the seed of its
+/// parent path and the last index, using SHAKE128. So the seed for a path can be handed
+/// out to derive all keys below that path, without revealing any other keys.
+#[must_use]
+pub fn derive_seed(master_seed: &[u8; 32], path: &DerivationPath) -> [u8; 32] {
+    let mut seed = shake(&[&DERIVATION_DOMAIN_SEP_MASTER, master_seed]);
+    for index in path.indices() {
+        seed = shake(&[&DERIVATION_DOMAIN_SEP_CHILD, &seed, &index.to_be_bytes()]);
+    }
+    seed
+}
+
+/// Derives the key pair for the given path from a master seed.
+///
+/// This is `GeneralizedXMSSSignatureScheme::key_gen_from_seed` applied to the
+/// seed derived by `derive_seed`. Keys for different paths are independent.
+///
+/// Warning: each path must only be used for a single key pair, i.e., a new key pair
+/// (e.g., with a different activation interval) needs a new path.
+#[must_use]
+pub fn derive_key_pair<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    master_seed: &[u8; 32],
+    path: &DerivationPath,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+) -> (
+    GeneralizedXMSSPublicKey<TH>,
+    GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+)
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    let seed = derive_seed(master_seed, path);
+    GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::key_gen_from_seed(
+        &seed,
+        activation_epoch,
+        num_active_epochs,
+    )
+}
+
+/// Helper function to hash the domain separator and the given inputs to 32 bytes.
+fn shake(inputs: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Shake128::default();
+    hasher.update(&DERIVATION_DOMAIN_SEP);
+    for input in inputs {
+        hasher.update(input);
+    }
+
+    let mut output = [0u8; 32];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signature::{
+        SignatureScheme,
+        test_fixtures::{IE, LOG_LIFETIME, PRF, Sig, TH},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_derivation_path_parsing() {
+        let path: DerivationPath = "m/17/0".parse().unwrap();
+        assert_eq!(path, DerivationPath::validator(17, 0));
+        assert_eq!(path.to_string(), "m/17/0");
+        assert_eq!(DerivationPath::new(vec![17]).child(0), path);
+
+        let master: DerivationPath = "m".parse().unwrap();
+        assert!(master.indices().is_empty());
+        assert_eq!(master.to_string(), "m");
+
+        assert!(matches!(
+            "17/0".parse::<DerivationPath>(),
+            Err(DerivationPathError::MissingMaster)
+        ));
+        assert!(matches!(
+            "m/17/".parse::<DerivationPath>(),
+            Err(DerivationPathError::InvalidIndex(_))
+        ));
+        assert!(matches!(
+            "m/4294967296".parse::<DerivationPath>(),
+            Err(DerivationPathError::InvalidIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_derive_seed_vectors() {
+        // known answers, these must never change as keys are recovered from master seeds
+        let master_seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        for (path, expected) in [
+            (
+                "m",
+                [
+                    0xe5, 0x79, 0x6a, 0xb0, 0x54, 0xfc, 0xb2, 0xfc, 0xa2, 0xc0, 0x65, 0x3e, 0x94,
+                    0xe6, 0x36, 0x16, 0x01, 0x1c, 0x22, 0x99, 0x3a, 0xc2, 0x84, 0x55, 0xd4, 0xbd,
+                    0xa5, 0xd3, 0xf2, 0xf9, 0xa3, 0xec,
+                ],
+            ),
+            (
+                "m/0",
+                [
+                    0x10, 0xa0, 0xe1, 0x07, 0xdc, 0x33, 0xcd, 0xd7, 0xd2, 0xef, 0x9b, 0xec, 0x40,
+                    0xd6, 0x42, 0xb7, 0xa3, 0x89, 0x16, 0x60, 0x80, 0x4e, 0xaf, 0x08, 0x91, 0x63,
+                    0x03, 0x11, 0xe9, 0xd7, 0x79, 0xec,
+                ],
+            ),
+            (
+                "m/17/0",
+                [
+                    0x02, 0xeb, 0xdb, 0x33, 0x98, 0xe4, 0xf1, 0x7d, 0x3a, 0x62, 0x45, 0x39, 0xe4,
+                    0x9d, 0xb3, 0x4a, 0x24, 0xe7, 0x6e, 0x6e, 0xfe, 0xe4, 0x92, 0xaf, 0xfd, 0x42,
+                    0x81, 0x45, 0x47, 0x92, 0x2e, 0x9a,
+                ],
+            ),
+        ] {
+            let path: DerivationPath = path.parse().unwrap();
+            assert_eq!(derive_seed(&master_seed, &path), expected, "path {path}");
+        }
+    }
+
+    #[test]
+    fn test_derive_key_pair() {
+        let master_seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let path = DerivationPath::validator(17, 0);
+        let (pk, sk) =
+            derive_key_pair::<PRF, IE, TH, LOG_LIFETIME>(&master_seed, &path, 0, 1 << LOG_LIFETIME);
+
+        // known answer for the public key
+        assert_eq!(
+            pk.root,
+            [
+                0x97, 0x5d, 0xde, 0x35, 0x9e, 0x34, 0xe6, 0xd2, 0x28, 0x5f, 0xdf, 0x4c, 0xe1, 0xdf,
+                0x8f, 0xc8, 0x1e, 0x54, 0xca, 0xf4, 0x35, 0x6c, 0x5d, 0x1d
+            ]
+        );
+
+        // the key is the one obtained from the derived seed, and it works
+        let (pk_from_seed, _) =
+            Sig::key_gen_from_seed(&derive_seed(&master_seed, &path), 0, 1 << LOG_LIFETIME);
+        assert_eq!(pk.root, pk_from_seed.root);
+        let message = [0x42; crate::MESSAGE_LENGTH];
+        let signature = Sig::sign(&sk, 9, &message).unwrap();
+        assert!(Sig::verify(&pk, 9, &message, &signature));
+
+        // keys for other paths are different, including prefixes and extensions of the path
+        for other_path in [
+            DerivationPath::validator(17, 1),
+            DerivationPath::validator(18, 0),
+            DerivationPath::new(vec![17]),
+            path.child(0),
+        ] {
+            let (other_pk, other_sk) = derive_key_pair::<PRF, IE, TH, LOG_LIFETIME>(
+                &master_seed,
+                &other_path,
+                0,
+                1 << LOG_LIFETIME,
+            );
+            assert_ne!(other_pk.root, pk.root);
+            assert_ne!(other_pk.parameter, pk.parameter);
+            assert_ne!(other_sk.prf_key, sk.prf_key);
+        }
+    }
+}
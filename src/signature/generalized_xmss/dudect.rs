@@ -0,0 +1,116 @@
+//! Dudect-style timing test for walking the chains when signing.
+//!
+//! We measure the time for walking the chains for two classes of codewords: a fixed
+//! codeword (class 0) and random codewords (class 1), in random order. Then, Welch's
+//! t-test checks whether the timing distributions of both classes differ. As in dudect,
+//! this is also done for measurements cropped at several percentiles, to remove outliers.
+//! A t-statistic above `T_THRESHOLD` is considered a timing leak.
+//!
+//! The measurements are noisy, so this only runs locally (in release mode) via
+//! `cargo test --release --features dudect dudect -- --test-threads=1`.
+
+use std::time::Instant;
+
+use rand::Rng;
+
+use crate::signature::{
+    SignatureScheme,
+    test_fixtures::{BASE, NUM_CHUNKS, Sig},
+};
+
+use super::SigningMode;
+
+/// Number of measurements, split randomly between the two classes.
+const NUM_MEASUREMENTS: usize = 20_000;
+
+/// Threshold for the t-statistic above which we consider the timing to leak.
+const T_THRESHOLD: f64 = 4.5;
+
+/// Percentiles at which the measurements are cropped, in addition to the uncropped measurements.
+const CROP_PERCENTILES: [f64; 3] = [0.5, 0.75, 0.9];
+
+/// Running mean and variance of a class of measurements (Welford's algorithm).
+#[derive(Default)]
+struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+/// Returns Welch's t-statistic for the two classes.
+fn t_statistic(a: &Moments, b: &Moments) -> f64 {
+    (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+}
+
+/// Measures walking the chains in the given mode, and returns the largest absolute
+/// t-statistic, over the uncropped measurements and all cropped measurements.
+fn max_t_statistic(mode: SigningMode) -> f64 {
+    let mut rng = rand::rng();
+    let (_pk, sk) = Sig::key_gen(&mut rng, 0, 16);
+    let epoch = 5;
+    let fixed_codeword = vec![0u8; NUM_CHUNKS];
+
+    let mut measurements = Vec::with_capacity(NUM_MEASUREMENTS);
+    for _ in 0..NUM_MEASUREMENTS {
+        let class = rng.random_bool(0.5);
+        let codeword: Vec<u8> = if class {
+            (0..NUM_CHUNKS)
+                .map(|_| rng.random_range(0..BASE as u8))
+                .collect()
+        } else {
+            fixed_codeword.clone()
+        };
+
+        let start = Instant::now();
+        let hashes = Sig::chain_hashes(&sk.prf_key, &sk.parameter, epoch, &codeword, mode);
+        let elapsed = start.elapsed().as_nanos() as f64;
+        std::hint::black_box(hashes);
+
+        measurements.push((class, elapsed));
+    }
+
+    let mut sorted: Vec<f64> = measurements.iter().map(|&(_, time)| time).collect();
+    sorted.sort_by(f64::total_cmp);
+    let thresholds = CROP_PERCENTILES
+        .iter()
+        .map(|&p| sorted[(p * sorted.len() as f64) as usize])
+        .chain([f64::INFINITY]);
+
+    thresholds
+        .map(|threshold| {
+            let mut classes = [Moments::default(), Moments::default()];
+            for &(class, time) in &measurements {
+                if time <= threshold {
+                    classes[class as usize].push(time);
+                }
+            }
+            t_statistic(&classes[0], &classes[1]).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_dudect_variable_time_leaks() {
+    // sanity check of the test: walking only as far as needed is detected
+    let t = max_t_statistic(SigningMode::VariableTime);
+    assert!(t > T_THRESHOLD, "t-statistic {t} does not detect the leak");
+}
+
+#[test]
+fn test_dudect_constant_time() {
+    let t = max_t_statistic(SigningMode::ConstantTime);
+    assert!(t < T_THRESHOLD, "t-statistic {t} indicates a timing leak");
+}
@@ -0,0 +1,652 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use dashmap::DashMap;
+use p3_field::PrimeCharacteristicRing;
+use p3_field::PrimeField32;
+use sha3::{Digest, Sha3_256};
+#[cfg(feature = "std")]
+use std::sync::LazyLock;
+use thiserror::Error;
+
+use crate::{
+    F,
+    inc_encoding::IncomparableEncoding,
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+};
+
+use super::{
+    GeneralizedXMSSPublicKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme,
+    HashTreeOpening,
+};
+
+/// Version of the byte encoding. It is increased whenever the layout changes.
+pub const FORMAT_VERSION: u8 = 0x01;
+
+/// Length of the header that precedes every encoded object.
+pub const HEADER_LENGTH: usize = 8;
+
+const OBJECT_TYPE_PUBLIC_KEY: u8 = 0x01;
+const OBJECT_TYPE_SIGNATURE: u8 = 0x02;
+
+const ELEMENT_TYPE_BYTES: u8 = 0x00;
+const ELEMENT_TYPE_KOALABEAR: u8 = 0x01;
+
+/// Domain separator for the digest in the scheme identifier, see `scheme_identifier`.
+const SCHEME_IDENTIFIER_DOMAIN_SEP: &[u8] = b"hashsig scheme identifier";
+
+/// Identifier of an instantiation in the header, see `scheme_identifier`.
+type SchemeIdentifier = [u8; HEADER_LENGTH - 2];
+
+/// Number of bytes of the digest in the scheme identifier, see `scheme_identifier`.
+const SCHEME_DIGEST_LENGTH: usize = HEADER_LENGTH - 4;
+
+/// Global cache for the digests in scheme identifiers, so that they are only computed
+/// once per instantiation. They are indexed by the description of the instantiation.
+#[cfg(feature = "std")]
+static SCHEME_DIGESTS: LazyLock<DashMap<Vec<u8>, [u8; SCHEME_DIGEST_LENGTH]>> =
+    LazyLock::new(DashMap::new);
+
+/// Global cache for the digests in scheme identifiers without std.
+#[cfg(not(feature = "std"))]
+static SCHEME_DIGESTS: spin::Mutex<BTreeMap<Vec<u8>, [u8; SCHEME_DIGEST_LENGTH]>> =
+    spin::Mutex::new(BTreeMap::new());
+
+/// Computes the digest of the description of an instantiation.
+fn scheme_digest(description: &[u8]) -> [u8; SCHEME_DIGEST_LENGTH] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(SCHEME_IDENTIFIER_DOMAIN_SEP);
+    hasher.update(description);
+    hasher.finalize()[..SCHEME_DIGEST_LENGTH]
+        .try_into()
+        .unwrap()
+}
+
+/// Returns the digest of the description of an instantiation, which is computed
+/// on the first call and cached afterwards.
+#[cfg(feature = "std")]
+fn cached_scheme_digest(description: Vec<u8>) -> [u8; SCHEME_DIGEST_LENGTH] {
+    if let Some(digest) = SCHEME_DIGESTS.get(description.as_slice()) {
+        return *digest;
+    }
+    let digest = scheme_digest(&description);
+    SCHEME_DIGESTS.insert(description, digest);
+    digest
+}
+
+#[cfg(not(feature = "std"))]
+fn cached_scheme_digest(description: Vec<u8>) -> [u8; SCHEME_DIGEST_LENGTH] {
+    *SCHEME_DIGESTS
+        .lock()
+        .entry(description)
+        .or_insert_with_key(|description| scheme_digest(description))
+}
+
+/// Error enum for decoding public keys and signatures from bytes.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DecodingError {
+    /// Occurs when the input does not have the exact length of the encoded object.
+    #[error("Invalid length: expected {expected} bytes, got {actual} bytes.")]
+    InvalidLength { expected: usize, actual: usize },
+
+    /// Occurs when the input has been encoded with an unknown version of the format.
+    #[error("Unsupported format version {0}.")]
+    UnsupportedVersion(u8),
+
+    /// Occurs when the input encodes a different kind of object,
+    /// e.g., a signature is decoded as a public key.
+    #[error("Expected object type {expected}, got {actual}.")]
+    WrongObjectType { expected: u8, actual: u8 },
+
+    /// Occurs when the input has been encoded for a different instantiation of the scheme.
+    #[error("Scheme identifier {actual:02x?} does not match the expected {expected:02x?}.")]
+    SchemeMismatch {
+        expected: SchemeIdentifier,
+        actual: SchemeIdentifier,
+    },
+
+    /// Occurs when a field element is not given in its canonical form, i.e.,
+    /// its value is not smaller than the modulus.
+    #[error("Field element {value:#010x} is not canonical.")]
+    NonCanonicalFieldElement { value: u32 },
+}
+
+/// Canonical fixed-length byte encoding of the components of keys and signatures.
+///
+/// - Arrays of bytes are encoded as they are.
+/// - Arrays of KoalaBear field elements are encoded as 4 bytes per element, given by the
+///   little-endian encoding of the canonical representative in `[0, p)`. Decoding rejects
+///   all values that are not smaller than `p`, so each element has a unique encoding.
+pub trait CanonicalEncoding: Sized {
+    /// Type of the elements, which is part of the scheme identifier.
+    const ELEMENT_TYPE: u8;
+
+    /// Length of the encoding in bytes.
+    const ENCODED_LENGTH: usize;
+
+    /// Appends the encoding to `out`.
+    fn write_bytes(&self, out: &mut Vec<u8>);
+
+    /// Decodes from bytes, which must have length exactly `ENCODED_LENGTH`.
+    fn read_bytes(bytes: &[u8]) -> Result<Self, DecodingError>;
+}
+
+impl<const N: usize> CanonicalEncoding for [u8; N] {
+    const ELEMENT_TYPE: u8 = ELEMENT_TYPE_BYTES;
+
+    const ENCODED_LENGTH: usize = N;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, DecodingError> {
+        bytes.try_into().map_err(|_| DecodingError::InvalidLength {
+            expected: N,
+            actual: bytes.len(),
+        })
+    }
+}
+
+impl<const N: usize> CanonicalEncoding for [F; N] {
+    const ELEMENT_TYPE: u8 = ELEMENT_TYPE_KOALABEAR;
+
+    const ENCODED_LENGTH: usize = 4 * N;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        for fe in self {
+            out.extend_from_slice(&fe.as_canonical_u32().to_le_bytes());
+        }
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, DecodingError> {
+        if bytes.len() != Self::ENCODED_LENGTH {
+            return Err(DecodingError::InvalidLength {
+                expected: Self::ENCODED_LENGTH,
+                actual: bytes.len(),
+            });
+        }
+
+        let mut elements = [F::ZERO; N];
+        for (fe, chunk) in elements.iter_mut().zip(bytes.chunks_exact(4)) {
+            let value = u32::from_le_bytes(chunk.try_into().unwrap());
+            if value >= F::ORDER_U32 {
+                return Err(DecodingError::NonCanonicalFieldElement { value });
+            }
+            *fe = F::from_u32(value);
+        }
+        Ok(elements)
+    }
+}
+
+/// Splits off the next component from the input. The caller checks the
+/// total length beforehand, so that there are always enough bytes left.
+fn read_next<T: CanonicalEncoding>(bytes: &mut &[u8]) -> Result<T, DecodingError> {
+    let (component, rest) = bytes.split_at(T::ENCODED_LENGTH);
+    *bytes = rest;
+    T::read_bytes(component)
+}
+
+/// Canonical byte encoding of public keys and signatures.
+///
+/// Each encoding starts with a header of `HEADER_LENGTH` bytes:
+///
+/// | offset | length | content                                                  |
+/// |--------|--------|----------------------------------------------------------|
+/// | 0      | 1      | format version (`FORMAT_VERSION`)                        |
+/// | 1      | 1      | object type: `0x01` for public keys, `0x02` for signatures |
+/// | 2      | 6      | scheme identifier, see `scheme_identifier`               |
+///
+/// The header is followed by the components of the object, each encoded using
+/// `CanonicalEncoding`, and without any length prefixes:
+///
+/// - public key: `root || parameter`
+/// - signature: `rho || co_path[0] || ... || co_path[LOG_LIFETIME - 1] || hashes[0] || ... || hashes[DIMENSION - 1]`
+///
+/// So all encodings of an instantiation have a fixed length, and decoding rejects all inputs
+/// that do not have exactly this length.
+impl<PRF, IE, TH, const LOG_LIFETIME: usize>
+    GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    TH::Parameter: CanonicalEncoding,
+    TH::Domain: CanonicalEncoding,
+    IE::Randomness: CanonicalEncoding,
+{
+    /// Length of an encoded public key in bytes.
+    pub const PUBLIC_KEY_LENGTH: usize =
+        HEADER_LENGTH + TH::Domain::ENCODED_LENGTH + TH::Parameter::ENCODED_LENGTH;
+
+    /// Length of an encoded signature in bytes.
+    pub const SIGNATURE_LENGTH: usize = HEADER_LENGTH
+        + IE::Randomness::ENCODED_LENGTH
+        + (LOG_LIFETIME + IE::DIMENSION) * TH::Domain::ENCODED_LENGTH;
+
+    /// Returns the identifier of this instantiation, which is part of the header.
+    ///
+    /// It is given by `LOG_LIFETIME` (1 byte), the type of the hash elements (1 byte,
+    /// `0x00` for bytes and `0x01` for KoalaBear field elements), and the first 4 bytes
+    /// of a SHA3-256 digest that covers the entire instantiation: `LOG_LIFETIME`, the
+    /// tweakable hash, and the encoding including its message hash, each with all of its
+    /// parameters (see `TweakableHash::write_identifier`). The PRF is not covered, as
+    /// it does not affect public keys and signatures.
+    ///
+    /// The digest is computed once per instantiation, and cached afterwards.
+    #[must_use]
+    pub fn scheme_identifier() -> SchemeIdentifier {
+        let digest = cached_scheme_digest(Self::scheme_description());
+        [
+            LOG_LIFETIME as u8,
+            TH::Domain::ELEMENT_TYPE,
+            digest[0],
+            digest[1],
+            digest[2],
+            digest[3],
+        ]
+    }
+
+    /// Returns the description of this instantiation that the digest of the identifier
+    /// covers, see `scheme_identifier`.
+    fn scheme_description() -> Vec<u8> {
+        let mut description = Vec::new();
+        description.extend_from_slice(&(LOG_LIFETIME as u32).to_le_bytes());
+        TH::write_identifier(&mut description);
+        IE::write_identifier(&mut description);
+        description
+    }
+
+    /// Encodes a public key into bytes.
+    #[must_use]
+    pub fn public_key_to_bytes(pk: &GeneralizedXMSSPublicKey<TH>) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::PUBLIC_KEY_LENGTH);
+        Self::write_header(OBJECT_TYPE_PUBLIC_KEY, &mut out);
+        pk.root.write_bytes(&mut out);
+        pk.parameter.write_bytes(&mut out);
+        out
+    }
+
+    /// Decodes a public key from bytes, which must have length exactly `PUBLIC_KEY_LENGTH`.
+    pub fn public_key_from_bytes(
+        bytes: &[u8],
+    ) -> Result<GeneralizedXMSSPublicKey<TH>, DecodingError> {
+        let mut rest = Self::read_header(OBJECT_TYPE_PUBLIC_KEY, Self::PUBLIC_KEY_LENGTH, bytes)?;
+        let root = read_next(&mut rest)?;
+        let parameter = read_next(&mut rest)?;
+        Ok(GeneralizedXMSSPublicKey { root, parameter })
+    }
+
+    /// Encodes a signature into bytes.
+    ///
+    /// Note: the signature must be well-formed, e.g., as produced by `sign`.
+    /// Otherwise, this function panics.
+    #[must_use]
+    pub fn signature_to_bytes(sig: &GeneralizedXMSSSignature<IE, TH>) -> Vec<u8> {
+        assert!(
+            sig.path.co_path.len() == LOG_LIFETIME && sig.hashes.len() == IE::DIMENSION,
+            "Encoding: signature is malformed"
+        );
+        let mut out = Vec::with_capacity(Self::SIGNATURE_LENGTH);
+        Self::write_header(OBJECT_TYPE_SIGNATURE, &mut out);
+        sig.rho.write_bytes(&mut out);
+        for node in &sig.path.co_path {
+            node.write_bytes(&mut out);
+        }
+        for hash in &sig.hashes {
+            hash.write_bytes(&mut out);
+        }
+        out
+    }
+
+    /// Decodes a signature from bytes, which must have length exactly `SIGNATURE_LENGTH`.
+    pub fn signature_from_bytes(
+        bytes: &[u8],
+    ) -> Result<GeneralizedXMSSSignature<IE, TH>, DecodingError> {
+        let mut rest = Self::read_header(OBJECT_TYPE_SIGNATURE, Self::SIGNATURE_LENGTH, bytes)?;
+        let rho = read_next(&mut rest)?;
+        let co_path = (0..LOG_LIFETIME)
+            .map(|_| read_next(&mut rest))
+            .collect::<Result<_, _>>()?;
+        let hashes = (0..IE::DIMENSION)
+            .map(|_| read_next(&mut rest))
+            .collect::<Result<_, _>>()?;
+        Ok(GeneralizedXMSSSignature {
+            path: HashTreeOpening { co_path },
+            rho,
+            hashes,
+        })
+    }
+
+    /// Writes the header for the given object type.
+    fn write_header(object_type: u8, out: &mut Vec<u8>) {
+        out.push(FORMAT_VERSION);
+        out.push(object_type);
+        out.extend_from_slice(&Self::scheme_identifier());
+    }
+
+    /// Checks the header and the total length, and returns the bytes after the header.
+    fn read_header(
+        object_type: u8,
+        expected_length: usize,
+        bytes: &[u8],
+    ) -> Result<&[u8], DecodingError> {
+        if bytes.len() < HEADER_LENGTH {
+            return Err(DecodingError::InvalidLength {
+                expected: expected_length,
+                actual: bytes.len(),
+            });
+        }
+        let (header, rest) = bytes.split_at(HEADER_LENGTH);
+
+        if header[0] != FORMAT_VERSION {
+            return Err(DecodingError::UnsupportedVersion(header[0]));
+        }
+        if header[1] != object_type {
+            return Err(DecodingError::WrongObjectType {
+                expected: object_type,
+                actual: header[1],
+            });
+        }
+        let expected = Self::scheme_identifier();
+        if header[2..] != expected {
+            return Err(DecodingError::SchemeMismatch {
+                expected,
+                actual: header[2..].try_into().unwrap(),
+            });
+        }
+        if bytes.len() != expected_length {
+            return Err(DecodingError::InvalidLength {
+                expected: expected_length,
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        inc_encoding::{basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding},
+        signature::{
+            SignatureScheme,
+            test_fixtures::{EXPECTED_SUM, IE, LOG_LIFETIME, MH, PRF, Sig, TH},
+        },
+        symmetric::{
+            message_hash::{MessageHash, poseidon::PoseidonMessageHashW1, sha::ShaMessageHash},
+            prf::{sha::ShaPRF, shake_to_field::ShakePRFtoF},
+            tweak_hash::{poseidon::PoseidonTweakW1L5, sha::ShaTweakHash},
+        },
+    };
+
+    use super::*;
+
+    type PRFPoseidon = ShakePRFtoF<7, 5>;
+    type THPoseidon = PoseidonTweakW1L5;
+    type MHPoseidon = PoseidonMessageHashW1;
+    type IEPoseidon =
+        TargetSumEncoding<MHPoseidon, { MHPoseidon::DIMENSION * (MHPoseidon::BASE - 1) / 2 }>;
+    type SigPoseidon = GeneralizedXMSSSignatureScheme<PRFPoseidon, IEPoseidon, THPoseidon, 6>;
+
+    #[test]
+    fn test_field_element_encoding() {
+        let elements = [
+            F::ZERO,
+            F::ONE,
+            F::from_u32(0x1234_5678),
+            F::from_u32(F::ORDER_U32 - 1),
+        ];
+        let mut bytes = Vec::new();
+        elements.write_bytes(&mut bytes);
+        assert_eq!(
+            bytes,
+            [
+                0x00, 0x00, 0x00, 0x00, // 0
+                0x01, 0x00, 0x00, 0x00, // 1
+                0x78, 0x56, 0x34, 0x12, // 0x12345678
+                0x00, 0x00, 0x00, 0x7f, // p - 1 = 0x7f000000
+            ]
+        );
+        assert_eq!(<[F; 4]>::read_bytes(&bytes), Ok(elements));
+
+        // values that are not reduced modulo p are rejected
+        for value in [F::ORDER_U32, F::ORDER_U32 + 1, u32::MAX] {
+            assert_eq!(
+                <[F; 1]>::read_bytes(&value.to_le_bytes()),
+                Err(DecodingError::NonCanonicalFieldElement { value })
+            );
+        }
+
+        assert_eq!(
+            <[F; 4]>::read_bytes(&bytes[1..]),
+            Err(DecodingError::InvalidLength {
+                expected: 16,
+                actual: 15
+            })
+        );
+    }
+
+    #[test]
+    fn test_golden_vectors() {
+        // known answers for format version 0x01, these must never change as encodings
+        // are stored and exchanged
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let (pk, sk) = Sig::key_gen_from_seed(&seed, 0, 1 << LOG_LIFETIME);
+
+        let pk_bytes = Sig::public_key_to_bytes(&pk);
+        assert_eq!(Sig::PUBLIC_KEY_LENGTH, 8 + 24 + 24);
+        assert_eq!(
+            pk_bytes,
+            [
+                // header: version, object type, LOG_LIFETIME, element type, digest
+                0x01, 0x01, 0x06, 0x00, 0x33, 0xef, 0x0f, 0xce, //
+                // root
+                0x3d, 0x58, 0x10, 0xac, 0x34, 0x7a, 0x79, 0x00, 0x55, 0x34, 0x4e, 0x4f, //
+                0x7d, 0xdb, 0xb0, 0xe4, 0xe7, 0x2c, 0xdd, 0x06, 0xab, 0x76, 0x96, 0x47, //
+                // parameter
+                0x0d, 0xc6, 0xdf, 0xfe, 0x78, 0x88, 0xa6, 0x88, 0x7d, 0x00, 0x70, 0x93, //
+                0xd7, 0x38, 0x63, 0x6d, 0x02, 0xba, 0x89, 0x96, 0x5f, 0x47, 0x06, 0x28, //
+            ]
+        );
+
+        let message = [0x42; crate::MESSAGE_LENGTH];
+        let signature = Sig::sign(&sk, 3, &message).unwrap();
+        let sig_bytes = Sig::signature_to_bytes(&signature);
+        assert_eq!(Sig::SIGNATURE_LENGTH, 8 + 24 + (6 + 48) * 24);
+        assert_eq!(sig_bytes.len(), Sig::SIGNATURE_LENGTH);
+        assert_eq!(
+            sig_bytes[..8],
+            [0x01, 0x02, 0x06, 0x00, 0x33, 0xef, 0x0f, 0xce]
+        );
+        // the signature is long, so we compare its SHA3-256 digest
+        assert_eq!(
+            Sha3_256::digest(&sig_bytes)[..],
+            [
+                0x41, 0xc1, 0x09, 0x42, 0x01, 0x11, 0x06, 0x53, 0xcb, 0xfe, 0x84, 0xf1, 0x32, 0x5f,
+                0x83, 0x08, 0xfc, 0x7b, 0x5c, 0x62, 0xc7, 0xc4, 0x78, 0x53, 0x18, 0x35, 0x21, 0xae,
+                0x57, 0x03, 0x41, 0x38
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        fn round_trip<S: SignatureScheme>(
+            public_key_to_bytes: fn(&S::PublicKey) -> Vec<u8>,
+            public_key_from_bytes: fn(&[u8]) -> Result<S::PublicKey, DecodingError>,
+            signature_to_bytes: fn(&S::Signature) -> Vec<u8>,
+            signature_from_bytes: fn(&[u8]) -> Result<S::Signature, DecodingError>,
+        ) {
+            let mut rng = rand::rng();
+            let (pk, sk) = S::key_gen(&mut rng, 0, S::LIFETIME as usize);
+            let epoch = 11;
+            let message = [0x17; crate::MESSAGE_LENGTH];
+            let signature = S::sign(&sk, epoch, &message).unwrap();
+
+            let pk_bytes = public_key_to_bytes(&pk);
+            let sig_bytes = signature_to_bytes(&signature);
+            let decoded_pk = public_key_from_bytes(&pk_bytes).unwrap();
+            let decoded_sig = signature_from_bytes(&sig_bytes).unwrap();
+            assert!(S::verify(&decoded_pk, epoch, &message, &decoded_sig));
+
+            // the encoding is canonical
+            assert_eq!(public_key_to_bytes(&decoded_pk), pk_bytes);
+            assert_eq!(signature_to_bytes(&decoded_sig), sig_bytes);
+        }
+
+        round_trip::<Sig>(
+            Sig::public_key_to_bytes,
+            Sig::public_key_from_bytes,
+            Sig::signature_to_bytes,
+            Sig::signature_from_bytes,
+        );
+        round_trip::<SigPoseidon>(
+            SigPoseidon::public_key_to_bytes,
+            SigPoseidon::public_key_from_bytes,
+            SigPoseidon::signature_to_bytes,
+            SigPoseidon::signature_from_bytes,
+        );
+    }
+
+    #[test]
+    fn test_scheme_identifier() {
+        // instantiations that only differ in the target sum, the kind of encoding, the
+        // parameter length, or the message hash have the same lifetime, element type,
+        // dimension, and base, but distinct identifiers
+        type OtherSumIE = TargetSumEncoding<MH, { EXPECTED_SUM + 1 }>;
+        type WinternitzIE = WinternitzEncoding<MH, 4, 0>;
+        type OtherParameterTH = ShaTweakHash<16, 24>;
+        type OtherMH = ShaMessageHash<24, 16, 48, 4>;
+        type OtherLifetimeSig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, 8>;
+        let identifiers = [
+            Sig::scheme_identifier(),
+            GeneralizedXMSSSignatureScheme::<PRF, OtherSumIE, TH, LOG_LIFETIME>::scheme_identifier(),
+            GeneralizedXMSSSignatureScheme::<PRF, WinternitzIE, TH, LOG_LIFETIME>::scheme_identifier(),
+            GeneralizedXMSSSignatureScheme::<PRF, IE, OtherParameterTH, LOG_LIFETIME>::scheme_identifier(),
+            GeneralizedXMSSSignatureScheme::<PRF, TargetSumEncoding<OtherMH, EXPECTED_SUM>, TH, LOG_LIFETIME>::scheme_identifier(),
+        ];
+        for (i, identifier) in identifiers.iter().enumerate() {
+            assert_eq!(identifier[..2], [LOG_LIFETIME as u8, ELEMENT_TYPE_BYTES]);
+            assert!(identifiers[i + 1..].iter().all(|other| other != identifier));
+        }
+
+        // the PRF does not affect the identifier
+        assert_eq!(
+            GeneralizedXMSSSignatureScheme::<ShaPRF<16, 24>, IE, TH, LOG_LIFETIME>::scheme_identifier(),
+            Sig::scheme_identifier()
+        );
+
+        // cached digests are the computed ones, also for another lifetime
+        for (identifier, description) in [
+            (Sig::scheme_identifier(), Sig::scheme_description()),
+            (
+                OtherLifetimeSig::scheme_identifier(),
+                OtherLifetimeSig::scheme_description(),
+            ),
+        ] {
+            assert_eq!(identifier[2..], scheme_digest(&description));
+        }
+        assert_ne!(
+            OtherLifetimeSig::scheme_identifier(),
+            Sig::scheme_identifier()
+        );
+    }
+
+    #[test]
+    fn test_decoding_errors() {
+        type OtherSig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, 8>;
+
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let (pk, sk) = Sig::key_gen_from_seed(&seed, 0, 1 << LOG_LIFETIME);
+        let signature = Sig::sign(&sk, 3, &[0x42; crate::MESSAGE_LENGTH]).unwrap();
+        let pk_bytes = Sig::public_key_to_bytes(&pk);
+        let sig_bytes = Sig::signature_to_bytes(&signature);
+
+        // truncated and extended inputs
+        for length in [
+            0,
+            HEADER_LENGTH - 1,
+            HEADER_LENGTH,
+            Sig::PUBLIC_KEY_LENGTH - 1,
+        ] {
+            assert_eq!(
+                Sig::public_key_from_bytes(&pk_bytes[..length]).err(),
+                Some(DecodingError::InvalidLength {
+                    expected: Sig::PUBLIC_KEY_LENGTH,
+                    actual: length
+                })
+            );
+        }
+        let mut extended = pk_bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Sig::public_key_from_bytes(&extended).err(),
+            Some(DecodingError::InvalidLength {
+                expected: Sig::PUBLIC_KEY_LENGTH,
+                actual: Sig::PUBLIC_KEY_LENGTH + 1
+            })
+        );
+        assert_eq!(
+            Sig::signature_from_bytes(&sig_bytes[..sig_bytes.len() - 1]).err(),
+            Some(DecodingError::InvalidLength {
+                expected: Sig::SIGNATURE_LENGTH,
+                actual: Sig::SIGNATURE_LENGTH - 1
+            })
+        );
+
+        // unknown versions
+        for version in [0x00, 0x02] {
+            let mut wrong_version = pk_bytes.clone();
+            wrong_version[0] = version;
+            assert_eq!(
+                Sig::public_key_from_bytes(&wrong_version).err(),
+                Some(DecodingError::UnsupportedVersion(version))
+            );
+        }
+
+        // a signature is not a public key, and vice versa
+        assert_eq!(
+            Sig::public_key_from_bytes(&sig_bytes).err(),
+            Some(DecodingError::WrongObjectType {
+                expected: 0x01,
+                actual: 0x02
+            })
+        );
+        assert_eq!(
+            Sig::signature_from_bytes(&pk_bytes).err(),
+            Some(DecodingError::WrongObjectType {
+                expected: 0x02,
+                actual: 0x01
+            })
+        );
+
+        // encodings for another instantiation are rejected
+        assert_eq!(
+            OtherSig::public_key_from_bytes(&pk_bytes).err(),
+            Some(DecodingError::SchemeMismatch {
+                expected: OtherSig::scheme_identifier(),
+                actual: Sig::scheme_identifier()
+            })
+        );
+        let mut rng = rand::rng();
+        let (poseidon_pk, _) = SigPoseidon::key_gen(&mut rng, 0, SigPoseidon::LIFETIME as usize);
+        assert!(matches!(
+            Sig::public_key_from_bytes(&SigPoseidon::public_key_to_bytes(&poseidon_pk)),
+            Err(DecodingError::SchemeMismatch { .. })
+        ));
+
+        // field elements must be canonical
+        let mut poseidon_pk_bytes = SigPoseidon::public_key_to_bytes(&poseidon_pk);
+        poseidon_pk_bytes[HEADER_LENGTH..HEADER_LENGTH + 4]
+            .copy_from_slice(&F::ORDER_U32.to_le_bytes());
+        assert_eq!(
+            SigPoseidon::public_key_from_bytes(&poseidon_pk_bytes).err(),
+            Some(DecodingError::NonCanonicalFieldElement {
+                value: F::ORDER_U32
+            })
+        );
+    }
+}
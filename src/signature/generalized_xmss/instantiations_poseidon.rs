@@ -4,7 +4,9 @@ pub mod lifetime_2_to_the_18 {
     pub mod winternitz {
         use crate::{
             inc_encoding::basic_winternitz::WinternitzEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::poseidon::PoseidonMessageHash, prf::shake_to_field::ShakePRFtoF,
                 tweak_hash::poseidon::PoseidonTweakHash,
@@ -41,6 +43,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 1
         pub type SIGWinternitzLifetime18W1 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W1`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W1Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W2: usize = 2;
         const BASE_W2: usize = 4;
@@ -63,6 +68,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 2
         pub type SIGWinternitzLifetime18W2 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W2`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W2Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W4: usize = 4;
         const BASE_W4: usize = 16;
@@ -85,6 +93,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 4
         pub type SIGWinternitzLifetime18W4 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W4`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W4Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W8: usize = 8;
         const BASE_W8: usize = 256;
@@ -107,6 +118,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 8
         pub type SIGWinternitzLifetime18W8 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W8`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W8Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -179,7 +193,9 @@ pub mod lifetime_2_to_the_18 {
     pub mod target_sum {
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::poseidon::PoseidonMessageHash, prf::shake_to_field::ShakePRFtoF,
                 tweak_hash::poseidon::PoseidonTweakHash,
@@ -216,10 +232,16 @@ pub mod lifetime_2_to_the_18 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime18W1NoOff =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<78>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W1NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W1NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<78>, THw1, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 1,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime18W1Off10 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<86>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W1Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W1Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<86>, THw1, LOG_LIFETIME>;
 
         const _CHUNK_SIZE_W2: usize = 2;
         const BASE_W2: usize = 4;
@@ -242,10 +264,16 @@ pub mod lifetime_2_to_the_18 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime18W2NoOff =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<117>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W2NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W2NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<117>, THw2, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 2,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime18W2Off10 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<129>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W2Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W2Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<129>, THw2, LOG_LIFETIME>;
 
         const _CHUNK_SIZE_W4: usize = 4;
         const BASE_W4: usize = 16;
@@ -268,10 +296,16 @@ pub mod lifetime_2_to_the_18 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime18W4NoOff =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<293>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W4NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W4NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<293>, THw4, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 4,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime18W4Off10 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<322>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W4Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W4Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<322>, THw4, LOG_LIFETIME>;
 
         const _CHUNK_SIZE_W8: usize = 8;
         const BASE_W8: usize = 256;
@@ -297,6 +331,9 @@ pub mod lifetime_2_to_the_18 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime18W8NoOff =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2550>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W8NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W8NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2550>, THw8, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 8,
         /// and target sum set at 1.1 * expectation (10% offset)
         ///
@@ -304,6 +341,9 @@ pub mod lifetime_2_to_the_18 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime18W8Off10 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2805>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W8Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W8Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2805>, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -406,7 +446,9 @@ pub mod lifetime_2_to_the_20 {
     pub mod winternitz {
         use crate::{
             inc_encoding::basic_winternitz::WinternitzEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::poseidon::PoseidonMessageHash, prf::shake_to_field::ShakePRFtoF,
                 tweak_hash::poseidon::PoseidonTweakHash,
@@ -443,6 +485,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 1
         pub type SIGWinternitzLifetime20W1 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W1`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W1Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W2: usize = 2;
         const BASE_W2: usize = 4;
@@ -465,6 +510,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 2
         pub type SIGWinternitzLifetime20W2 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W2`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W2Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W4: usize = 4;
         const BASE_W4: usize = 16;
@@ -487,6 +535,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 4
         pub type SIGWinternitzLifetime20W4 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W4`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W4Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
 
         const HASH_LEN_FE_W8: usize = 8;
         const CHUNK_SIZE_W8: usize = 8;
@@ -510,6 +561,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 8
         pub type SIGWinternitzLifetime20W8 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W8`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W8Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -582,7 +636,9 @@ pub mod lifetime_2_to_the_20 {
     pub mod target_sum {
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::poseidon::PoseidonMessageHash, prf::shake_to_field::ShakePRFtoF,
                 tweak_hash::poseidon::PoseidonTweakHash,
@@ -619,10 +675,16 @@ pub mod lifetime_2_to_the_20 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime20W1NoOff =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<78>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W1NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W1NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<78>, THw1, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 1,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime20W1Off10 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<86>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W1Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W1Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<86>, THw1, LOG_LIFETIME>;
 
         const _CHUNK_SIZE_W2: usize = 2;
         const BASE_W2: usize = 4;
@@ -645,10 +707,16 @@ pub mod lifetime_2_to_the_20 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime20W2NoOff =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<117>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W2NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W2NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<117>, THw2, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 2,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime20W2Off10 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<129>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W2Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W2Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<129>, THw2, LOG_LIFETIME>;
 
         const _CHUNK_SIZE_W4: usize = 4;
         const BASE_W4: usize = 16;
@@ -671,10 +739,16 @@ pub mod lifetime_2_to_the_20 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime20W4NoOff =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<293>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W4NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W4NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<293>, THw4, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 4,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime20W4Off10 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<322>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W4Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W4Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<322>, THw4, LOG_LIFETIME>;
 
         const HASH_LEN_FE_W8: usize = 8;
         const _CHUNK_SIZE_W8: usize = 8;
@@ -701,6 +775,9 @@ pub mod lifetime_2_to_the_20 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime20W8NoOff =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2550>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W8NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W8NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2550>, THw8, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 8,
         /// and target sum set at 1.1 * expectation (10% offset)
         ///
@@ -708,6 +785,9 @@ pub mod lifetime_2_to_the_20 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime20W8Off10 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2805>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W8Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W8Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2805>, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
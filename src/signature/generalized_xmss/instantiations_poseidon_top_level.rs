@@ -2,7 +2,9 @@
 pub mod lifetime_2_to_the_18 {
     use crate::{
         inc_encoding::target_sum::TargetSumEncoding,
-        signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+        signature::generalized_xmss::{
+            GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+        },
         symmetric::{
             message_hash::top_level_poseidon::TopLevelPoseidonMessageHash,
             prf::shake_to_field::ShakePRFtoF, tweak_hash::poseidon::PoseidonTweakHash,
@@ -45,6 +47,9 @@ pub mod lifetime_2_to_the_18 {
 
     pub type SIGTopLevelTargetSumLifetime18Dim64Base8 =
         GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+    /// Same as `SIGTopLevelTargetSumLifetime18Dim64Base8`, but with a Merkle tree traversal in the secret key
+    pub type SIGTopLevelTargetSumLifetime18Dim64Base8Traversal =
+        GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
 
     #[cfg(test)]
     mod test {
@@ -86,7 +91,9 @@ pub mod lifetime_2_to_the_32 {
 
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::top_level_poseidon::TopLevelPoseidonMessageHash,
                 prf::shake_to_field::ShakePRFtoF, tweak_hash::poseidon::PoseidonTweakHash,
@@ -130,6 +137,9 @@ pub mod lifetime_2_to_the_32 {
 
         pub type SIGTopLevelTargetSumLifetime32Dim64Base8 =
             GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        /// Same as `SIGTopLevelTargetSumLifetime32Dim64Base8`, but with a Merkle tree traversal in the secret key
+        pub type SIGTopLevelTargetSumLifetime32Dim64Base8Traversal =
+            GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -167,7 +177,9 @@ pub mod lifetime_2_to_the_32 {
 
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::top_level_poseidon::TopLevelPoseidonMessageHash,
                 prf::shake_to_field::ShakePRFtoF, tweak_hash::poseidon::PoseidonTweakHash,
@@ -211,6 +223,9 @@ pub mod lifetime_2_to_the_32 {
 
         pub type SIGTopLevelTargetSumLifetime32Dim48Base10 =
             GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        /// Same as `SIGTopLevelTargetSumLifetime32Dim48Base10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTopLevelTargetSumLifetime32Dim48Base10Traversal =
+            GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -247,7 +262,9 @@ pub mod lifetime_2_to_the_32 {
     pub mod size_optimized {
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::top_level_poseidon::TopLevelPoseidonMessageHash,
                 prf::shake_to_field::ShakePRFtoF, tweak_hash::poseidon::PoseidonTweakHash,
@@ -291,6 +308,9 @@ pub mod lifetime_2_to_the_32 {
 
         pub type SIGTopLevelTargetSumLifetime32Dim32Base26 =
             GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        /// Same as `SIGTopLevelTargetSumLifetime32Dim32Base26`, but with a Merkle tree traversal in the secret key
+        pub type SIGTopLevelTargetSumLifetime32Dim32Base26Traversal =
+            GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -330,7 +350,9 @@ pub mod lifetime_2_to_the_32 {
 pub mod lifetime_2_to_the_8 {
     use crate::{
         inc_encoding::target_sum::TargetSumEncoding,
-        signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+        signature::generalized_xmss::{
+            GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+        },
         symmetric::{
             message_hash::top_level_poseidon::TopLevelPoseidonMessageHash,
             prf::shake_to_field::ShakePRFtoF, tweak_hash::poseidon::PoseidonTweakHash,
@@ -376,6 +398,9 @@ pub mod lifetime_2_to_the_8 {
 
     pub type SIGTopLevelTargetSumLifetime8Dim64Base8 =
         GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+    /// Same as `SIGTopLevelTargetSumLifetime8Dim64Base8`, but with a Merkle tree traversal in the secret key
+    pub type SIGTopLevelTargetSumLifetime8Dim64Base8Traversal =
+        GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
 
     #[cfg(test)]
     mod test {
@@ -386,6 +411,8 @@ pub mod lifetime_2_to_the_8 {
         use crate::signature::test_templates::test_signature_scheme_correctness;
 
         use super::SIGTopLevelTargetSumLifetime8Dim64Base8;
+        #[cfg(feature = "slow-tests")]
+        use super::SIGTopLevelTargetSumLifetime8Dim64Base8Traversal;
 
         #[test]
         pub fn test_internal_consistency() {
@@ -405,6 +432,11 @@ pub mod lifetime_2_to_the_8 {
                 0,
                 SIGTopLevelTargetSumLifetime8Dim64Base8::LIFETIME as usize,
             );
+            test_signature_scheme_correctness::<SIGTopLevelTargetSumLifetime8Dim64Base8Traversal>(
+                213,
+                0,
+                SIGTopLevelTargetSumLifetime8Dim64Base8Traversal::LIFETIME as usize,
+            );
         }
     }
 }
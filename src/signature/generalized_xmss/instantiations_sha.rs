@@ -4,7 +4,9 @@ pub mod lifetime_2_to_the_18 {
     pub mod winternitz {
         use crate::{
             inc_encoding::basic_winternitz::WinternitzEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
             },
@@ -25,6 +27,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 1
         pub type SIGWinternitzLifetime18W1 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W1`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W1Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W2: usize = 2;
         const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
@@ -36,6 +41,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 2
         pub type SIGWinternitzLifetime18W2 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W2`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W2Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W4: usize = 4;
         const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
@@ -47,6 +55,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 4
         pub type SIGWinternitzLifetime18W4 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W4`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W4Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W8: usize = 8;
         const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
@@ -58,6 +69,9 @@ pub mod lifetime_2_to_the_18 {
         /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 8
         pub type SIGWinternitzLifetime18W8 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime18W8`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime18W8Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -130,7 +144,9 @@ pub mod lifetime_2_to_the_18 {
     pub mod target_sum {
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
             },
@@ -152,10 +168,16 @@ pub mod lifetime_2_to_the_18 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime18W1NoOff =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<72>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W1NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W1NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<72>, THw1, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 1,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime18W1Off10 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<80>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W1Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W1Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<80>, THw1, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W2: usize = 2;
         const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
@@ -168,10 +190,16 @@ pub mod lifetime_2_to_the_18 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime18W2NoOff =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<108>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W2NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W2NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<108>, THw2, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 2,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime18W2Off10 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<119>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W2Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W2Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<119>, THw2, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W4: usize = 4;
         const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
@@ -184,10 +212,16 @@ pub mod lifetime_2_to_the_18 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime18W4NoOff =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<270>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W4NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W4NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<270>, THw4, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 4,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime18W4Off10 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<297>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W4Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W4Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<297>, THw4, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W8: usize = 8;
         const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
@@ -203,6 +237,9 @@ pub mod lifetime_2_to_the_18 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime18W8NoOff =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2295>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W8NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W8NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2295>, THw8, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 8,
         /// and target sum set at 1.1 * expectation (10% offset)
         ///
@@ -210,19 +247,22 @@ pub mod lifetime_2_to_the_18 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime18W8Off10 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2525>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime18W8Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime18W8Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2525>, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
-            use crate::signature::SignatureScheme;
-
-            #[cfg(feature = "slow-tests")]
-            use crate::signature::test_templates::test_signature_scheme_correctness;
+            use crate::signature::{
+                SignatureScheme, test_templates::test_signature_scheme_correctness,
+            };
 
             use super::{
                 SIGTargetSumLifetime18W1NoOff, SIGTargetSumLifetime18W1Off10,
-                SIGTargetSumLifetime18W2NoOff, SIGTargetSumLifetime18W2Off10,
-                SIGTargetSumLifetime18W4NoOff, SIGTargetSumLifetime18W4Off10,
-                SIGTargetSumLifetime18W8NoOff, SIGTargetSumLifetime18W8Off10,
+                SIGTargetSumLifetime18W1Off10Traversal, SIGTargetSumLifetime18W2NoOff,
+                SIGTargetSumLifetime18W2Off10, SIGTargetSumLifetime18W4NoOff,
+                SIGTargetSumLifetime18W4Off10, SIGTargetSumLifetime18W8NoOff,
+                SIGTargetSumLifetime18W8Off10,
             };
 
             #[test]
@@ -289,6 +329,14 @@ pub mod lifetime_2_to_the_18 {
                 );
             }
             #[test]
+            pub fn test_w1_traversal_correctness() {
+                // the activation interval consists of two bottom trees, so this is fast
+                // enough to run without `slow-tests`
+                test_signature_scheme_correctness::<SIGTargetSumLifetime18W1Off10Traversal>(
+                    1030, 1024, 16,
+                );
+            }
+            #[test]
             #[cfg(feature = "slow-tests")]
             pub fn test_w8_correctness() {
                 test_signature_scheme_correctness::<SIGTargetSumLifetime18W8NoOff>(
@@ -313,7 +361,9 @@ pub mod lifetime_2_to_the_20 {
     pub mod winternitz {
         use crate::{
             inc_encoding::basic_winternitz::WinternitzEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
             },
@@ -334,6 +384,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 1
         pub type SIGWinternitzLifetime20W1 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W1`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W1Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W2: usize = 2;
         const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
@@ -345,6 +398,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 2
         pub type SIGWinternitzLifetime20W2 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W2`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W2Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W4: usize = 4;
         const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
@@ -356,6 +412,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 4
         pub type SIGWinternitzLifetime20W4 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W4`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W4Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W8: usize = 8;
         const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
@@ -367,6 +426,9 @@ pub mod lifetime_2_to_the_20 {
         /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 8
         pub type SIGWinternitzLifetime20W8 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
+        /// Same as `SIGWinternitzLifetime20W8`, but with a Merkle tree traversal in the secret key
+        pub type SIGWinternitzLifetime20W8Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -440,7 +502,9 @@ pub mod lifetime_2_to_the_20 {
     pub mod target_sum {
         use crate::{
             inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+            signature::generalized_xmss::{
+                GeneralizedXMSSSignatureScheme, traversal::GeneralizedXMSSTraversalScheme,
+            },
             symmetric::{
                 message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
             },
@@ -462,10 +526,16 @@ pub mod lifetime_2_to_the_20 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime20W1NoOff =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<72>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W1NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W1NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<72>, THw1, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 1,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime20W1Off10 =
             GeneralizedXMSSSignatureScheme<PRFw1, IEw1<80>, THw1, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W1Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W1Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw1, IEw1<80>, THw1, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W2: usize = 2;
         const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
@@ -478,10 +548,16 @@ pub mod lifetime_2_to_the_20 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime20W2NoOff =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<108>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W2NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W2NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<108>, THw2, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 2,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime20W2Off10 =
             GeneralizedXMSSSignatureScheme<PRFw2, IEw2<119>, THw2, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W2Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W2Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw2, IEw2<119>, THw2, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W4: usize = 4;
         const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
@@ -494,10 +570,16 @@ pub mod lifetime_2_to_the_20 {
         /// and target sum set at expectation
         pub type SIGTargetSumLifetime20W4NoOff =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<270>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W4NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W4NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<270>, THw4, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 4,
         /// and target sum set at 1.1 * expectation (10% offset)
         pub type SIGTargetSumLifetime20W4Off10 =
             GeneralizedXMSSSignatureScheme<PRFw4, IEw4<297>, THw4, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W4Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W4Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw4, IEw4<297>, THw4, LOG_LIFETIME>;
 
         const CHUNK_SIZE_W8: usize = 8;
         const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
@@ -513,6 +595,9 @@ pub mod lifetime_2_to_the_20 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime20W8NoOff =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2295>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W8NoOff`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W8NoOffTraversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2295>, THw8, LOG_LIFETIME>;
         /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 8,
         /// and target sum set at 1.1 * expectation (10% offset)
         ///
@@ -520,6 +605,9 @@ pub mod lifetime_2_to_the_20 {
         /// and so signing may fail from time to time. It is not recommended to use this.
         pub type SIGTargetSumLifetime20W8Off10 =
             GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2525>, THw8, LOG_LIFETIME>;
+        /// Same as `SIGTargetSumLifetime20W8Off10`, but with a Merkle tree traversal in the secret key
+        pub type SIGTargetSumLifetime20W8Off10Traversal =
+            GeneralizedXMSSTraversalScheme<PRFw8, IEw8<2525>, THw8, LOG_LIFETIME>;
 
         #[cfg(test)]
         mod test {
@@ -0,0 +1,444 @@
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    MESSAGE_LENGTH,
+    inc_encoding::IncomparableEncoding,
+    parallelism::Parallelism,
+    signature::{PreparationError, SignatureScheme, SignatureSchemeSecretKey, SigningError},
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+};
+
+use super::{GeneralizedXMSSSecretKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme};
+
+/// Status of the background preparation of a `ManagedSecretKey`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreparationStatus {
+    /// No bottom tree is being computed, and the prepared interval can still be advanced.
+    Idle,
+    /// The bottom tree with the given index is being computed in the background.
+    InProgress { bottom_tree_index: usize },
+    /// The prepared interval reaches the end of the activation interval,
+    /// so there is nothing left to prepare.
+    Exhausted,
+    /// The computation of the bottom tree with the given index has panicked. No further
+    /// computation is started in the background, see `ManagedSecretKey::prepare_for_epoch`.
+    Failed { bottom_tree_index: usize },
+}
+
+/// Background thread computing the next bottom tree.
+struct Worker {
+    bottom_tree_index: usize,
+    handle: JoinHandle<()>,
+}
+
+/// State of the background preparation.
+#[derive(Default)]
+struct Preparation {
+    /// The background thread, as long as it has not been joined.
+    worker: Option<Worker>,
+    /// The index of the bottom tree whose computation has panicked, if any.
+    failed_bottom_tree_index: Option<usize>,
+}
+
+impl Preparation {
+    /// Joins the background thread if it has finished, or waits for it to finish
+    /// if `block` is set. A panic of the thread is recorded as a failure.
+    fn join(&mut self, block: bool) {
+        if !block
+            && self
+                .worker
+                .as_ref()
+                .is_some_and(|running| !running.handle.is_finished())
+        {
+            return;
+        }
+        if let Some(worker) = self.worker.take() {
+            if worker.handle.join().is_err() {
+                self.failed_bottom_tree_index = Some(worker.bottom_tree_index);
+            }
+        }
+    }
+}
+
+/// Handle for a `GeneralizedXMSSSecretKey` that advances the prepared interval
+/// automatically and in the background.
+///
+/// Once signing enters the second half of the prepared interval, the next bottom tree is
+/// computed on a background thread. While this happens, signing continues with the current
+/// prepared interval. When the bottom tree is ready, it is swapped into the key, which
+/// only takes a short write lock. So signing never waits for a bottom tree to be built,
+/// as long as there is enough time between entering the second half of the prepared interval
+/// and leaving it, which is the number of leafs of a bottom tree (by default, about
+/// `sqrt(LIFETIME)` epochs). For keys with more than two levels, the background thread also
+/// computes the sub-trees of higher levels that contain the next bottom tree, if needed.
+///
+/// Note: after the swap, the key is no longer prepared for the first half of the old
+/// prepared interval. This is fine if epochs are signed in increasing order.
+///
+/// Signing never panics or blocks because of the background computation: signing for an
+/// epoch that is not prepared yet returns an error, and if the computation panics, this is
+/// reported by `status`.
+///
+/// The handle can be shared between threads, e.g., using an `Arc`.
+pub struct ManagedSecretKey<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> {
+    sk: Arc<RwLock<GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>>>,
+    preparation: Mutex<Preparation>,
+}
+
+impl<PRF, IE, TH, const LOG_LIFETIME: usize> ManagedSecretKey<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF: Pseudorandom + 'static,
+    IE: IncomparableEncoding + Sync + Send + 'static,
+    TH: TweakableHash + 'static,
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>: Send + Sync,
+{
+    /// Creates a handle that manages the preparation of the given secret key.
+    pub fn new(sk: GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>) -> Self {
+        Self {
+            sk: Arc::new(RwLock::new(sk)),
+            preparation: Mutex::new(Preparation::default()),
+        }
+    }
+
+    /// Returns read access to the managed secret key, e.g., to serialize it.
+    ///
+    /// Note: while the returned guard is alive, a finished bottom tree cannot be swapped in.
+    pub fn secret_key(
+        &self,
+    ) -> RwLockReadGuard<'_, GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>> {
+        // swapping in a bottom tree cannot panic, so the lock cannot be poisoned in a
+        // way that leaves the key in an inconsistent state.
+        self.sk.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the activation interval of the managed secret key.
+    pub fn get_activation_interval(&self) -> Range<u64> {
+        self.secret_key().get_activation_interval()
+    }
+
+    /// Returns the interval for which the managed secret key is currently prepared.
+    pub fn get_prepared_interval(&self) -> Range<u64> {
+        self.secret_key().get_prepared_interval()
+    }
+
+    /// Returns the state of the background preparation.
+    fn preparation(&self) -> MutexGuard<'_, Preparation> {
+        // the state is only modified by joining the worker, which does not panic
+        self.preparation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the status of the background preparation.
+    pub fn status(&self) -> PreparationStatus {
+        let mut preparation = self.preparation();
+        preparation.join(false);
+        if let Some(worker) = preparation.worker.as_ref() {
+            return PreparationStatus::InProgress {
+                bottom_tree_index: worker.bottom_tree_index,
+            };
+        }
+        if let Some(bottom_tree_index) = preparation.failed_bottom_tree_index {
+            return PreparationStatus::Failed { bottom_tree_index };
+        }
+        drop(preparation);
+
+        if self.secret_key().next_bottom_tree_index().is_some() {
+            PreparationStatus::Idle
+        } else {
+            PreparationStatus::Exhausted
+        }
+    }
+
+    /// Signs a message for the given epoch, see `SignatureScheme::sign`.
+    ///
+    /// If the epoch is in the second half of the prepared interval, this
+    /// starts computing the next bottom tree in the background.
+    ///
+    /// Returns `SigningError::EpochNotPrepared` if the epoch is after the prepared interval,
+    /// e.g., if the next bottom tree is still being computed, and does not wait for it.
+    pub fn sign(
+        &self,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<GeneralizedXMSSSignature<IE, TH>, SigningError> {
+        // scheduling first lets the background thread run while we sign. This is fine,
+        // as the swap keeps the second half of the prepared interval prepared.
+        self.schedule_preparation(epoch);
+        // the intervals are checked by the scheme while we hold the read lock,
+        // so the key cannot change between the checks and signing
+        let sk = self.secret_key();
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::sign(&sk, epoch, message)
+    }
+
+    /// Starts computing the next bottom tree in the background, if the given epoch
+    /// is in the second half of the prepared interval and nothing is computed yet.
+    ///
+    /// This is called by `sign`, but can also be called directly, e.g., at the start
+    /// of each epoch, to start the preparation before signing.
+    ///
+    /// Returns `true` if a background computation has been started. After a computation
+    /// has failed, nothing is started anymore, see `PreparationStatus::Failed`.
+    pub fn schedule_preparation(&self, epoch: u64) -> bool {
+        // a finished worker is joined here, so that panics are recorded
+        let mut preparation = self.preparation();
+        preparation.join(false);
+        if preparation.worker.is_some() || preparation.failed_bottom_tree_index.is_some() {
+            return false;
+        }
+
+        // check that we are in the second half of the prepared interval and can advance
+        let sk = self.secret_key();
+        let leafs_per_bottom_tree = sk.leafs_per_bottom_tree() as u64;
+        let prepared_interval = sk.get_prepared_interval();
+        if !prepared_interval.contains(&epoch)
+            || epoch < prepared_interval.start + leafs_per_bottom_tree
+        {
+            return false;
+        }
+        let Some(bottom_tree_index) = sk.next_bottom_tree_index() else {
+            return false;
+        };
+        drop(sk);
+
+        let shared_sk = Arc::clone(&self.sk);
+        // the worker computes the bottom tree as configured for the caller
+        let parallelism = Parallelism::current();
+        let handle = thread::spawn(move || {
+            // the bottom tree (and the sub-trees of higher levels containing it, if needed)
+            // is computed while only holding a read lock, so that signing can continue
+            // in the meantime.
+            let new_left_bottom_tree_index = bottom_tree_index - 1;
+            let new_subtrees = {
+                let sk = shared_sk.read().unwrap_or_else(PoisonError::into_inner);
+                parallelism.run(|| sk.compute_subtrees(new_left_bottom_tree_index))
+            };
+
+            // swapping it in is cheap. Only this worker modifies the key, so it is still
+            // the bottom tree that is needed.
+            let mut sk = shared_sk.write().unwrap_or_else(PoisonError::into_inner);
+            debug_assert_eq!(sk.next_bottom_tree_index(), Some(bottom_tree_index));
+            sk.install_subtrees(new_left_bottom_tree_index, new_subtrees);
+        });
+
+        preparation.worker = Some(Worker {
+            bottom_tree_index,
+            handle,
+        });
+        true
+    }
+
+    /// Moves the prepared interval forward so that it contains the given epoch,
+    /// see `SignatureSchemeSecretKey::prepare_for_epoch`.
+    ///
+    /// This waits for a running background computation, and then computes
+    /// the required bottom trees on the calling thread. If the background computation
+    /// has failed before, this retries it, and on success, background preparation resumes.
+    pub fn prepare_for_epoch(&self, epoch: u64) -> Result<(), PreparationError> {
+        // holding the lock on the preparation ensures that no new computation is started
+        let mut preparation = self.preparation();
+        preparation.join(true);
+        let result = self
+            .sk
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .prepare_for_epoch(epoch);
+        if result.is_ok() {
+            preparation.failed_bottom_tree_index = None;
+        }
+        drop(preparation);
+        result
+    }
+
+    /// Blocks until the background computation (if any) has finished
+    /// and the new bottom tree is swapped in, or the computation has failed.
+    pub fn wait(&self) {
+        self.preparation().join(true);
+    }
+
+    /// Waits for the background computation and returns the managed secret key.
+    pub fn into_inner(self) -> GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME> {
+        self.wait();
+        let Ok(sk) = Arc::try_unwrap(self.sk) else {
+            unreachable!("Managed key: the worker has finished, so the key is not shared")
+        };
+        sk.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::signature::test_fixtures::{LOG_LIFETIME, Sig};
+
+    use super::*;
+
+    #[test]
+    fn test_preparation_starts_in_second_half() {
+        let mut rng = rand::rng();
+        let (_pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let managed = ManagedSecretKey::new(sk);
+        let message = rng.random();
+        assert_eq!(managed.status(), PreparationStatus::Idle);
+        assert_eq!(managed.get_prepared_interval(), 0..16);
+
+        // signing in the first half does not trigger anything
+        managed.sign(7, &message).unwrap();
+        assert_eq!(managed.status(), PreparationStatus::Idle);
+        assert_eq!(managed.get_prepared_interval(), 0..16);
+
+        // signing in the second half computes bottom tree 2 in the background
+        managed.sign(8, &message).unwrap();
+        assert!(matches!(
+            managed.status(),
+            PreparationStatus::InProgress {
+                bottom_tree_index: 2
+            } | PreparationStatus::Idle
+        ));
+        managed.wait();
+        assert_eq!(managed.get_prepared_interval(), 8..24);
+        assert_eq!(managed.status(), PreparationStatus::Idle);
+
+        // epoch 8 is now in the first half, so nothing is triggered
+        assert!(!managed.schedule_preparation(8));
+
+        // jumping ahead is possible, but not going back
+        managed.prepare_for_epoch(50).unwrap();
+        assert_eq!(managed.get_prepared_interval(), 48..64);
+        assert_eq!(managed.status(), PreparationStatus::Exhausted);
+        assert!(managed.prepare_for_epoch(20).is_err());
+    }
+
+    #[test]
+    fn test_signing_all_epochs_without_manual_preparation() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let managed = ManagedSecretKey::new(sk);
+
+        for epoch in 0..(1 << LOG_LIFETIME) {
+            let message = rng.random();
+            let signature = managed.sign(epoch, &message).unwrap();
+            assert!(Sig::verify(&pk, epoch, &message, &signature));
+
+            // give the background thread the time it would have between two epochs
+            managed.wait();
+        }
+
+        assert_eq!(managed.status(), PreparationStatus::Exhausted);
+        let sk = managed.into_inner();
+        assert_eq!(sk.get_prepared_interval(), 48..64);
+    }
+
+    #[test]
+    fn test_nothing_to_prepare_for_short_key() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 16);
+        let managed = ManagedSecretKey::new(sk);
+        assert_eq!(managed.status(), PreparationStatus::Exhausted);
+
+        let message = rng.random();
+        assert!(!managed.schedule_preparation(12));
+        let signature = managed.sign(12, &message).unwrap();
+        assert!(Sig::verify(&pk, 12, &message, &signature));
+        assert_eq!(managed.get_prepared_interval(), 0..16);
+    }
+
+    #[test]
+    fn test_signing_past_prepared_interval_while_worker_runs() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let managed = ManagedSecretKey::new(sk);
+        let message = rng.random();
+
+        // a worker that only finishes once released, so that it is still running below
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        managed.preparation().worker = Some(Worker {
+            bottom_tree_index: 2,
+            handle: thread::spawn(move || {
+                let _ = released.recv();
+            }),
+        });
+        assert_eq!(
+            managed.status(),
+            PreparationStatus::InProgress {
+                bottom_tree_index: 2
+            }
+        );
+
+        // signing past the prepared interval fails instead of waiting or panicking
+        assert!(matches!(
+            managed.sign(16, &message),
+            Err(SigningError::EpochNotPrepared { epoch: 16 })
+        ));
+        assert!(matches!(
+            managed.sign(64, &message),
+            Err(SigningError::EpochNotActive { epoch: 64 })
+        ));
+
+        // signing within the prepared interval is not affected
+        let signature = managed.sign(9, &message).unwrap();
+        assert!(Sig::verify(&pk, 9, &message, &signature));
+
+        release.send(()).unwrap();
+        managed.wait();
+        assert_eq!(managed.status(), PreparationStatus::Idle);
+
+        // now, a real worker prepares the next bottom tree
+        managed.sign(10, &message).unwrap();
+        managed.wait();
+        let signature = managed.sign(16, &message).unwrap();
+        assert!(Sig::verify(&pk, 16, &message, &signature));
+    }
+
+    #[test]
+    fn test_failed_worker_is_reported() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let managed = ManagedSecretKey::new(sk);
+        let message = rng.random();
+
+        managed.preparation().worker = Some(Worker {
+            bottom_tree_index: 2,
+            handle: thread::spawn(|| panic!("computing the bottom tree failed")),
+        });
+        managed.wait();
+        assert_eq!(
+            managed.status(),
+            PreparationStatus::Failed {
+                bottom_tree_index: 2
+            }
+        );
+
+        // signing still works, but no new computation is started
+        let signature = managed.sign(8, &message).unwrap();
+        assert!(Sig::verify(&pk, 8, &message, &signature));
+        assert!(!managed.schedule_preparation(8));
+        assert_eq!(
+            managed.status(),
+            PreparationStatus::Failed {
+                bottom_tree_index: 2
+            }
+        );
+
+        // preparing on the calling thread recovers
+        managed.prepare_for_epoch(16).unwrap();
+        assert_eq!(managed.get_prepared_interval(), 16..32);
+        assert_eq!(managed.status(), PreparationStatus::Idle);
+        assert!(managed.schedule_preparation(24));
+        managed.wait();
+        assert_eq!(managed.get_prepared_interval(), 24..40);
+    }
+}
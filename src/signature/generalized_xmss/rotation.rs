@@ -0,0 +1,534 @@
+use std::ops::Range;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    MESSAGE_LENGTH,
+    inc_encoding::IncomparableEncoding,
+    signature::{
+        SignatureScheme, SignatureSchemeSecretKey, VerificationError,
+        stateful::{StatefulSigner, StatefulSigningError, WatermarkStorage},
+    },
+    symmetric::{
+        prf::Pseudorandom,
+        tweak_hash::{MessagePreHash, TweakableHash},
+    },
+};
+
+use super::{
+    GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignature,
+    GeneralizedXMSSSignatureScheme, encoding::CanonicalEncoding, expand_activation_time,
+};
+
+/// Context string under which handover certificates are signed, so that a handover
+/// signature is not valid for an application message signed via `sign_bytes` under
+/// a different context.
+///
+/// Note: this only holds if the application signs all of its messages via `sign_bytes`.
+/// A message of `MESSAGE_LENGTH` bytes that is signed directly via `sign` may be equal
+/// to the pre-hash of a handover, so such messages must not be signed with keys that
+/// are rotated with handover certificates.
+pub const HANDOVER_CONTEXT: &[u8] = b"hashsig/handover/v1";
+
+/// Error enum for key rotation and the issuance and verification of handover certificates.
+#[derive(Debug, Error)]
+pub enum RotationError {
+    /// Occurs when the lifetime of the scheme does not have room for a successor
+    /// starting right after the current activation interval.
+    #[error(
+        "No activation interval of {num_active_epochs} epochs starting at epoch {start} fits into the lifetime {lifetime}."
+    )]
+    LifetimeExhausted {
+        start: u64,
+        num_active_epochs: u64,
+        lifetime: u64,
+    },
+
+    /// Occurs when the activation interval of the successor is too short, not aligned
+    /// to bottom trees, or not contained in the lifetime.
+    #[error("Activation interval {interval:?} is not a valid activation interval.")]
+    InvalidActivationInterval { interval: Range<u64> },
+
+    /// Occurs when the activation interval of the successor does not start right
+    /// after the activation interval of the current key.
+    #[error("Activation interval {interval:?} does not start at epoch {expected_start}.")]
+    NotContiguous {
+        expected_start: u64,
+        interval: Range<u64>,
+    },
+
+    /// Occurs when the epoch of a handover is not in the handover window of the current key.
+    #[error("Epoch {epoch} is not in the handover window {window:?}.")]
+    EpochNotInHandoverWindow { epoch: u64, window: Range<u64> },
+
+    /// Occurs when the signature of a handover certificate is invalid.
+    #[error("Invalid handover signature: {0}")]
+    InvalidSignature(#[from] VerificationError),
+
+    /// Occurs when the current key fails to sign the handover.
+    #[error(transparent)]
+    Signing(#[from] StatefulSigningError),
+}
+
+/// Error for the verification of a chain of handover certificates.
+///
+/// It contains the index of the first rejected certificate, and the reason for rejecting it.
+#[derive(Debug, Error)]
+#[error("Handover certificate {index} in the chain is invalid: {error}")]
+pub struct HandoverChainError {
+    /// Index of the rejected certificate in the chain.
+    pub index: usize,
+    /// Reason for rejecting the certificate.
+    pub error: RotationError,
+}
+
+/// Certificate by which a key hands over to its successor.
+///
+/// It contains the public key and the activation interval of the successor, signed by
+/// the current key for an epoch in its handover window. The activation interval of the
+/// successor starts right after the activation interval of the current key.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HandoverCertificate<IE: IncomparableEncoding, TH: TweakableHash> {
+    new_public_key: GeneralizedXMSSPublicKey<TH>,
+    new_activation_interval: Range<u64>,
+    epoch: u64,
+    signature: GeneralizedXMSSSignature<IE, TH>,
+}
+
+impl<IE: IncomparableEncoding, TH: TweakableHash> HandoverCertificate<IE, TH> {
+    /// Returns the public key of the successor.
+    #[must_use]
+    pub const fn new_public_key(&self) -> &GeneralizedXMSSPublicKey<TH> {
+        &self.new_public_key
+    }
+
+    /// Returns the activation interval of the successor.
+    #[must_use]
+    pub fn new_activation_interval(&self) -> Range<u64> {
+        self.new_activation_interval.clone()
+    }
+
+    /// Returns the epoch for which the current key signed the handover.
+    #[must_use]
+    pub const fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the signature of the current key.
+    #[must_use]
+    pub const fn signature(&self) -> &GeneralizedXMSSSignature<IE, TH> {
+        &self.signature
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash + MessagePreHash,
+    const LOG_LIFETIME: usize,
+> GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter> + CanonicalEncoding,
+    TH::Domain: CanonicalEncoding,
+    IE::Randomness: CanonicalEncoding,
+{
+    /// Generates the successor of a key, i.e., a key pair whose activation interval
+    /// starts right after the activation interval of the given key.
+    ///
+    /// The successor has the same tree layout as the given key, and is active for at least
+    /// `num_active_epochs` epochs, where the interval is expanded as in `key_gen_with_layout`.
+    /// Returns an error if the expanded interval does not fit into the lifetime (key generation
+    /// would shift it to the left, overlapping the given key).
+    pub fn generate_successor<R: Rng>(
+        rng: &mut R,
+        sk: &GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+        num_active_epochs: usize,
+    ) -> Result<
+        (
+            <Self as SignatureScheme>::PublicKey,
+            <Self as SignatureScheme>::SecretKey,
+        ),
+        RotationError,
+    > {
+        let start = sk.get_activation_interval().end;
+        let tree_layout = sk.tree_layout();
+        let log_bottom_tree = tree_layout.log_bottom_tree;
+        let leafs_per_bottom_tree = 1 << log_bottom_tree;
+        let (expanded_start, expanded_end) = expand_activation_time::<LOG_LIFETIME>(
+            log_bottom_tree,
+            start as usize,
+            num_active_epochs,
+        );
+        if start >= Self::LIFETIME
+            || (expanded_start * leafs_per_bottom_tree) as u64 != start
+            || expanded_end * leafs_per_bottom_tree > Self::LIFETIME as usize
+        {
+            return Err(RotationError::LifetimeExhausted {
+                start,
+                num_active_epochs: num_active_epochs as u64,
+                lifetime: Self::LIFETIME,
+            });
+        }
+
+        Ok(Self::key_gen_with_layout(
+            rng,
+            start as usize,
+            num_active_epochs,
+            tree_layout,
+        ))
+    }
+
+    /// Returns the handover window of a key with the given activation interval, i.e.,
+    /// the second half of its activation interval. A key signs the handover to its
+    /// successor for one of these epochs.
+    ///
+    /// The window only depends on the activation interval, not on the tree layout of the
+    /// key, so that verifiers do not need to know the tree layout. For keys of `key_gen`
+    /// that are active for two bottom trees, these are the epochs of the last bottom tree.
+    #[must_use]
+    pub const fn handover_window(activation_interval: &Range<u64>) -> Range<u64> {
+        let length = activation_interval
+            .end
+            .saturating_sub(activation_interval.start);
+        (activation_interval.end - length / 2)..activation_interval.end
+    }
+
+    /// Returns the message that is signed in a handover certificate. It is the pre-hash,
+    /// under `HANDOVER_CONTEXT`, of the encoded public key of the successor, followed by
+    /// the start and end of its activation interval (8 bytes each, little-endian).
+    #[must_use]
+    pub fn handover_message(
+        new_public_key: &GeneralizedXMSSPublicKey<TH>,
+        new_activation_interval: &Range<u64>,
+    ) -> [u8; MESSAGE_LENGTH] {
+        let mut message = Self::public_key_to_bytes(new_public_key);
+        message.extend_from_slice(&new_activation_interval.start.to_le_bytes());
+        message.extend_from_slice(&new_activation_interval.end.to_le_bytes());
+        TH::pre_hash(HANDOVER_CONTEXT, &message)
+    }
+
+    /// Issues a certificate by which the key of the signer hands over to its successor.
+    ///
+    /// The epoch must be in the handover window of the key, see `handover_window`, and
+    /// the activation interval of the successor must start right after the activation
+    /// interval of the key. The handover is signed via the signer, so the epoch is
+    /// consumed and can not be used to sign any other message afterwards.
+    pub fn issue_handover<W: WatermarkStorage>(
+        signer: &mut StatefulSigner<Self, W>,
+        epoch: u64,
+        new_public_key: GeneralizedXMSSPublicKey<TH>,
+        new_activation_interval: Range<u64>,
+    ) -> Result<HandoverCertificate<IE, TH>, RotationError> {
+        let activation_interval = signer.secret_key().get_activation_interval();
+        Self::check_handover(&activation_interval, epoch, &new_activation_interval)?;
+
+        let message = Self::handover_message(&new_public_key, &new_activation_interval);
+        let signature = signer.sign(epoch, &message)?;
+
+        Ok(HandoverCertificate {
+            new_public_key,
+            new_activation_interval,
+            epoch,
+            signature,
+        })
+    }
+
+    /// Verifies that a certificate hands over from the key with the given public key
+    /// and activation interval to the successor contained in the certificate.
+    pub fn verify_handover(
+        public_key: &GeneralizedXMSSPublicKey<TH>,
+        activation_interval: &Range<u64>,
+        certificate: &HandoverCertificate<IE, TH>,
+    ) -> Result<(), RotationError> {
+        Self::check_handover(
+            activation_interval,
+            certificate.epoch,
+            &certificate.new_activation_interval,
+        )?;
+
+        let message = Self::handover_message(
+            &certificate.new_public_key,
+            &certificate.new_activation_interval,
+        );
+        Self::verify_checked(
+            public_key,
+            certificate.epoch,
+            &message,
+            &certificate.signature,
+        )?;
+        Ok(())
+    }
+
+    /// Verifies a chain of handover certificates, starting at the key with the given
+    /// public key and activation interval. Each certificate must hand over from the
+    /// successor contained in the previous certificate.
+    ///
+    /// Returns the public key and the activation interval of the last key in the chain,
+    /// or the index of the first invalid certificate and the reason for rejecting it.
+    pub fn verify_handover_chain<'a>(
+        genesis_public_key: &'a GeneralizedXMSSPublicKey<TH>,
+        genesis_activation_interval: Range<u64>,
+        chain: &'a [HandoverCertificate<IE, TH>],
+    ) -> Result<(&'a GeneralizedXMSSPublicKey<TH>, Range<u64>), HandoverChainError> {
+        let mut public_key = genesis_public_key;
+        let mut activation_interval = genesis_activation_interval;
+        for (index, certificate) in chain.iter().enumerate() {
+            Self::verify_handover(public_key, &activation_interval, certificate)
+                .map_err(|error| HandoverChainError { index, error })?;
+            public_key = &certificate.new_public_key;
+            activation_interval = certificate.new_activation_interval.clone();
+        }
+        Ok((public_key, activation_interval))
+    }
+
+    /// Helper function to check the activation interval of the successor and the epoch of a
+    /// handover from a key with the given activation interval. It does not check the signature.
+    fn check_handover(
+        activation_interval: &Range<u64>,
+        epoch: u64,
+        new_activation_interval: &Range<u64>,
+    ) -> Result<(), RotationError> {
+        // the successor must be a valid output of `key_gen_with_layout` for some tree layout,
+        // i.e., an interval within the lifetime that covers at least two bottom trees and is
+        // aligned to them. As bottom trees have at least two leafs, the verifier does not need
+        // to know the tree layout of the successor for this.
+        const MIN_LEAFS_PER_BOTTOM_TREE: u64 = 2;
+        if new_activation_interval.end > Self::LIFETIME
+            || new_activation_interval.end < new_activation_interval.start
+            || new_activation_interval.end - new_activation_interval.start
+                < 2 * MIN_LEAFS_PER_BOTTOM_TREE
+            || !new_activation_interval
+                .start
+                .is_multiple_of(MIN_LEAFS_PER_BOTTOM_TREE)
+            || !new_activation_interval
+                .end
+                .is_multiple_of(MIN_LEAFS_PER_BOTTOM_TREE)
+        {
+            return Err(RotationError::InvalidActivationInterval {
+                interval: new_activation_interval.clone(),
+            });
+        }
+        if new_activation_interval.start != activation_interval.end {
+            return Err(RotationError::NotContiguous {
+                expected_start: activation_interval.end,
+                interval: new_activation_interval.clone(),
+            });
+        }
+
+        let window = Self::handover_window(activation_interval);
+        if !window.contains(&epoch) {
+            return Err(RotationError::EpochNotInHandoverWindow { epoch, window });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signature::{
+        PreHashSignatureScheme, stateful::InMemoryWatermarkStorage, test_fixtures::Sig,
+    };
+
+    use super::*;
+    use crate::signature::generalized_xmss::TreeLayout;
+
+    /// Helper function to create a signer for a secret key.
+    fn signer(
+        sk: <Sig as SignatureScheme>::SecretKey,
+    ) -> StatefulSigner<Sig, InMemoryWatermarkStorage> {
+        StatefulSigner::new(sk, InMemoryWatermarkStorage::default()).unwrap()
+    }
+
+    #[test]
+    fn test_handover_chain() {
+        let mut rng = rand::rng();
+
+        // genesis key for epochs [0, 16), and two successors
+        let (genesis_pk, genesis_sk) = Sig::key_gen(&mut rng, 0, 16);
+        let genesis_interval = genesis_sk.get_activation_interval();
+        assert_eq!(Sig::handover_window(&genesis_interval), 8..16);
+
+        let (pk_1, sk_1) = Sig::generate_successor(&mut rng, &genesis_sk, 16).unwrap();
+        let interval_1 = sk_1.get_activation_interval();
+        assert_eq!(interval_1, 16..32);
+        let (pk_2, sk_2) = Sig::generate_successor(&mut rng, &sk_1, 20).unwrap();
+        let interval_2 = sk_2.get_activation_interval();
+        assert_eq!(interval_2, 32..56);
+
+        let cert_1 = Sig::issue_handover(&mut signer(genesis_sk), 15, pk_1, interval_1).unwrap();
+        let cert_2 = Sig::issue_handover(&mut signer(sk_1), 24, pk_2, interval_2.clone()).unwrap();
+        assert_eq!(cert_2.epoch(), 24);
+        assert_eq!(cert_2.new_activation_interval(), interval_2);
+
+        let chain = [cert_1, cert_2];
+        let (last_pk, last_interval) =
+            Sig::verify_handover_chain(&genesis_pk, genesis_interval.clone(), &chain).unwrap();
+        assert_eq!(
+            Sig::public_key_to_bytes(last_pk),
+            Sig::public_key_to_bytes(chain[1].new_public_key())
+        );
+        assert_eq!(last_interval, interval_2);
+
+        // an empty chain ends at the genesis key
+        let (last_pk, last_interval) =
+            Sig::verify_handover_chain(&genesis_pk, genesis_interval.clone(), &[]).unwrap();
+        assert_eq!(
+            Sig::public_key_to_bytes(last_pk),
+            Sig::public_key_to_bytes(&genesis_pk)
+        );
+        assert_eq!(last_interval, genesis_interval);
+
+        // a chain that skips a certificate is not contiguous
+        let err = Sig::verify_handover_chain(&genesis_pk, genesis_interval.clone(), &chain[1..])
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err.index, 0);
+        assert!(matches!(err.error, RotationError::NotContiguous { .. }));
+
+        // a chain starting at a different key is rejected
+        let (other_pk, _) = Sig::key_gen(&mut rng, 0, 16);
+        let err = Sig::verify_handover_chain(&other_pk, genesis_interval, &chain)
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err.index, 0);
+        assert!(matches!(err.error, RotationError::InvalidSignature(_)));
+    }
+
+    #[test]
+    fn test_handover_rejected() {
+        let mut rng = rand::rng();
+
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 16);
+        let interval = sk.get_activation_interval();
+        let mut signer = signer(sk);
+        let mut successor_pk = || Sig::key_gen(&mut rng, 16, 16).0;
+
+        // the epoch must be in the handover window
+        assert!(matches!(
+            Sig::issue_handover(&mut signer, 3, successor_pk(), 16..32),
+            Err(RotationError::EpochNotInHandoverWindow { epoch: 3, .. })
+        ));
+
+        // the successor must start right after the key, cover at least two bottom trees,
+        // and be aligned to them
+        assert!(matches!(
+            Sig::issue_handover(&mut signer, 12, successor_pk(), 16..18),
+            Err(RotationError::InvalidActivationInterval { .. })
+        ));
+        assert!(matches!(
+            Sig::issue_handover(&mut signer, 12, successor_pk(), 16..23),
+            Err(RotationError::InvalidActivationInterval { .. })
+        ));
+        assert!(matches!(
+            Sig::issue_handover(&mut signer, 12, successor_pk(), 32..48),
+            Err(RotationError::NotContiguous {
+                expected_start: 16,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Sig::issue_handover(&mut signer, 12, successor_pk(), 16..72),
+            Err(RotationError::InvalidActivationInterval { .. })
+        ));
+
+        // the handover epoch can not be used for anything else
+        signer.sign(12, &[1; MESSAGE_LENGTH]).unwrap();
+        assert!(matches!(
+            Sig::issue_handover(&mut signer, 12, successor_pk(), 16..32),
+            Err(RotationError::Signing(
+                StatefulSigningError::EpochNotAboveWatermark { .. }
+            ))
+        ));
+
+        // tampering with the certificate invalidates the signature
+        let mut cert = Sig::issue_handover(&mut signer, 13, successor_pk(), 16..32).unwrap();
+        Sig::verify_handover(&pk, &interval, &cert).unwrap();
+        cert.new_activation_interval = 16..24;
+        assert!(matches!(
+            Sig::verify_handover(&pk, &interval, &cert),
+            Err(RotationError::InvalidSignature(_))
+        ));
+        cert.new_activation_interval = 16..32;
+        cert.epoch = 14;
+        assert!(matches!(
+            Sig::verify_handover(&pk, &interval, &cert),
+            Err(RotationError::InvalidSignature(_))
+        ));
+        cert.epoch = 13;
+        cert.new_public_key = successor_pk();
+        assert!(matches!(
+            Sig::verify_handover(&pk, &interval, &cert),
+            Err(RotationError::InvalidSignature(_))
+        ));
+
+        // a handover signature is not a signature on the plain message
+        let message = Sig::handover_message(cert.new_public_key(), &(16..32));
+        assert!(!Sig::verify_bytes(&pk, 13, b"", &message, cert.signature()));
+    }
+
+    #[test]
+    fn test_handover_non_default_layout() {
+        let mut rng = rand::rng();
+
+        // bottom trees of two leafs, so that activation intervals are not aligned to the
+        // bottom trees of the default tree layout
+        let tree_layout = TreeLayout {
+            log_bottom_tree: 1,
+            num_levels: 3,
+        };
+        let (genesis_pk, mut genesis_sk) = Sig::key_gen_with_layout(&mut rng, 0, 10, tree_layout);
+        let genesis_interval = genesis_sk.get_activation_interval();
+        assert_eq!(genesis_interval, 0..10);
+        assert_eq!(Sig::handover_window(&genesis_interval), 5..10);
+
+        // the successor has the same tree layout
+        let (pk_1, sk_1) = Sig::generate_successor(&mut rng, &genesis_sk, 6).unwrap();
+        assert_eq!(sk_1.tree_layout(), tree_layout);
+        let interval_1 = sk_1.get_activation_interval();
+        assert_eq!(interval_1, 10..16);
+
+        genesis_sk.prepare_for_epoch(9).unwrap();
+        let cert_1 =
+            Sig::issue_handover(&mut signer(genesis_sk), 9, pk_1, interval_1.clone()).unwrap();
+        let chain = [cert_1];
+        let (last_pk, last_interval) =
+            Sig::verify_handover_chain(&genesis_pk, genesis_interval, &chain).unwrap();
+        assert_eq!(last_interval, interval_1);
+
+        // the successor can sign in its activation interval
+        let mut signer_1 = signer(sk_1);
+        let signature = signer_1.sign(10, &[2; MESSAGE_LENGTH]).unwrap();
+        assert!(Sig::verify(last_pk, 10, &[2; MESSAGE_LENGTH], &signature));
+    }
+
+    #[test]
+    fn test_generate_successor_lifetime_exhausted() {
+        let mut rng = rand::rng();
+
+        // the last key of the lifetime has no successor
+        let (_, sk) = Sig::key_gen(&mut rng, 48, 16);
+        assert!(matches!(
+            Sig::generate_successor(&mut rng, &sk, 16),
+            Err(RotationError::LifetimeExhausted {
+                start: 64,
+                lifetime: 64,
+                ..
+            })
+        ));
+
+        // the successor must fit into the rest of the lifetime
+        let (_, sk) = Sig::key_gen(&mut rng, 32, 16);
+        assert!(matches!(
+            Sig::generate_successor(&mut rng, &sk, 20),
+            Err(RotationError::LifetimeExhausted { start: 48, .. })
+        ));
+        let (_, successor) = Sig::generate_successor(&mut rng, &sk, 16).unwrap();
+        assert_eq!(successor.get_activation_interval(), 48..64);
+    }
+}
@@ -0,0 +1,292 @@
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MESSAGE_LENGTH,
+    inc_encoding::IncomparableEncoding,
+    parallelism::map_range,
+    signature::{SignatureSchemeSecretKey, SigningError},
+    symmetric::{
+        prf::Pseudorandom,
+        tweak_hash::TweakableHash,
+        tweak_hash_tree::{HashTreeMultiOpening, combined_multi_path, hash_tree_verify_multi},
+    },
+};
+
+use super::{
+    GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignatureScheme, SigningMode,
+};
+
+/// Signatures of a single signer for several epochs, e.g., for a log of consecutive epochs.
+///
+/// The Merkle authentication paths of the epochs share most of their nodes. Instead of
+/// one path per signature, the log contains a single multi-opening for all epochs, which
+/// contains each node once. The rest of each signature is the same as for `sign`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct GeneralizedXMSSSignatureLog<IE: IncomparableEncoding, TH: TweakableHash> {
+    epochs: Vec<u64>,
+    entries: Vec<SignatureLogEntry<IE, TH>>,
+    opening: HashTreeMultiOpening<TH>,
+}
+
+/// Signature for one epoch in a log, without the Merkle path.
+/// It contains the encoding randomness and a list of hashes.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct SignatureLogEntry<IE: IncomparableEncoding, TH: TweakableHash> {
+    rho: IE::Randomness,
+    hashes: Vec<TH::Domain>,
+}
+
+impl<IE: IncomparableEncoding, TH: TweakableHash> GeneralizedXMSSSignatureLog<IE, TH> {
+    /// Returns the epochs for which the signatures were created, in increasing order.
+    #[must_use]
+    pub fn epochs(&self) -> &[u64] {
+        &self.epochs
+    }
+
+    /// Returns the number of Merkle tree nodes that the signatures share.
+    #[must_use]
+    pub const fn num_opening_nodes(&self) -> usize {
+        self.opening.nodes.len()
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    /// Signs a list of messages, given as tuples `(epoch, message)`, and returns a log of
+    /// the signatures, see `GeneralizedXMSSSignatureLog`. Each signature is the same as
+    /// the one computed by `sign`, except for the Merkle path.
+    ///
+    /// The epochs must be strictly increasing, and the key must be prepared for all of
+    /// them, i.e., they must be in the prepared interval. Otherwise, an error is returned.
+    /// As for `sign`, the caller must make sure to never sign two different messages for
+    /// the same epoch.
+    pub fn sign_log(
+        sk: &GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+        messages: &[(u64, &[u8; MESSAGE_LENGTH])],
+    ) -> Result<GeneralizedXMSSSignatureLog<IE, TH>, SigningError> {
+        if messages.is_empty() {
+            return Err(SigningError::NoMessages);
+        }
+        if let Some(pair) = messages.windows(2).find(|pair| pair[0].0 >= pair[1].0) {
+            return Err(SigningError::EpochsNotIncreasing {
+                previous_epoch: pair[0].0,
+                epoch: pair[1].0,
+            });
+        }
+        let epochs: Vec<u64> = messages.iter().map(|(epoch, _)| *epoch).collect();
+        if let Some(&epoch) = epochs
+            .iter()
+            .find(|epoch| !sk.get_activation_interval().contains(epoch))
+        {
+            return Err(SigningError::EpochNotActive { epoch });
+        }
+        if let Some(&epoch) = epochs
+            .iter()
+            .find(|epoch| !sk.get_prepared_interval().contains(epoch))
+        {
+            return Err(SigningError::EpochNotPrepared { epoch });
+        }
+
+        // the prepared sub-trees and the top tree contain all nodes of the paths
+        let mut subtrees: Vec<_> = sk.prepared_subtrees.iter().flatten().collect();
+        subtrees.push(&sk.top_tree);
+        let opening = combined_multi_path(&subtrees, &epochs);
+
+        let entries = messages
+            .iter()
+            .map(|&(epoch, message)| {
+                let (rho, hashes) = Self::encode_and_walk_chains(
+                    &sk.prf_key,
+                    &sk.parameter,
+                    epoch,
+                    message,
+                    SigningMode::VariableTime,
+                )?;
+                Ok(SignatureLogEntry { rho, hashes })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(GeneralizedXMSSSignatureLog {
+            epochs,
+            entries,
+            opening,
+        })
+    }
+
+    /// Verifies a log of signatures for the given list of messages, given as tuples
+    /// `(epoch, message)`. The log must contain exactly these epochs, in this order.
+    ///
+    /// The log may come from an untrusted source, so this returns false if it is
+    /// malformed. Signatures are verified in parallel (see `Parallelism`).
+    pub fn verify_log(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        messages: &[(u64, &[u8; MESSAGE_LENGTH])],
+        log: &GeneralizedXMSSSignatureLog<IE, TH>,
+    ) -> bool
+    where
+        IE::Randomness: Sync,
+    {
+        // the log must be for these epochs, which must be strictly increasing
+        if messages.is_empty()
+            || log.epochs.len() != messages.len()
+            || log.entries.len() != messages.len()
+            || log
+                .epochs
+                .iter()
+                .zip(messages)
+                .any(|(epoch, (message_epoch, _))| epoch != message_epoch)
+            || log.epochs.windows(2).any(|pair| pair[0] >= pair[1])
+            || log.epochs[log.epochs.len() - 1] >= (1 << LOG_LIFETIME)
+            || log
+                .entries
+                .iter()
+                .any(|entry| entry.hashes.len() != IE::DIMENSION)
+        {
+            return false;
+        }
+
+        // recompute the leafs, i.e., the one-time public keys of all epochs
+        let leafs: Option<Vec<_>> = map_range(0..messages.len(), |i| {
+            let (epoch, message) = messages[i];
+            let entry = &log.entries[i];
+            Self::chain_ends(pk, epoch, message, &entry.rho, &entry.hashes, true).ok()
+        })
+        .into_iter()
+        .collect();
+        let Some(leafs) = leafs else {
+            return false;
+        };
+
+        // and check them against the root
+        // Note: the checks above ensure that hash_tree_verify_multi does not panic.
+        hash_tree_verify_multi(
+            &pk.parameter,
+            &pk.root,
+            LOG_LIFETIME,
+            &log.epochs,
+            &leafs,
+            &log.opening,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signature::{
+        SignatureScheme,
+        test_fixtures::{IE, PRF, TH},
+    };
+
+    use super::*;
+
+    const LOG_LIFETIME: usize = 8;
+    type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+    #[test]
+    fn test_sign_log() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+
+        // a log of consecutive epochs, spanning both prepared bottom trees
+        let contents: Vec<[u8; MESSAGE_LENGTH]> = (0..12).map(|i| [i; MESSAGE_LENGTH]).collect();
+        let messages: Vec<_> = contents
+            .iter()
+            .enumerate()
+            .map(|(i, message)| (10 + i as u64, message))
+            .collect();
+        let log = Sig::sign_log(&sk, &messages).unwrap();
+        assert_eq!(log.epochs(), (10..22).collect::<Vec<_>>());
+        assert!(Sig::verify_log(&pk, &messages, &log));
+
+        // the log agrees with the individual signatures, and it is smaller
+        for (&(epoch, message), entry) in messages.iter().zip(&log.entries) {
+            let sig = Sig::sign(&sk, epoch, message).unwrap();
+            assert!(sig.hashes == entry.hashes);
+            assert!(Sig::verify(&pk, epoch, message, &sig));
+        }
+        assert!(log.num_opening_nodes() < messages.len() * LOG_LIFETIME);
+
+        // it is only valid for the epochs and messages in the log
+        let mut other_messages = messages.clone();
+        other_messages[3].1 = &contents[4];
+        assert!(!Sig::verify_log(&pk, &other_messages, &log));
+        let mut other_messages = messages.clone();
+        other_messages[11].0 = 22;
+        assert!(!Sig::verify_log(&pk, &other_messages, &log));
+        assert!(!Sig::verify_log(&pk, &messages[1..], &log));
+        let (other_pk, _) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        assert!(!Sig::verify_log(&other_pk, &messages, &log));
+
+        // a malformed log is rejected without panicking
+        let mut malformed = Sig::sign_log(&sk, &messages).unwrap();
+        malformed.opening.nodes.pop();
+        assert!(!Sig::verify_log(&pk, &messages, &malformed));
+        let mut malformed = Sig::sign_log(&sk, &messages).unwrap();
+        malformed.entries[5].hashes.pop();
+        assert!(!Sig::verify_log(&pk, &messages, &malformed));
+        let mut malformed = Sig::sign_log(&sk, &messages).unwrap();
+        malformed.epochs.swap(0, 1);
+        assert!(!Sig::verify_log(&pk, &messages, &malformed));
+    }
+
+    #[test]
+    fn test_sign_log_sparse_epochs() {
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::key_gen(&mut rng, 16, 64);
+        sk.prepare_for_epoch(50).unwrap();
+
+        let message = [0x42; MESSAGE_LENGTH];
+        let messages: Vec<_> = [51, 60, 61, 79]
+            .iter()
+            .map(|&epoch| (epoch, &message))
+            .collect();
+        let log = Sig::sign_log(&sk, &messages).unwrap();
+        assert!(Sig::verify_log(&pk, &messages, &log));
+
+        // a single epoch needs a full path
+        let log = Sig::sign_log(&sk, &messages[..1]).unwrap();
+        assert_eq!(log.num_opening_nodes(), LOG_LIFETIME);
+        assert!(Sig::verify_log(&pk, &messages[..1], &log));
+
+        // the epochs must be strictly increasing
+        let unordered = [messages[1], messages[0]];
+        assert!(matches!(
+            Sig::sign_log(&sk, &unordered),
+            Err(SigningError::EpochsNotIncreasing {
+                previous_epoch: 60,
+                epoch: 51
+            })
+        ));
+        let repeated = [messages[0], messages[0]];
+        assert!(matches!(
+            Sig::sign_log(&sk, &repeated),
+            Err(SigningError::EpochsNotIncreasing { .. })
+        ));
+
+        // there must be at least one message, and the key must be active and prepared
+        assert!(matches!(
+            Sig::sign_log(&sk, &[]),
+            Err(SigningError::NoMessages)
+        ));
+        assert!(matches!(
+            Sig::sign_log(&sk, &[(10, &message), (51, &message)]),
+            Err(SigningError::EpochNotActive { epoch: 10 })
+        ));
+        assert!(matches!(
+            Sig::sign_log(&sk, &[(17, &message), (51, &message)]),
+            Err(SigningError::EpochNotPrepared { epoch: 17 })
+        ));
+    }
+}
@@ -0,0 +1,570 @@
+use ::ssz::{Decode, DecodeError, Encode};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    symmetric::{tweak_hash::TweakableHash, tweak_hash_tree::HashTreeOpening},
+};
+
+use super::{
+    GeneralizedXMSSPublicKey, GeneralizedXMSSSignature,
+    encoding::{CanonicalEncoding, DecodingError},
+};
+
+/// Size of a chunk for SSZ merkleization.
+const BYTES_PER_CHUNK: usize = 32;
+
+/// Error enum for converting a signature or an opening into its fixed-size SSZ representation.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SszError {
+    /// Occurs when the co-path does not have the length of the SSZ vector.
+    #[error("Invalid co-path length: expected {expected}, got {actual}.")]
+    InvalidCoPathLength { expected: usize, actual: usize },
+
+    /// Occurs when the number of chain hashes does not match the dimension of the encoding.
+    #[error("Invalid number of hashes: expected {expected}, got {actual}.")]
+    InvalidNumberOfHashes { expected: usize, actual: usize },
+}
+
+/// `HashTreeOpening` of a tree with depth `DEPTH`, as the SSZ container
+///
+/// ```text
+/// class HashTreeOpening(Container):
+///     co_path: Vector[Domain, DEPTH]
+/// ```
+///
+/// Here, `Domain` is `ByteVector[N]` for hashes given by `N` bytes, and `Vector[uint32, N]`
+/// for hashes given by `N` KoalaBear field elements (using the canonical representatives).
+pub struct SszHashTreeOpening<TH: TweakableHash, const DEPTH: usize>(HashTreeOpening<TH>);
+
+impl<TH: TweakableHash, const DEPTH: usize> SszHashTreeOpening<TH, DEPTH> {
+    /// Returns the opening.
+    #[must_use]
+    pub const fn inner(&self) -> &HashTreeOpening<TH> {
+        &self.0
+    }
+
+    /// Returns the opening, consuming the SSZ representation.
+    #[must_use]
+    pub fn into_inner(self) -> HashTreeOpening<TH> {
+        self.0
+    }
+}
+
+impl<TH: TweakableHash, const DEPTH: usize> TryFrom<HashTreeOpening<TH>>
+    for SszHashTreeOpening<TH, DEPTH>
+{
+    type Error = SszError;
+
+    fn try_from(opening: HashTreeOpening<TH>) -> Result<Self, Self::Error> {
+        if opening.co_path.len() != DEPTH {
+            return Err(SszError::InvalidCoPathLength {
+                expected: DEPTH,
+                actual: opening.co_path.len(),
+            });
+        }
+        Ok(Self(opening))
+    }
+}
+
+impl<TH, const DEPTH: usize> SszHashTreeOpening<TH, DEPTH>
+where
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+{
+    /// Returns the SSZ `hash_tree_root` of the opening.
+    #[must_use]
+    pub fn hash_tree_root(&self) -> [u8; 32] {
+        merkleize(vec![vector_root(&self.0.co_path)])
+    }
+}
+
+impl<TH, const DEPTH: usize> Encode for SszHashTreeOpening<TH, DEPTH>
+where
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+{
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        DEPTH * TH::Domain::ENCODED_LENGTH
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        for node in &self.0.co_path {
+            node.write_bytes(buf);
+        }
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        <Self as Encode>::ssz_fixed_len()
+    }
+}
+
+impl<TH, const DEPTH: usize> Decode for SszHashTreeOpening<TH, DEPTH>
+where
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+{
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        DEPTH * TH::Domain::ENCODED_LENGTH
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        check_length(bytes, <Self as Decode>::ssz_fixed_len())?;
+        let co_path = read_vector(bytes)?;
+        Ok(Self(HashTreeOpening { co_path }))
+    }
+}
+
+/// `GeneralizedXMSSSignature` for a lifetime of `2^LOG_LIFETIME`, as the SSZ container
+///
+/// ```text
+/// class Signature(Container):
+///     path: HashTreeOpening        # with DEPTH = LOG_LIFETIME
+///     rho: Randomness
+///     hashes: Vector[Domain, DIMENSION]
+/// ```
+///
+/// Here, `Randomness` is `ByteVector[N]` or `Vector[uint32, N]`, as for `Domain`,
+/// see `SszHashTreeOpening`, and `DIMENSION` is the dimension of the incomparable encoding.
+/// As all fields have a fixed size, the SSZ serialization is the concatenation of the fields.
+pub struct SszSignature<IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>(
+    GeneralizedXMSSSignature<IE, TH>,
+);
+
+impl<IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    SszSignature<IE, TH, LOG_LIFETIME>
+{
+    /// Returns the signature.
+    #[must_use]
+    pub const fn inner(&self) -> &GeneralizedXMSSSignature<IE, TH> {
+        &self.0
+    }
+
+    /// Returns the signature, consuming the SSZ representation.
+    #[must_use]
+    pub fn into_inner(self) -> GeneralizedXMSSSignature<IE, TH> {
+        self.0
+    }
+}
+
+impl<IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    TryFrom<GeneralizedXMSSSignature<IE, TH>> for SszSignature<IE, TH, LOG_LIFETIME>
+{
+    type Error = SszError;
+
+    fn try_from(signature: GeneralizedXMSSSignature<IE, TH>) -> Result<Self, Self::Error> {
+        if signature.path.co_path.len() != LOG_LIFETIME {
+            return Err(SszError::InvalidCoPathLength {
+                expected: LOG_LIFETIME,
+                actual: signature.path.co_path.len(),
+            });
+        }
+        if signature.hashes.len() != IE::DIMENSION {
+            return Err(SszError::InvalidNumberOfHashes {
+                expected: IE::DIMENSION,
+                actual: signature.hashes.len(),
+            });
+        }
+        Ok(Self(signature))
+    }
+}
+
+impl<IE, TH, const LOG_LIFETIME: usize> SszSignature<IE, TH, LOG_LIFETIME>
+where
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+    IE::Randomness: CanonicalEncoding,
+{
+    /// Length of the SSZ serialization in bytes.
+    const SSZ_LENGTH: usize = IE::Randomness::ENCODED_LENGTH
+        + (LOG_LIFETIME + IE::DIMENSION) * TH::Domain::ENCODED_LENGTH;
+
+    /// Returns the SSZ `hash_tree_root` of the signature.
+    #[must_use]
+    pub fn hash_tree_root(&self) -> [u8; 32] {
+        let path_root = merkleize(vec![vector_root(&self.0.path.co_path)]);
+        merkleize(vec![
+            path_root,
+            basic_root(&self.0.rho),
+            vector_root(&self.0.hashes),
+        ])
+    }
+}
+
+impl<IE, TH, const LOG_LIFETIME: usize> Encode for SszSignature<IE, TH, LOG_LIFETIME>
+where
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+    IE::Randomness: CanonicalEncoding,
+{
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        Self::SSZ_LENGTH
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        for node in &self.0.path.co_path {
+            node.write_bytes(buf);
+        }
+        self.0.rho.write_bytes(buf);
+        for hash in &self.0.hashes {
+            hash.write_bytes(buf);
+        }
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        Self::SSZ_LENGTH
+    }
+}
+
+impl<IE, TH, const LOG_LIFETIME: usize> Decode for SszSignature<IE, TH, LOG_LIFETIME>
+where
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+    IE::Randomness: CanonicalEncoding,
+{
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        Self::SSZ_LENGTH
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        check_length(bytes, Self::SSZ_LENGTH)?;
+        let (path_bytes, rest) = bytes.split_at(LOG_LIFETIME * TH::Domain::ENCODED_LENGTH);
+        let (rho_bytes, hashes_bytes) = rest.split_at(IE::Randomness::ENCODED_LENGTH);
+        Ok(Self(GeneralizedXMSSSignature {
+            path: HashTreeOpening {
+                co_path: read_vector(path_bytes)?,
+            },
+            rho: read_basic(rho_bytes)?,
+            hashes: read_vector(hashes_bytes)?,
+        }))
+    }
+}
+
+/// The public key is the SSZ container
+///
+/// ```text
+/// class PublicKey(Container):
+///     root: Domain
+///     parameter: Parameter
+/// ```
+///
+/// Here, `Domain` and `Parameter` are `ByteVector[N]` or `Vector[uint32, N]`,
+/// see `SszHashTreeOpening`.
+impl<TH> GeneralizedXMSSPublicKey<TH>
+where
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+    TH::Parameter: CanonicalEncoding,
+{
+    /// Length of the SSZ serialization in bytes.
+    const SSZ_LENGTH: usize = TH::Domain::ENCODED_LENGTH + TH::Parameter::ENCODED_LENGTH;
+
+    /// Returns the SSZ `hash_tree_root` of the public key.
+    #[must_use]
+    pub fn hash_tree_root(&self) -> [u8; 32] {
+        merkleize(vec![basic_root(&self.root), basic_root(&self.parameter)])
+    }
+}
+
+impl<TH> Encode for GeneralizedXMSSPublicKey<TH>
+where
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+    TH::Parameter: CanonicalEncoding,
+{
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        Self::SSZ_LENGTH
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        self.root.write_bytes(buf);
+        self.parameter.write_bytes(buf);
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        Self::SSZ_LENGTH
+    }
+}
+
+impl<TH> Decode for GeneralizedXMSSPublicKey<TH>
+where
+    TH: TweakableHash,
+    TH::Domain: CanonicalEncoding,
+    TH::Parameter: CanonicalEncoding,
+{
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        Self::SSZ_LENGTH
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        check_length(bytes, Self::SSZ_LENGTH)?;
+        let (root_bytes, parameter_bytes) = bytes.split_at(TH::Domain::ENCODED_LENGTH);
+        Ok(Self {
+            root: read_basic(root_bytes)?,
+            parameter: read_basic(parameter_bytes)?,
+        })
+    }
+}
+
+/// Helper function to check that the input has exactly the expected length.
+const fn check_length(bytes: &[u8], expected: usize) -> Result<(), DecodeError> {
+    if bytes.len() != expected {
+        return Err(DecodeError::InvalidByteLength {
+            len: bytes.len(),
+            expected,
+        });
+    }
+    Ok(())
+}
+
+/// Helper function to decode a single component, whose length has already been checked.
+fn read_basic<T: CanonicalEncoding>(bytes: &[u8]) -> Result<T, DecodeError> {
+    T::read_bytes(bytes).map_err(|e| match e {
+        DecodingError::InvalidLength { expected, actual } => DecodeError::InvalidByteLength {
+            len: actual,
+            expected,
+        },
+        e => DecodeError::BytesInvalid(e.to_string()),
+    })
+}
+
+/// Helper function to decode a vector of components, whose total length has already been checked.
+fn read_vector<T: CanonicalEncoding>(bytes: &[u8]) -> Result<Vec<T>, DecodeError> {
+    bytes
+        .chunks_exact(T::ENCODED_LENGTH)
+        .map(read_basic)
+        .collect()
+}
+
+/// `hash_tree_root` of a component, which is a vector of basic SSZ types (bytes or `uint32`).
+/// It is given by the merkleization of the packed serialization.
+fn basic_root<T: CanonicalEncoding>(value: &T) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(T::ENCODED_LENGTH);
+    value.write_bytes(&mut bytes);
+    let chunks = bytes
+        .chunks(BYTES_PER_CHUNK)
+        .map(|chunk| {
+            let mut padded_chunk = [0u8; BYTES_PER_CHUNK];
+            padded_chunk[..chunk.len()].copy_from_slice(chunk);
+            padded_chunk
+        })
+        .collect();
+    merkleize(chunks)
+}
+
+/// `hash_tree_root` of a vector of components, given by the merkleization of their roots.
+fn vector_root<T: CanonicalEncoding>(values: &[T]) -> [u8; 32] {
+    merkleize(values.iter().map(basic_root).collect())
+}
+
+/// SSZ merkleization: pads the chunks with zero chunks to the next power of two,
+/// and returns the root of the SHA-256 Merkle tree with these chunks as leafs.
+fn merkleize(mut chunks: Vec<[u8; 32]>) -> [u8; 32] {
+    chunks.resize(chunks.len().next_power_of_two(), [0u8; BYTES_PER_CHUNK]);
+    while chunks.len() > 1 {
+        chunks = chunks
+            .chunks_exact(2)
+            .map(|pair| {
+                Sha256::new()
+                    .chain_update(pair[0])
+                    .chain_update(pair[1])
+                    .finalize()
+                    .into()
+            })
+            .collect();
+    }
+    chunks[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::PrimeField32;
+    use ssz_derive::{Decode, Encode};
+
+    use crate::{
+        F,
+        inc_encoding::target_sum::TargetSumEncoding,
+        signature::{
+            SignatureScheme,
+            generalized_xmss::GeneralizedXMSSSignatureScheme,
+            test_fixtures::{IE, LOG_LIFETIME, Sig, TH},
+        },
+        symmetric::{
+            message_hash::{MessageHash, poseidon::PoseidonMessageHashW1},
+            prf::shake_to_field::ShakePRFtoF,
+            tweak_hash::poseidon::PoseidonTweakW1L5,
+        },
+    };
+
+    use super::*;
+    use crate::signature::generalized_xmss::encoding::HEADER_LENGTH;
+
+    type PRFPoseidon = ShakePRFtoF<7, 5>;
+    type THPoseidon = PoseidonTweakW1L5;
+    type MHPoseidon = PoseidonMessageHashW1;
+    type IEPoseidon =
+        TargetSumEncoding<MHPoseidon, { MHPoseidon::DIMENSION * (MHPoseidon::BASE - 1) / 2 }>;
+    type SigPoseidon = GeneralizedXMSSSignatureScheme<PRFPoseidon, IEPoseidon, THPoseidon, 6>;
+
+    /// Beacon-style container embedding a public key and a signature.
+    #[derive(Encode, Decode)]
+    struct SignedAttestation {
+        validator_index: u64,
+        public_key: GeneralizedXMSSPublicKey<THPoseidon>,
+        epoch: u64,
+        signature: SszSignature<IEPoseidon, THPoseidon, 6>,
+    }
+
+    #[test]
+    fn test_ssz_golden_vectors() {
+        // known answers, these must never change as roots are committed to in containers
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let (pk, sk) = Sig::key_gen_from_seed(&seed, 0, 1 << LOG_LIFETIME);
+        let message = [0x42; crate::MESSAGE_LENGTH];
+        let signature = Sig::sign(&sk, 3, &message).unwrap();
+
+        // the public key is serialized as root || parameter
+        let pk_bytes = pk.as_ssz_bytes();
+        assert_eq!(pk_bytes, Sig::public_key_to_bytes(&pk)[HEADER_LENGTH..]);
+        assert_eq!(
+            pk.hash_tree_root(),
+            [
+                0xe5, 0x9c, 0xd6, 0xf0, 0xc7, 0x9a, 0x1d, 0x20, 0xa9, 0x6f, 0x56, 0x4b, 0x27, 0x56,
+                0x5e, 0x54, 0x07, 0xc5, 0x3b, 0xc7, 0x45, 0x16, 0x6e, 0xc4, 0xbd, 0x8c, 0x6f, 0x14,
+                0xd8, 0x8a, 0x29, 0x67
+            ]
+        );
+
+        let signature = SszSignature::<IE, TH, LOG_LIFETIME>::try_from(signature).unwrap();
+        let sig_bytes = signature.as_ssz_bytes();
+        assert_eq!(sig_bytes.len(), 24 + (6 + 48) * 24);
+        assert_eq!(
+            <SszSignature<IE, TH, LOG_LIFETIME> as Encode>::ssz_fixed_len(),
+            sig_bytes.len()
+        );
+        assert_eq!(
+            signature.hash_tree_root(),
+            [
+                0xdf, 0x99, 0x92, 0x75, 0x41, 0xbb, 0x2f, 0x5c, 0x0c, 0x53, 0x35, 0x57, 0x4c, 0xaa,
+                0xab, 0xe2, 0x87, 0x7b, 0x96, 0x4a, 0x78, 0x85, 0x66, 0x3c, 0xda, 0x8c, 0x48, 0x77,
+                0x2b, 0x4e, 0x1b, 0x5e
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ssz_container_round_trip() {
+        let mut rng = rand::rng();
+        let (pk, sk) = SigPoseidon::key_gen(&mut rng, 0, SigPoseidon::LIFETIME as usize);
+        let epoch = 13;
+        let message = [0x17; crate::MESSAGE_LENGTH];
+        let signature = SigPoseidon::sign(&sk, epoch, &message).unwrap();
+
+        let attestation = SignedAttestation {
+            validator_index: 42,
+            public_key: pk,
+            epoch,
+            signature: signature.try_into().unwrap(),
+        };
+        let bytes = attestation.as_ssz_bytes();
+        assert_eq!(
+            bytes.len(),
+            8 + <GeneralizedXMSSPublicKey<THPoseidon> as Encode>::ssz_fixed_len()
+                + 8
+                + <SszSignature<IEPoseidon, THPoseidon, 6> as Encode>::ssz_fixed_len()
+        );
+
+        let decoded = SignedAttestation::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded.validator_index, 42);
+        assert_eq!(
+            decoded.public_key.hash_tree_root(),
+            attestation.public_key.hash_tree_root()
+        );
+        assert_eq!(
+            decoded.signature.hash_tree_root(),
+            attestation.signature.hash_tree_root()
+        );
+        assert_eq!(decoded.as_ssz_bytes(), bytes);
+        assert!(SigPoseidon::verify(
+            &decoded.public_key,
+            decoded.epoch,
+            &message,
+            decoded.signature.inner()
+        ));
+
+        // the opening can be used on its own
+        let opening =
+            SszHashTreeOpening::<THPoseidon, 6>::try_from(decoded.signature.into_inner().path)
+                .unwrap();
+        let opening_bytes = opening.as_ssz_bytes();
+        let decoded_opening =
+            SszHashTreeOpening::<THPoseidon, 6>::from_ssz_bytes(&opening_bytes).unwrap();
+        assert_eq!(decoded_opening.hash_tree_root(), opening.hash_tree_root());
+    }
+
+    #[test]
+    fn test_ssz_errors() {
+        let mut rng = rand::rng();
+        let (pk, sk) = SigPoseidon::key_gen(&mut rng, 0, SigPoseidon::LIFETIME as usize);
+        let signature = SigPoseidon::sign(&sk, 2, &[0x17; crate::MESSAGE_LENGTH]).unwrap();
+
+        // the sizes of the vectors must match the instantiation
+        assert!(matches!(
+            SszSignature::<IEPoseidon, THPoseidon, 5>::try_from(signature),
+            Err(SszError::InvalidCoPathLength {
+                expected: 5,
+                actual: 6
+            })
+        ));
+
+        // strict length checks
+        let bytes = pk.as_ssz_bytes();
+        for length in [0, bytes.len() - 1, bytes.len() + 1] {
+            let mut input = bytes.clone();
+            input.resize(length, 0);
+            assert_eq!(
+                GeneralizedXMSSPublicKey::<THPoseidon>::from_ssz_bytes(&input).err(),
+                Some(DecodeError::InvalidByteLength {
+                    len: length,
+                    expected: bytes.len()
+                })
+            );
+        }
+
+        // field elements must be canonical
+        let mut input = bytes;
+        input[..4].copy_from_slice(&F::ORDER_U32.to_le_bytes());
+        assert!(matches!(
+            GeneralizedXMSSPublicKey::<THPoseidon>::from_ssz_bytes(&input),
+            Err(DecodeError::BytesInvalid(_))
+        ));
+    }
+}
@@ -0,0 +1,382 @@
+use std::ops::Range;
+
+use crate::{
+    F, MESSAGE_LENGTH,
+    inc_encoding::IncomparableEncoding,
+    signature::VerificationError,
+    symmetric::{
+        prf::Pseudorandom,
+        tweak_hash::{TweakableHash, poseidon::record_poseidon_calls},
+    },
+};
+
+pub use crate::symmetric::tweak_hash::poseidon::{PermutationTrace, PoseidonCall};
+
+use super::{GeneralizedXMSSPublicKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme};
+
+/// Trace of walking one hash chain during verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTrace {
+    /// Index of the chain.
+    pub chain_index: u8,
+    /// Position in the chain at which the walk starts, given by the codeword.
+    pub start_pos_in_chain: u8,
+    /// Nodes of the chain, starting with the hash from the signature at position
+    /// `start_pos_in_chain`, and ending with the end of the chain.
+    pub nodes: Vec<Vec<F>>,
+    /// Indices of the Poseidon calls computing the steps, one per step.
+    pub calls: Range<usize>,
+}
+
+/// Trace of one step up the Merkle path during verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleStepTrace {
+    /// Level of the parent, where the leafs are in level 0.
+    pub level: u8,
+    /// Position of the parent within its level.
+    pub position: u64,
+    /// Whether the current node is the left child, i.e., the sibling is the right child.
+    pub is_left_child: bool,
+    /// The sibling, which is taken from the co-path of the signature.
+    pub sibling: Vec<F>,
+    /// The parent, i.e., the hash of both children.
+    pub parent: Vec<F>,
+    /// Index of the Poseidon call computing the parent.
+    pub call: usize,
+}
+
+/// Trace of a successful verification, which serves as a witness for a proof of
+/// the verification in an arithmetic circuit.
+///
+/// It contains the intermediate values of verification, and all calls to Poseidon in
+/// the order in which they are evaluated: first the message encoding, then the chain
+/// steps (chain by chain), then the hash of the chain ends, and finally the Merkle path.
+/// The intermediate values refer to their Poseidon calls by index, and each call
+/// contains the input and output states of its permutations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationTrace {
+    /// The epoch.
+    pub epoch: u64,
+    /// The message.
+    pub message: [u8; MESSAGE_LENGTH],
+    /// The parameter of the tweakable hash, from the public key.
+    pub parameter: Vec<F>,
+    /// The randomness of the message encoding, from the signature.
+    pub rho: Vec<F>,
+    /// The codeword obtained by encoding the message.
+    pub codeword: Vec<u8>,
+    /// Indices of the Poseidon calls of the message encoding.
+    pub encoding_calls: Range<usize>,
+    /// The chains, ordered by their index.
+    pub chains: Vec<ChainTrace>,
+    /// The leaf of the Merkle tree, i.e., the hash of the chain ends.
+    pub leaf: Vec<F>,
+    /// Indices of the Poseidon calls computing the leaf.
+    pub leaf_calls: Range<usize>,
+    /// The path from the leaf to the root, ordered by level.
+    pub merkle_path: Vec<MerkleStepTrace>,
+    /// The root, which is equal to the root in the public key.
+    pub root: Vec<F>,
+    /// All Poseidon calls, in order of evaluation.
+    pub poseidon_calls: Vec<PoseidonCall>,
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter> + AsRef<[F]>,
+    TH::Domain: AsRef<[F]>,
+    IE::Randomness: AsRef<[F]>,
+{
+    /// Verifies a signature like `verify_checked`, and returns the trace of
+    /// verification if the signature is valid. See `VerificationTrace`.
+    ///
+    /// This is only available for instantiations based on Poseidon2, for which hashes
+    /// are given by field elements. It is much slower than `verify`, as all
+    /// permutation states are recorded and chains are not walked in parallel.
+    pub fn verify_with_trace(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &GeneralizedXMSSSignature<IE, TH>,
+    ) -> Result<VerificationTrace, VerificationError> {
+        if epoch >= (1 << LOG_LIFETIME) {
+            return Err(VerificationError::EpochOutOfRange {
+                epoch,
+                lifetime: (1 << LOG_LIFETIME),
+            });
+        }
+        Self::check_well_formed(sig)?;
+
+        let mut poseidon_calls = Vec::new();
+
+        // first get back the codeword
+        let (encoding, encoding_calls) = record(&mut poseidon_calls, || {
+            IE::encode(&pk.parameter.into(), message, &sig.rho, epoch)
+        });
+        let Ok(codeword) = encoding else {
+            return Err(VerificationError::EncodingRejected);
+        };
+        assert!(
+            codeword.len() == IE::DIMENSION,
+            "Encoding is broken: returned too many or too few chunks."
+        );
+
+        // walk the chains step by step
+        let mut chains = Vec::with_capacity(IE::DIMENSION);
+        let mut chain_ends = Vec::with_capacity(IE::DIMENSION);
+        for (chain_index, (&start_pos_in_chain, start)) in
+            codeword.iter().zip(&sig.hashes).enumerate()
+        {
+            let mut current = *start;
+            let mut nodes = vec![current.as_ref().to_vec()];
+            let first_call = poseidon_calls.len();
+            for pos_in_chain in (start_pos_in_chain as usize + 1)..IE::BASE {
+                let tweak = TH::chain_tweak(epoch, chain_index as u8, pos_in_chain as u8);
+                (current, _) = record(&mut poseidon_calls, || {
+                    TH::apply(&pk.parameter, &tweak, &[current])
+                });
+                nodes.push(current.as_ref().to_vec());
+            }
+            chains.push(ChainTrace {
+                chain_index: chain_index as u8,
+                start_pos_in_chain,
+                nodes,
+                calls: first_call..poseidon_calls.len(),
+            });
+            chain_ends.push(current);
+        }
+
+        // hash the chain ends to get the leaf
+        let tweak = TH::tree_tweak(0, epoch);
+        let (leaf, leaf_calls) = record(&mut poseidon_calls, || {
+            TH::apply(&pk.parameter, &tweak, &chain_ends)
+        });
+
+        // walk up the Merkle path
+        let mut current_node = leaf;
+        let mut current_position = epoch;
+        let mut merkle_path = Vec::with_capacity(LOG_LIFETIME);
+        for (l, sibling) in sig.path.co_path.iter().enumerate() {
+            let is_left_child = current_position.is_multiple_of(2);
+            let children = if is_left_child {
+                [current_node, *sibling]
+            } else {
+                [*sibling, current_node]
+            };
+            current_position >>= 1;
+            let tweak = TH::tree_tweak((l + 1) as u8, current_position);
+            let (parent, calls) = record(&mut poseidon_calls, || {
+                TH::apply(&pk.parameter, &tweak, &children)
+            });
+            merkle_path.push(MerkleStepTrace {
+                level: (l + 1) as u8,
+                position: current_position,
+                is_left_child,
+                sibling: sibling.as_ref().to_vec(),
+                parent: parent.as_ref().to_vec(),
+                call: calls.start,
+            });
+            current_node = parent;
+        }
+
+        if current_node != pk.root {
+            return Err(VerificationError::RootMismatch);
+        }
+
+        Ok(VerificationTrace {
+            epoch,
+            message: *message,
+            parameter: pk.parameter.as_ref().to_vec(),
+            rho: sig.rho.as_ref().to_vec(),
+            codeword,
+            encoding_calls,
+            chains,
+            leaf: leaf.as_ref().to_vec(),
+            leaf_calls,
+            merkle_path,
+            root: current_node.as_ref().to_vec(),
+            poseidon_calls,
+        })
+    }
+}
+
+/// Helper function to run `f`, appending its Poseidon calls to `poseidon_calls`.
+/// Returns the result of `f` and the indices of its calls.
+fn record<T>(poseidon_calls: &mut Vec<PoseidonCall>, f: impl FnOnce() -> T) -> (T, Range<usize>) {
+    let (result, calls) = record_poseidon_calls(f);
+    let start = poseidon_calls.len();
+    poseidon_calls.extend(calls);
+    (result, start..poseidon_calls.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_symmetric::Permutation;
+
+    use crate::{
+        inc_encoding::target_sum::TargetSumEncoding,
+        poseidon2_16, poseidon2_24,
+        signature::{SignatureScheme, SignatureSchemeSecretKey},
+        symmetric::{
+            message_hash::{MessageHash, poseidon::PoseidonMessageHashW1},
+            prf::shake_to_field::ShakePRFtoF,
+            tweak_hash::poseidon::PoseidonTweakW1L5,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type PRF = ShakePRFtoF<7, 5>;
+    type TH = PoseidonTweakW1L5;
+    type MH = PoseidonMessageHashW1;
+    const BASE: usize = MH::BASE;
+    const NUM_CHUNKS: usize = MH::DIMENSION;
+    const MAX_CHUNK_VALUE: usize = BASE - 1;
+    const EXPECTED_SUM: usize = NUM_CHUNKS * MAX_CHUNK_VALUE / 2;
+    type IE = TargetSumEncoding<MH, EXPECTED_SUM>;
+    const LOG_LIFETIME: usize = 6;
+    type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+    #[test]
+    fn test_verify_with_trace() {
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let epoch = 37;
+        let message = [0x42; MESSAGE_LENGTH];
+        sk.prepare_for_epoch(epoch).unwrap();
+        let sig = Sig::sign(&sk, epoch, &message).unwrap();
+
+        let trace = Sig::verify_with_trace(&pk, epoch, &message, &sig).unwrap();
+        assert_eq!(trace.root, pk.root.to_vec());
+        assert_eq!(trace.parameter, pk.parameter.to_vec());
+        assert_eq!(trace.codeword.len(), IE::DIMENSION);
+
+        // the calls are partitioned into encoding, chains, leaf, and Merkle path
+        let calls = &trace.poseidon_calls;
+        assert_eq!(trace.encoding_calls.start, 0);
+        assert!(!trace.encoding_calls.is_empty());
+        let mut next_call = trace.encoding_calls.end;
+
+        // chains end at the end, and each step is a call
+        assert_eq!(trace.chains.len(), IE::DIMENSION);
+        for (chain_index, chain) in trace.chains.iter().enumerate() {
+            assert_eq!(chain.chain_index as usize, chain_index);
+            assert_eq!(chain.start_pos_in_chain, trace.codeword[chain_index]);
+            assert_eq!(chain.nodes[0], sig.hashes[chain_index].to_vec());
+            assert_eq!(chain.nodes.len(), BASE - chain.start_pos_in_chain as usize);
+            assert_eq!(chain.calls.start, next_call);
+            assert_eq!(chain.calls.len(), chain.nodes.len() - 1);
+            for (call, node) in calls[chain.calls.clone()].iter().zip(&chain.nodes[1..]) {
+                assert!(matches!(call, PoseidonCall::Compress { .. }));
+                assert_eq!(call.permutations()[0].width(), 16);
+                assert_eq!(call.output(), node.as_slice());
+            }
+            next_call = chain.calls.end;
+        }
+
+        // the leaf is the output of the sponge
+        assert_eq!(trace.leaf_calls.start, next_call);
+        let leaf_call = &calls[trace.leaf_calls.end - 1];
+        assert!(matches!(leaf_call, PoseidonCall::Sponge { .. }));
+        assert_eq!(leaf_call.output(), trace.leaf.as_slice());
+        next_call = trace.leaf_calls.end;
+
+        // the Merkle path leads from the leaf to the root
+        assert_eq!(trace.merkle_path.len(), LOG_LIFETIME);
+        let mut current_node = trace.leaf.clone();
+        let mut position = epoch;
+        for (l, step) in trace.merkle_path.iter().enumerate() {
+            assert_eq!(step.level as usize, l + 1);
+            assert_eq!(step.is_left_child, position % 2 == 0);
+            position >>= 1;
+            assert_eq!(step.position, position);
+            assert_eq!(step.call, next_call);
+            let PoseidonCall::Compress { permutation, .. } = &calls[step.call] else {
+                panic!("Merkle step must be a compression");
+            };
+            assert_eq!(permutation.width(), 24);
+            assert_eq!(calls[step.call].output(), step.parent.as_slice());
+
+            // the children are part of the input of the permutation
+            let (left, right) = if step.is_left_child {
+                (&current_node, &step.sibling)
+            } else {
+                (&step.sibling, &current_node)
+            };
+            let children: Vec<F> = left.iter().chain(right.iter()).copied().collect();
+            assert!(
+                permutation
+                    .input
+                    .windows(children.len())
+                    .any(|window| window == children.as_slice())
+            );
+
+            current_node = step.parent.clone();
+            next_call += 1;
+        }
+        assert_eq!(current_node, trace.root);
+        assert_eq!(next_call, calls.len());
+
+        // all permutation states are consistent with the permutations
+        for call in calls {
+            for permutation in call.permutations() {
+                let output = match permutation.width() {
+                    16 => poseidon2_16()
+                        .permute(permutation.input.clone().try_into().unwrap())
+                        .to_vec(),
+                    24 => poseidon2_24()
+                        .permute(permutation.input.clone().try_into().unwrap())
+                        .to_vec(),
+                    width => panic!("unexpected permutation width {width}"),
+                };
+                assert_eq!(output, permutation.output);
+            }
+            if let PoseidonCall::Compress {
+                permutation,
+                output,
+            } = call
+            {
+                for (i, fe) in output.iter().enumerate() {
+                    assert_eq!(*fe, permutation.output[i] + permutation.input[i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_with_trace_invalid() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let epoch = 3;
+        let message = [0x42; MESSAGE_LENGTH];
+        let mut sig = Sig::sign(&sk, epoch, &message).unwrap();
+
+        assert!(matches!(
+            Sig::verify_with_trace(&pk, epoch, &[0x43; MESSAGE_LENGTH], &sig),
+            Err(VerificationError::EncodingRejected | VerificationError::RootMismatch)
+        ));
+        assert!(matches!(
+            Sig::verify_with_trace(&pk, 1 << LOG_LIFETIME, &message, &sig),
+            Err(VerificationError::EpochOutOfRange { .. })
+        ));
+
+        sig.path.co_path[0] = pk.root;
+        assert!(matches!(
+            Sig::verify_with_trace(&pk, epoch, &message, &sig),
+            Err(VerificationError::RootMismatch)
+        ));
+        sig.path.co_path.pop();
+        assert!(matches!(
+            Sig::verify_with_trace(&pk, epoch, &message, &sig),
+            Err(VerificationError::InvalidPathLength { .. })
+        ));
+    }
+}
@@ -0,0 +1,782 @@
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData, ops::Range};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::ZeroizeOnDrop;
+
+use crate::{
+    MESSAGE_LENGTH,
+    inc_encoding::IncomparableEncoding,
+    signature::{
+        PreparationError, SignatureScheme, SignatureSchemeSecretKey, SigningError,
+        VerificationError,
+    },
+    symmetric::{
+        prf::Pseudorandom,
+        tweak_hash::TweakableHash,
+        tweak_hash_tree::{HashSubTree, HashTreeOpening, SparseTreePadding, wipe_nodes},
+    },
+};
+
+use super::{
+    GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignature,
+    GeneralizedXMSSSignatureScheme, REDACTED, SigningMode, leaf_hash, tree_padding,
+};
+
+/// Generalized XMSS signature scheme with a Merkle tree traversal in the secret key.
+///
+/// Instead of storing sub-trees of the Merkle tree, as `GeneralizedXMSSSignatureScheme`
+/// does, the secret key computes the authentication paths with a Merkle tree traversal.
+/// See `GeneralizedXMSSTraversalSecretKey`.
+///
+/// Public keys and signatures are the same as for `GeneralizedXMSSSignatureScheme`, as they
+/// do not depend on how the signer stores the Merkle tree. So the strategy can be selected
+/// per instantiation, and verifiers do not need to know which one the signer uses.
+pub struct GeneralizedXMSSTraversalScheme<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> {
+    _prf: PhantomData<PRF>,
+    _ie: PhantomData<IE>,
+    _th: PhantomData<TH>,
+}
+
+/// Secret key for `GeneralizedXMSSTraversalScheme`.
+/// It contains a PRF key and the state of a Merkle tree traversal.
+///
+/// Instead of a top tree and two bottom trees, the key stores the authentication path of
+/// the epoch for which it is prepared, and for each layer of the tree, a tree hash instance
+/// that computes the node that enters the authentication path next. This is the logarithmic
+/// traversal of [Szy04], on which the BDS traversal [BDS08] is based: the key stores
+/// O(LOG_LIFETIME) nodes, and advancing to the next epoch computes at most
+/// `2 * LOG_LIFETIME - 1` leafs, i.e., one-time public keys. In contrast, keys of
+/// `GeneralizedXMSSSignatureScheme` store about `sqrt(LIFETIME)` nodes, and compute a bottom
+/// tree of about `sqrt(LIFETIME)` leafs whenever the prepared interval is advanced.
+///
+/// The prepared interval always consists of a single epoch, and advancing the preparation
+/// moves it to the next epoch. So the preparation must be advanced after each epoch,
+/// whether the key has signed for this epoch or not.
+///
+/// References:
+/// [Szy04] M. Szydlo, "Merkle Tree Traversal in Log Space and Time", Eurocrypt 2004
+/// [BDS08] J. Buchmann, E. Dahmen, M. Schneider, "Merkle Tree Traversal Revisited", PQCrypto 2008
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct GeneralizedXMSSTraversalSecretKey<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> {
+    prf_key: PRF::Key,
+    parameter: TH::Parameter,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+    top_tree_padding_seed: [u8; 32],
+    /// The epoch for which the key is prepared.
+    epoch: u64,
+    /// The authentication path of the leaf of `epoch`, with one node per layer.
+    auth_path: Vec<TH::Domain>,
+    /// For each layer, the tree hash instance for the next node of the authentication path.
+    tree_hashes: Vec<TreeHash<TH>>,
+    _encoding_type: PhantomData<IE>,
+}
+
+/// Tree hash instance, which computes a node of the Merkle tree from the leafs below it,
+/// one leaf at a time. It only stores the roots of the complete sub-trees whose sibling
+/// has not been computed yet, which are at most one per layer, and finally the node.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct TreeHash<TH: TweakableHash> {
+    /// The layer of the node to compute.
+    layer: usize,
+    /// The position of the node to compute, or `None` if there is no such node, because
+    /// the authentication path does not change in this layer until the key expires.
+    position: Option<u64>,
+    /// The next leaf to compute.
+    next_leaf: u64,
+    /// Roots of the complete sub-trees, given by layer, position, and value.
+    /// The layers are decreasing. Once the node has been computed, it is the only entry.
+    stack: Vec<(usize, u64, TH::Domain)>,
+}
+
+impl<TH: TweakableHash> TreeHash<TH> {
+    /// Creates a tree hash instance for the node at the given position in the given layer
+    /// of the sparse Merkle tree whose leafs are the epochs in the activation interval.
+    fn new(
+        layer: usize,
+        position: Option<u64>,
+        activation_interval: &Range<u64>,
+        padding: &SparseTreePadding<TH>,
+    ) -> Self {
+        let mut tree_hash = Self {
+            layer,
+            position,
+            next_leaf: 0,
+            stack: Vec::new(),
+        };
+        if let Some(position) = position {
+            let leafs = (position << layer)..((position + 1) << layer);
+            tree_hash.next_leaf = leafs.start.max(activation_interval.start);
+            if tree_hash.next_leaf >= leafs.end.min(activation_interval.end) {
+                // no leaf below the node is active, so it is a padding node
+                let node = padding
+                    .node(layer, position)
+                    .expect("Traversal: node is neither active nor padding.");
+                tree_hash.push(layer, position, node);
+            }
+        }
+        tree_hash
+    }
+
+    /// Returns the node, or `None` if it has not been computed yet.
+    fn node(&self) -> Option<TH::Domain> {
+        match self.stack.as_slice() {
+            &[(layer, _, node)] if layer == self.layer => Some(node),
+            _ => None,
+        }
+    }
+
+    /// Pushes a node onto the stack. The buffer of the stack is allocated for one node per
+    /// layer up to the layer of the node, so that it is never reallocated, which would free
+    /// the old buffer without wiping it. Only stacks of deserialized instances can be full
+    /// before, and then the old buffer is wiped.
+    fn push(&mut self, layer: usize, position: u64, node: TH::Domain) {
+        if self.stack.len() == self.stack.capacity() {
+            let mut stack = Vec::with_capacity((self.layer + 1).max(self.stack.len() + 1));
+            stack.extend_from_slice(&self.stack);
+            wipe_nodes(&mut self.stack);
+            self.stack = stack;
+        }
+        self.stack.push((layer, position, node));
+    }
+
+    /// Returns the lowest layer of a node on the stack, which is the layer of the node
+    /// to compute if the stack is empty, or `None` if there is nothing to compute.
+    fn low(&self) -> Option<usize> {
+        if self.position.is_none() || self.node().is_some() {
+            return None;
+        }
+        Some(self.stack.last().map_or(self.layer, |(layer, _, _)| *layer))
+    }
+
+    /// Computes the next leaf, and then all parents for which both children are known.
+    /// Children that are not active are padding nodes.
+    fn update<PRF: Pseudorandom, IE: IncomparableEncoding>(
+        &mut self,
+        prf_key: &PRF::Key,
+        parameter: &TH::Parameter,
+        activation_interval: &Range<u64>,
+        padding: &SparseTreePadding<TH>,
+    ) where
+        PRF::Domain: Into<TH::Domain>,
+    {
+        let padding_node = |layer: usize, position: u64| {
+            padding
+                .node(layer, position)
+                .expect("Traversal: sibling of an active node is neither active nor padding.")
+        };
+
+        let leaf = self.next_leaf;
+        self.next_leaf += 1;
+        let (mut layer, mut position) = (0, leaf);
+        let mut node = leaf_hash::<PRF, IE, TH>(prf_key, parameter, leaf);
+        while layer < self.layer {
+            let children = if position.is_multiple_of(2) {
+                if position < (activation_interval.end - 1) >> layer {
+                    // the right sibling is active, so we wait until it is computed
+                    self.push(layer, position, node);
+                    return;
+                }
+                [node, padding_node(layer, position + 1)]
+            } else {
+                // the left sibling is on the stack, unless it is not active
+                match self.stack.last() {
+                    Some(&(sibling_layer, sibling_position, sibling))
+                        if sibling_layer == layer && sibling_position + 1 == position =>
+                    {
+                        self.stack.pop();
+                        [sibling, node]
+                    }
+                    _ => [padding_node(layer, position - 1), node],
+                }
+            };
+            layer += 1;
+            position >>= 1;
+            node = TH::apply(parameter, &TH::tree_tweak(layer as u8, position), &children);
+        }
+        self.push(layer, position, node);
+    }
+}
+
+impl<TH: TweakableHash> Drop for TreeHash<TH> {
+    fn drop(&mut self) {
+        // As for the trees of `GeneralizedXMSSSecretKey`, the nodes are derived from the
+        // PRF key, so we wipe them before the memory is freed.
+        wipe_nodes(&mut self.stack);
+    }
+}
+
+/// Helper function to get the position of the node in the given layer that enters the
+/// authentication path next after the given epoch, or `None` if the authentication path
+/// does not change in this layer before the end of the activation interval.
+fn next_auth_node_position(activation_end: u64, layer: usize, epoch: u64) -> Option<u64> {
+    let next_change = ((epoch >> layer) + 1) << layer;
+    (next_change < activation_end).then_some((next_change >> layer) ^ 0x01)
+}
+
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    GeneralizedXMSSTraversalSecretKey<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    /// Creates a key with a Merkle tree traversal from a secret key of the generalized XMSS
+    /// scheme, which is prepared for the first epoch of the prepared interval of that key.
+    /// Both keys produce the same signatures.
+    ///
+    /// Nodes are taken from the sub-trees of the given key if possible. So for keys with
+    /// the default tree layout, this does not compute any leafs.
+    #[must_use]
+    pub fn from_secret_key(sk: &GeneralizedXMSSSecretKey<PRF, IE, TH, LOG_LIFETIME>) -> Self {
+        let mut subtrees: Vec<&HashSubTree<TH>> = sk.prepared_subtrees.iter().flatten().collect();
+        subtrees.push(&sk.top_tree);
+
+        let mut traversal_sk = Self {
+            prf_key: sk.prf_key.clone(),
+            parameter: sk.parameter,
+            activation_epoch: sk.activation_epoch,
+            num_active_epochs: sk.num_active_epochs,
+            top_tree_padding_seed: sk.top_tree_padding_seed,
+            epoch: 0,
+            auth_path: Vec::new(),
+            tree_hashes: Vec::new(),
+            _encoding_type: PhantomData,
+        };
+        traversal_sk.initialize(sk.get_prepared_interval().start, &subtrees);
+        traversal_sk
+    }
+
+    /// Returns the padding of the sparse Merkle tree of the key.
+    fn padding(&self) -> SparseTreePadding<TH> {
+        tree_padding::<TH, LOG_LIFETIME>(
+            self.top_tree_padding_seed,
+            self.activation_epoch,
+            self.num_active_epochs,
+        )
+    }
+
+    /// Prepares the key for the given epoch from scratch, i.e., computes the authentication
+    /// path of the epoch and the next node of the authentication path in each layer.
+    /// Nodes are taken from the given sub-trees if they contain them, and computed otherwise.
+    fn initialize(&mut self, epoch: u64, subtrees: &[&HashSubTree<TH>]) {
+        let activation_interval = self.get_activation_interval();
+        let padding = self.padding();
+        let node = |layer: usize, position: u64| {
+            if let Some(node) = subtrees
+                .iter()
+                .find_map(|subtree| subtree.node(layer, position))
+            {
+                return *node;
+            }
+            let mut tree_hash =
+                TreeHash::new(layer, Some(position), &activation_interval, &padding);
+            loop {
+                if let Some(node) = tree_hash.node() {
+                    return node;
+                }
+                tree_hash.update::<PRF, IE>(
+                    &self.prf_key,
+                    &self.parameter,
+                    &activation_interval,
+                    &padding,
+                );
+            }
+        };
+
+        let auth_path = (0..LOG_LIFETIME)
+            .map(|layer| node(layer, (epoch >> layer) ^ 0x01))
+            .collect();
+        let tree_hashes = (0..LOG_LIFETIME)
+            .map(|layer| {
+                let position = next_auth_node_position(activation_interval.end, layer, epoch);
+                let mut tree_hash = TreeHash::new(layer, position, &activation_interval, &padding);
+                if let (Some(position), None) = (position, tree_hash.node()) {
+                    tree_hash.push(layer, position, node(layer, position));
+                }
+                tree_hash
+            })
+            .collect();
+
+        self.epoch = epoch;
+        self.auth_path = auth_path;
+        self.tree_hashes = tree_hashes;
+    }
+}
+
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    SignatureSchemeSecretKey for GeneralizedXMSSTraversalSecretKey<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    fn get_activation_interval(&self) -> Range<u64> {
+        let start = self.activation_epoch as u64;
+        let end = start + self.num_active_epochs as u64;
+        start..end
+    }
+
+    fn get_prepared_interval(&self) -> Range<u64> {
+        self.epoch..self.epoch + 1
+    }
+
+    fn advance_preparation(&mut self) {
+        let activation_interval = self.get_activation_interval();
+        let epoch = self.epoch + 1;
+        if epoch >= activation_interval.end {
+            return;
+        }
+        let padding = self.padding();
+
+        // The authentication path changes in all layers below the highest layer in which the
+        // ancestors of the old and the new epoch differ. The new nodes have been computed by
+        // the tree hash instances, which now start computing the next node in these layers.
+        for layer in 0..LOG_LIFETIME {
+            if !epoch.is_multiple_of(1 << layer) {
+                break;
+            }
+            self.auth_path[layer] = self.tree_hashes[layer].node().expect(
+                "Traversal: node of the authentication path has not been computed in time.",
+            );
+            let position = next_auth_node_position(activation_interval.end, layer, epoch);
+            self.tree_hashes[layer] =
+                TreeHash::new(layer, position, &activation_interval, &padding);
+        }
+
+        // We spend a budget of `2 * LOG_LIFETIME - 1` leafs on the tree hash instances, always
+        // updating the instance with the lowest node on its stack (the lowest layer in case of
+        // a tie). As shown in [Szy04], all nodes are then computed before they are needed.
+        for _ in 0..2 * LOG_LIFETIME - 1 {
+            let Some(layer) = (0..LOG_LIFETIME)
+                .filter_map(|layer| Some((self.tree_hashes[layer].low()?, layer)))
+                .min()
+                .map(|(_, layer)| layer)
+            else {
+                break;
+            };
+            self.tree_hashes[layer].update::<PRF, IE>(
+                &self.prf_key,
+                &self.parameter,
+                &activation_interval,
+                &padding,
+            );
+        }
+
+        self.epoch = epoch;
+    }
+
+    fn prepare_for_epoch(&mut self, epoch: u64) -> Result<(), PreparationError> {
+        let activation_interval = self.get_activation_interval();
+        if !activation_interval.contains(&epoch) {
+            return Err(PreparationError::EpochNotActive {
+                epoch,
+                activation_interval,
+            });
+        }
+        let prepared_interval = self.get_prepared_interval();
+        if epoch < prepared_interval.start {
+            return Err(PreparationError::EpochBehindPreparedInterval {
+                epoch,
+                prepared_interval,
+            });
+        }
+
+        // Advancing computes about LOG_LIFETIME leafs per epoch on average. Initializing the
+        // traversal for the epoch computes at most two nodes per layer, whose sub-trees
+        // are disjoint within a layer, i.e., at most two leafs per active epoch.
+        let num_skipped_epochs = epoch - self.epoch;
+        if num_skipped_epochs.saturating_mul(LOG_LIFETIME as u64)
+            > 2 * self.num_active_epochs as u64
+        {
+            self.initialize(epoch, &[]);
+        } else {
+            for _ in 0..num_skipped_epochs {
+                self.advance_preparation();
+            }
+        }
+        Ok(())
+    }
+
+    fn tree_memory_bytes(&self) -> usize {
+        // the key stores the authentication path and the nodes of the tree hash instances
+        let num_nodes = self.auth_path.len()
+            + self
+                .tree_hashes
+                .iter()
+                .map(|tree_hash| tree_hash.stack.len())
+                .sum::<usize>();
+        num_nodes * core::mem::size_of::<TH::Domain>()
+    }
+}
+
+// The secret fields wipe themselves: the PRF key is required to do so by the `Pseudorandom`
+// trait, and the tree hash instances wipe their nodes when dropped. The authentication
+// path is public, as it is part of the signature.
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    ZeroizeOnDrop for GeneralizedXMSSTraversalSecretKey<PRF, IE, TH, LOG_LIFETIME>
+{
+}
+
+/// Redacts the PRF key and the nodes, and only shows the intervals of the key.
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    fmt::Debug for GeneralizedXMSSTraversalSecretKey<PRF, IE, TH, LOG_LIFETIME>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneralizedXMSSTraversalSecretKey")
+            .field("prf_key", &REDACTED)
+            .field("activation_epoch", &self.activation_epoch)
+            .field("num_active_epochs", &self.num_active_epochs)
+            .field("epoch", &self.epoch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding + Sync + Send,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+> SignatureScheme for GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Domain: Into<TH::Domain>,
+    PRF::Randomness: Into<IE::Randomness>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    type PublicKey = GeneralizedXMSSPublicKey<TH>;
+
+    type SecretKey = GeneralizedXMSSTraversalSecretKey<PRF, IE, TH, LOG_LIFETIME>;
+
+    type Signature = GeneralizedXMSSSignature<IE, TH>;
+
+    const LIFETIME: u64 = 1 << LOG_LIFETIME;
+
+    /// Note: key generation is the same as for `GeneralizedXMSSSignatureScheme`, which
+    /// computes all leafs in parallel. So it temporarily needs as much memory as a
+    /// secret key of that scheme.
+    fn key_gen<R: Rng>(
+        rng: &mut R,
+        activation_epoch: usize,
+        num_active_epochs: usize,
+    ) -> (Self::PublicKey, Self::SecretKey) {
+        let (pk, sk) = GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::key_gen(
+            rng,
+            activation_epoch,
+            num_active_epochs,
+        );
+        (pk, GeneralizedXMSSTraversalSecretKey::from_secret_key(&sk))
+    }
+
+    fn sign(
+        sk: &Self::SecretKey,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Self::Signature, SigningError> {
+        // check that epoch is indeed a valid epoch in the activation range
+        if !sk.get_activation_interval().contains(&epoch) {
+            return Err(SigningError::EpochNotActive { epoch });
+        }
+
+        // check that we are prepared for this epoch
+        if !sk.get_prepared_interval().contains(&epoch) {
+            return Err(SigningError::EpochNotPrepared { epoch });
+        }
+
+        // the Merkle path is the authentication path that the traversal maintains,
+        // the rest of the signature is computed as for `GeneralizedXMSSSignatureScheme`
+        let path = HashTreeOpening {
+            co_path: sk.auth_path.clone(),
+        };
+        let (rho, hashes) =
+            GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::encode_and_walk_chains(
+                &sk.prf_key,
+                &sk.parameter,
+                epoch,
+                message,
+                SigningMode::VariableTime,
+            )?;
+
+        Ok(GeneralizedXMSSSignature { path, rho, hashes })
+    }
+
+    fn verify_checked(
+        pk: &Self::PublicKey,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &Self::Signature,
+    ) -> Result<(), VerificationError> {
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::verify_checked(
+            pk, epoch, message, sig,
+        )
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::internal_consistency_check();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::signature::{
+        generalized_xmss::TreeLayout,
+        test_fixtures::{IE, PRF, TH},
+        test_templates::test_signature_scheme_correctness,
+    };
+
+    use super::*;
+
+    /// Checks that a traversal key created from the key with the given layout produces
+    /// the same signatures as that key for all epochs, when advancing one epoch at a time.
+    fn check_same_signatures<const LOG_LIFETIME: usize>(
+        activation_epoch: usize,
+        num_active_epochs: usize,
+        tree_layout: TreeLayout,
+    ) {
+        type Sig<const L: usize> = GeneralizedXMSSSignatureScheme<PRF, IE, TH, L>;
+        type TraversalSig<const L: usize> = GeneralizedXMSSTraversalScheme<PRF, IE, TH, L>;
+
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::<LOG_LIFETIME>::key_gen_with_layout(
+            &mut rng,
+            activation_epoch,
+            num_active_epochs,
+            tree_layout,
+        );
+        let mut traversal_sk = GeneralizedXMSSTraversalSecretKey::from_secret_key(&sk);
+        let activation_interval = sk.get_activation_interval();
+        assert_eq!(traversal_sk.get_activation_interval(), activation_interval);
+        let start = activation_interval.start;
+        assert_eq!(traversal_sk.get_prepared_interval(), start..start + 1);
+
+        let message = rng.random();
+        for epoch in activation_interval.clone() {
+            assert_eq!(traversal_sk.get_prepared_interval(), epoch..epoch + 1);
+            sk.prepare_for_epoch(epoch).unwrap();
+            let signature = Sig::<LOG_LIFETIME>::sign(&sk, epoch, &message).unwrap();
+            let traversal_signature =
+                TraversalSig::<LOG_LIFETIME>::sign(&traversal_sk, epoch, &message).unwrap();
+            assert_eq!(traversal_signature.path.co_path, signature.path.co_path);
+            assert!(traversal_signature.hashes == signature.hashes);
+            assert!(TraversalSig::<LOG_LIFETIME>::verify(
+                &pk,
+                epoch,
+                &message,
+                &traversal_signature
+            ));
+            traversal_sk.advance_preparation();
+        }
+
+        // advancing stops at the end of the activation interval
+        let last = activation_interval.end - 1;
+        assert_eq!(traversal_sk.get_prepared_interval(), last..last + 1);
+    }
+
+    #[test]
+    fn test_traversal_correctness() {
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        Sig::internal_consistency_check();
+
+        test_signature_scheme_correctness::<Sig>(0, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(13, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(63, 0, Sig::LIFETIME as usize);
+        test_signature_scheme_correctness::<Sig>(21, 13, 20);
+    }
+
+    #[test]
+    fn test_traversal_same_signatures() {
+        let layout = |log_bottom_tree, num_levels| TreeLayout {
+            log_bottom_tree,
+            num_levels,
+        };
+
+        // the full tree, and intervals that are not aligned to large sub-trees,
+        // so that the traversal needs the padding of the sparse tree
+        check_same_signatures::<6>(0, 64, layout(3, 2));
+        check_same_signatures::<6>(2, 40, layout(1, 2));
+        check_same_signatures::<6>(22, 12, layout(1, 3));
+        check_same_signatures::<7>(32, 64, layout(3, 2));
+        check_same_signatures::<7>(6, 100, layout(1, 2));
+        check_same_signatures::<5>(30, 2, layout(1, 2));
+    }
+
+    #[test]
+    fn test_traversal_memory() {
+        const LOG_LIFETIME: usize = 8;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let mut rng = rand::rng();
+        let (_pk, mut sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let mut traversal_sk = GeneralizedXMSSTraversalSecretKey::from_secret_key(&sk);
+
+        // the traversal stores the authentication path, and for each layer at most one node.
+        // As shown in [Szy04], the stacks of all tree hash instances together contain at most
+        // one further node per layer, so the key stores at most `3 * LOG_LIFETIME` nodes.
+        let domain_size = std::mem::size_of::<<TH as TweakableHash>::Domain>();
+        let bound = 3 * LOG_LIFETIME * domain_size;
+        let mut max_memory_bytes = 0;
+        for _ in 0..1 << LOG_LIFETIME {
+            max_memory_bytes = max_memory_bytes.max(traversal_sk.tree_memory_bytes());
+            traversal_sk.advance_preparation();
+        }
+        assert!(max_memory_bytes <= bound);
+
+        // which is a lot less than storing the top tree and two bottom trees
+        sk.advance_preparation();
+        assert!(2 * max_memory_bytes < sk.tree_memory_bytes());
+    }
+
+    #[test]
+    fn test_traversal_nodes_wiped() {
+        const LOG_LIFETIME: usize = 6;
+        type TraversalSig = GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let mut rng = rand::rng();
+        let (_pk, mut sk) = TraversalSig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        sk.prepare_for_epoch(5).unwrap();
+
+        // dropping the key wipes the stacks of the tree hash instances,
+        // which include the nodes that they have computed
+        let entry_size = std::mem::size_of::<(usize, u64, <TH as TweakableHash>::Domain)>();
+        let buffers: Vec<_> = sk
+            .tree_hashes
+            .iter()
+            .filter(|tree_hash| tree_hash.stack.capacity() > 0)
+            .map(|tree_hash| {
+                (
+                    tree_hash.stack.as_ptr().cast(),
+                    tree_hash.stack.capacity() * entry_size,
+                )
+            })
+            .collect();
+        assert!(!buffers.is_empty());
+        let wiped = crate::test_allocator::wiped_on_free(&buffers, || drop(sk));
+        assert!(wiped.iter().all(|wiped| *wiped == Some(true)));
+    }
+
+    #[test]
+    fn test_traversal_prepare_for_epoch() {
+        const LOG_LIFETIME: usize = 6;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        type TraversalSig = GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let seed = rand::rng().random();
+        let (pk, sk) = Sig::key_gen(&mut ChaCha20Rng::from_seed(seed), 8, 48);
+        let (traversal_pk, mut traversal_sk) =
+            TraversalSig::key_gen(&mut ChaCha20Rng::from_seed(seed), 8, 48);
+        assert_eq!(traversal_pk.root, pk.root);
+        assert_eq!(traversal_sk.get_activation_interval(), 8..56);
+
+        // both small steps, which advance one epoch at a time, and large steps,
+        // which initialize the traversal for the new epoch
+        let message = [0x42; MESSAGE_LENGTH];
+        for epoch in [8, 9, 12, 13, 40, 41, 47, 55] {
+            traversal_sk.prepare_for_epoch(epoch).unwrap();
+            assert_eq!(traversal_sk.get_prepared_interval(), epoch..epoch + 1);
+            let signature = TraversalSig::sign(&traversal_sk, epoch, &message).unwrap();
+            assert!(TraversalSig::verify(&pk, epoch, &message, &signature));
+        }
+
+        // afterwards, advancing still works
+        let mut traversal_sk = GeneralizedXMSSTraversalSecretKey::from_secret_key(&sk);
+        traversal_sk.prepare_for_epoch(33).unwrap();
+        for epoch in 33..56 {
+            let signature = TraversalSig::sign(&traversal_sk, epoch, &message).unwrap();
+            assert!(TraversalSig::verify(&pk, epoch, &message, &signature));
+            traversal_sk.advance_preparation();
+        }
+
+        // epochs outside the activation interval or behind the key are rejected
+        assert!(matches!(
+            traversal_sk.prepare_for_epoch(56),
+            Err(PreparationError::EpochNotActive { epoch: 56, .. })
+        ));
+        assert!(matches!(
+            traversal_sk.prepare_for_epoch(54),
+            Err(PreparationError::EpochBehindPreparedInterval { epoch: 54, .. })
+        ));
+
+        // signing for another epoch than the prepared one fails
+        assert!(matches!(
+            TraversalSig::sign(&traversal_sk, 54, &message),
+            Err(SigningError::EpochNotPrepared { epoch: 54 })
+        ));
+        assert!(matches!(
+            TraversalSig::sign(&traversal_sk, 56, &message),
+            Err(SigningError::EpochNotActive { epoch: 56 })
+        ));
+    }
+
+    #[test]
+    fn test_traversal_from_multi_level_key() {
+        const LOG_LIFETIME: usize = 8;
+        type Sig = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+        type TraversalSig = GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+        let mut rng = rand::rng();
+        let (pk, mut sk) = Sig::key_gen_with_levels(&mut rng, 0, 1 << LOG_LIFETIME, 3);
+        sk.prepare_for_epoch(100).unwrap();
+        let start = sk.get_prepared_interval().start;
+        let traversal_sk = GeneralizedXMSSTraversalSecretKey::from_secret_key(&sk);
+        assert_eq!(traversal_sk.get_prepared_interval(), start..start + 1);
+
+        let message = [0x42; MESSAGE_LENGTH];
+        let signature = Sig::sign(&sk, start, &message).unwrap();
+        let traversal_signature = TraversalSig::sign(&traversal_sk, start, &message).unwrap();
+        assert_eq!(traversal_signature.path.co_path, signature.path.co_path);
+        assert!(TraversalSig::verify(
+            &pk,
+            start,
+            &message,
+            &traversal_signature
+        ));
+    }
+
+    #[test]
+    fn test_traversal_serialization() {
+        const LOG_LIFETIME: usize = 6;
+        type TraversalSig = GeneralizedXMSSTraversalScheme<PRF, IE, TH, LOG_LIFETIME>;
+        let config = bincode::config::standard();
+
+        let mut rng = rand::rng();
+        let (pk, mut sk) = TraversalSig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        sk.prepare_for_epoch(5).unwrap();
+
+        // a key restored in the middle of a traversal continues where the original left off
+        let bytes = bincode::serde::encode_to_vec(&sk, config).unwrap();
+        let (mut restored, _): (
+            GeneralizedXMSSTraversalSecretKey<PRF, IE, TH, LOG_LIFETIME>,
+            _,
+        ) = bincode::serde::decode_from_slice(&bytes, config).unwrap();
+        let message = [0x42; MESSAGE_LENGTH];
+        for epoch in 5..20 {
+            let signature = TraversalSig::sign(&restored, epoch, &message).unwrap();
+            assert!(TraversalSig::verify(&pk, epoch, &message, &signature));
+            restored.advance_preparation();
+        }
+
+        // and the debug output does not leak the key
+        let debug = format!("{sk:?}");
+        assert!(debug.contains(REDACTED));
+        assert!(debug.contains("epoch: 5"));
+    }
+}
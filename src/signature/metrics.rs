@@ -0,0 +1,163 @@
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Prefix of the names of all metrics in the Prometheus text format.
+const METRIC_PREFIX: &str = "hashsig_secret_key";
+
+/// Metrics about a secret key, obtained via `SignatureSchemeSecretKey::metrics`.
+///
+/// Epoch intervals are given by their (inclusive) start and (exclusive) end. All values
+/// are gauges, and can be exported in the Prometheus text format via `to_prometheus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecretKeyMetrics {
+    /// First epoch of the activation interval.
+    pub activation_start: u64,
+    /// First epoch after the activation interval.
+    pub expiration_epoch: u64,
+    /// First epoch of the prepared interval.
+    pub prepared_start: u64,
+    /// First epoch after the prepared interval.
+    pub prepared_end: u64,
+    /// The next epoch to sign for, with respect to which the remaining epochs are counted.
+    pub next_epoch: u64,
+    /// Number of epochs for which the key can still sign.
+    pub remaining_epochs: u64,
+    /// Number of epochs for which the key can still sign without further preparation.
+    pub remaining_prepared_epochs: u64,
+    /// First epoch for which signing requires a call to `advance_preparation`, or
+    /// `None` if the prepared interval reaches the end of the activation interval.
+    pub next_preparation_epoch: Option<u64>,
+    /// Estimated memory used by the precomputed trees, in bytes.
+    pub tree_memory_bytes: u64,
+}
+
+impl SecretKeyMetrics {
+    /// Returns the metrics in the Prometheus text exposition format, with the given labels
+    /// (e.g., to identify the key) attached to all samples. Metric names have the prefix
+    /// `hashsig_secret_key_`. If there is no next preparation epoch, it is omitted.
+    #[must_use]
+    pub fn to_prometheus(&self, labels: &[(&str, &str)]) -> String {
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(name, value)| format!("{name}=\"{}\"", escape_label_value(value)))
+                .collect();
+            format!("{{{}}}", labels.join(","))
+        };
+
+        let gauges = [
+            (
+                "activation_start_epoch",
+                "First epoch of the activation interval.",
+                Some(self.activation_start),
+            ),
+            (
+                "expiration_epoch",
+                "First epoch after the activation interval.",
+                Some(self.expiration_epoch),
+            ),
+            (
+                "prepared_start_epoch",
+                "First epoch of the prepared interval.",
+                Some(self.prepared_start),
+            ),
+            (
+                "prepared_end_epoch",
+                "First epoch after the prepared interval.",
+                Some(self.prepared_end),
+            ),
+            (
+                "next_epoch",
+                "Next epoch to sign for.",
+                Some(self.next_epoch),
+            ),
+            (
+                "remaining_epochs",
+                "Number of epochs for which the key can still sign.",
+                Some(self.remaining_epochs),
+            ),
+            (
+                "remaining_prepared_epochs",
+                "Number of epochs for which the key can still sign without further preparation.",
+                Some(self.remaining_prepared_epochs),
+            ),
+            (
+                "next_preparation_epoch",
+                "First epoch for which signing requires advancing the preparation.",
+                self.next_preparation_epoch,
+            ),
+            (
+                "tree_memory_bytes",
+                "Estimated memory used by the precomputed trees.",
+                Some(self.tree_memory_bytes),
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in gauges {
+            let Some(value) = value else {
+                continue;
+            };
+            // writing into a string does not fail
+            let _ = writeln!(out, "# HELP {METRIC_PREFIX}_{name} {help}");
+            let _ = writeln!(out, "# TYPE {METRIC_PREFIX}_{name} gauge");
+            let _ = writeln!(out, "{METRIC_PREFIX}_{name}{labels} {value}");
+        }
+        out
+    }
+}
+
+/// Helper function to escape a label value for the Prometheus text format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_prometheus() {
+        let metrics = SecretKeyMetrics {
+            activation_start: 0,
+            expiration_epoch: 64,
+            prepared_start: 8,
+            prepared_end: 24,
+            next_epoch: 10,
+            remaining_epochs: 54,
+            remaining_prepared_epochs: 14,
+            next_preparation_epoch: Some(24),
+            tree_memory_bytes: 1234,
+        };
+
+        let text = metrics.to_prometheus(&[("key", "validator-1")]);
+        assert_eq!(text.lines().count(), 9 * 3);
+        assert!(text.contains(
+            "# TYPE hashsig_secret_key_remaining_epochs gauge\n\
+             hashsig_secret_key_remaining_epochs{key=\"validator-1\"} 54\n"
+        ));
+        assert!(
+            text.contains("hashsig_secret_key_next_preparation_epoch{key=\"validator-1\"} 24\n")
+        );
+        assert!(text.contains("hashsig_secret_key_tree_memory_bytes{key=\"validator-1\"} 1234\n"));
+
+        // no labels, and no next preparation epoch
+        let metrics = SecretKeyMetrics {
+            next_preparation_epoch: None,
+            ..metrics
+        };
+        let text = metrics.to_prometheus(&[]);
+        assert_eq!(text.lines().count(), 8 * 3);
+        assert!(!text.contains("next_preparation_epoch"));
+        assert!(text.contains("hashsig_secret_key_expiration_epoch 64\n"));
+
+        // label values are escaped
+        let text = metrics.to_prometheus(&[("path", "a\\b\"c\nd")]);
+        assert!(text.contains("hashsig_secret_key_next_epoch{path=\"a\\\\b\\\"c\\nd\"} 10\n"));
+    }
+}
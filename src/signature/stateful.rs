@@ -0,0 +1,395 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::MESSAGE_LENGTH;
+
+use super::{SignatureScheme, SignatureSchemeSecretKey, SigningError, metrics::SecretKeyMetrics};
+
+/// Error enum for signing with a `StatefulSigner`.
+#[derive(Debug, Error)]
+pub enum StatefulSigningError {
+    /// Occurs when the epoch is not strictly larger than the last epoch
+    /// for which a signature has been released.
+    #[error("Refusing to sign for epoch {epoch}, already signed for epoch {last_signed_epoch}.")]
+    EpochNotAboveWatermark { epoch: u64, last_signed_epoch: u64 },
+
+    /// Occurs when the watermark could not be persisted. In this case,
+    /// no signature is released.
+    #[error("Failed to persist the watermark: {0}")]
+    Storage(#[from] io::Error),
+
+    /// Occurs when the underlying signature scheme fails to sign.
+    #[error(transparent)]
+    Signing(#[from] SigningError),
+}
+
+/// Defines the interface for durable storage of the watermark of a `StatefulSigner`,
+/// i.e., the last epoch for which a signature has been released.
+pub trait WatermarkStorage {
+    /// Loads the last signed epoch, or `None` if nothing has been signed so far.
+    fn load(&self) -> io::Result<Option<u64>>;
+
+    /// Stores the last signed epoch. Implementations must only return `Ok`
+    /// once the value has been persisted durably, i.e., it survives a crash.
+    fn store(&mut self, last_signed_epoch: u64) -> io::Result<()>;
+}
+
+/// Watermark storage that only keeps the watermark in memory.
+///
+/// Warning: this does not survive restarts, and is meant for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryWatermarkStorage {
+    last_signed_epoch: Option<u64>,
+}
+
+impl WatermarkStorage for InMemoryWatermarkStorage {
+    fn load(&self) -> io::Result<Option<u64>> {
+        Ok(self.last_signed_epoch)
+    }
+
+    fn store(&mut self, last_signed_epoch: u64) -> io::Result<()> {
+        self.last_signed_epoch = Some(last_signed_epoch);
+        Ok(())
+    }
+}
+
+/// Watermark storage backed by a file.
+///
+/// The watermark is stored as a big-endian `u64`. A missing file means that
+/// nothing has been signed so far. Updates are written to a temporary file,
+/// which is synced and then atomically renamed to the target path. Finally,
+/// the parent directory is synced so that the rename itself is durable.
+#[derive(Debug)]
+pub struct FileWatermarkStorage {
+    path: PathBuf,
+}
+
+impl FileWatermarkStorage {
+    /// Creates a storage for the watermark at the given path.
+    /// The file (and the temporary file next to it) is only created on `store`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the watermark file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the temporary file that is renamed to the watermark file.
+    fn temporary_path(&self) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+        self.path.with_file_name(file_name)
+    }
+}
+
+impl WatermarkStorage for FileWatermarkStorage {
+    fn load(&self) -> io::Result<Option<u64>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        // the file must contain exactly one u64, everything else means it is corrupted
+        let mut bytes = Vec::with_capacity(8);
+        file.read_to_end(&mut bytes)?;
+        let bytes: [u8; 8] = bytes.try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Watermark file does not contain exactly 8 bytes",
+            )
+        })?;
+        Ok(Some(u64::from_be_bytes(bytes)))
+    }
+
+    fn store(&mut self, last_signed_epoch: u64) -> io::Result<()> {
+        // write the new watermark to a temporary file and make sure it hits the disk
+        let temporary_path = self.temporary_path();
+        let mut file = File::create(&temporary_path)?;
+        file.write_all(&last_signed_epoch.to_be_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        // atomically replace the old watermark, so that a crash leaves either the
+        // old or the new watermark, but never a partially written file.
+        fs::rename(&temporary_path, &self.path)?;
+
+        // make the rename durable by syncing the directory containing the file.
+        // Note: directories cannot be opened for syncing on all platforms.
+        #[cfg(unix)]
+        {
+            let parent = match self.path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            File::open(parent)?.sync_all()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A signer that owns a secret key and enforces that it signs at most once per epoch.
+///
+/// The signer keeps a watermark, which is the last epoch for which a signature has been
+/// released. It only signs for epochs strictly above the watermark, which means that
+/// epochs have to be used in increasing order. Before a signature is returned, the new
+/// watermark is persisted using the given `WatermarkStorage`. If this fails, the signature
+/// is not released.
+///
+/// Note: the watermark must be stored alongside the secret key. Restoring an old backup of
+/// the watermark (or none at all) together with the key can lead to signing the same epoch twice.
+pub struct StatefulSigner<S: SignatureScheme, W: WatermarkStorage = FileWatermarkStorage> {
+    sk: S::SecretKey,
+    storage: W,
+    last_signed_epoch: Option<u64>,
+}
+
+impl<S: SignatureScheme, W: WatermarkStorage> StatefulSigner<S, W> {
+    /// Creates a signer from a secret key and a storage for the watermark.
+    /// The current watermark is loaded from the storage.
+    pub fn new(sk: S::SecretKey, storage: W) -> io::Result<Self> {
+        let last_signed_epoch = storage.load()?;
+        Ok(Self {
+            sk,
+            storage,
+            last_signed_epoch,
+        })
+    }
+
+    /// Returns the last epoch for which a signature has been released,
+    /// or `None` if nothing has been signed so far.
+    #[must_use]
+    pub const fn last_signed_epoch(&self) -> Option<u64> {
+        self.last_signed_epoch
+    }
+
+    /// Returns the secret key owned by this signer.
+    #[must_use]
+    pub const fn secret_key(&self) -> &S::SecretKey {
+        &self.sk
+    }
+
+    /// Returns metrics about the secret key, where the next epoch to sign for is the
+    /// one after the watermark (or the start of the activation interval if nothing has
+    /// been signed so far). See `SignatureSchemeSecretKey::metrics`.
+    #[must_use]
+    pub fn metrics(&self) -> SecretKeyMetrics {
+        let next_epoch = self.last_signed_epoch.map_or_else(
+            || self.sk.get_activation_interval().start,
+            |last_signed_epoch| last_signed_epoch + 1,
+        );
+        self.sk.metrics(next_epoch)
+    }
+
+    /// Advances the prepared interval of the secret key.
+    /// See `SignatureSchemeSecretKey::advance_preparation`.
+    pub fn advance_preparation(&mut self) {
+        self.sk.advance_preparation();
+    }
+
+    /// Signs a message for the given epoch, if the epoch is strictly above the
+    /// watermark. The watermark is updated and persisted before the signature is returned.
+    pub fn sign(
+        &mut self,
+        epoch: u64,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<S::Signature, StatefulSigningError> {
+        if let Some(last_signed_epoch) = self.last_signed_epoch {
+            if epoch <= last_signed_epoch {
+                return Err(StatefulSigningError::EpochNotAboveWatermark {
+                    epoch,
+                    last_signed_epoch,
+                });
+            }
+        }
+
+        let signature = S::sign(&self.sk, epoch, message)?;
+
+        // persist the watermark before the signature leaves this function
+        self.storage.store(epoch)?;
+        self.last_signed_epoch = Some(epoch);
+
+        Ok(signature)
+    }
+
+    /// Consumes the signer and returns the secret key and the watermark storage.
+    pub fn into_parts(self) -> (S::SecretKey, W) {
+        (self.sk, self.storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::signature::test_fixtures::{LOG_LIFETIME, Sig};
+
+    use super::*;
+
+    /// Storage that always fails to persist the watermark.
+    struct FailingWatermarkStorage;
+
+    impl WatermarkStorage for FailingWatermarkStorage {
+        fn load(&self) -> io::Result<Option<u64>> {
+            Ok(None)
+        }
+
+        fn store(&mut self, _last_signed_epoch: u64) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+    }
+
+    /// Returns a fresh path in the temporary directory.
+    fn temporary_watermark_path() -> PathBuf {
+        let mut rng = rand::rng();
+        let name = format!(
+            "hashsig-watermark-{}-{:016x}",
+            std::process::id(),
+            rng.random::<u64>()
+        );
+        std::env::temp_dir().join(name)
+    }
+
+    #[test]
+    fn test_refuses_epochs_at_or_below_watermark() {
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let mut signer =
+            StatefulSigner::<Sig, _>::new(sk, InMemoryWatermarkStorage::default()).unwrap();
+        assert_eq!(signer.last_signed_epoch(), None);
+
+        let message = rng.random();
+        let signature = signer.sign(3, &message).unwrap();
+        assert!(Sig::verify(&pk, 3, &message, &signature));
+        assert_eq!(signer.last_signed_epoch(), Some(3));
+
+        // signing the same or an earlier epoch again is refused, even for the same message
+        for epoch in [3, 2, 0] {
+            assert!(matches!(
+                signer.sign(epoch, &message),
+                Err(StatefulSigningError::EpochNotAboveWatermark {
+                    last_signed_epoch: 3,
+                    ..
+                })
+            ));
+        }
+
+        // epochs can be skipped
+        let signature = signer.sign(7, &message).unwrap();
+        assert!(Sig::verify(&pk, 7, &message, &signature));
+        assert_eq!(signer.last_signed_epoch(), Some(7));
+    }
+
+    #[test]
+    fn test_metrics_follow_watermark() {
+        let mut rng = rand::rng();
+        let (_pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let mut signer =
+            StatefulSigner::<Sig, _>::new(sk, InMemoryWatermarkStorage::default()).unwrap();
+
+        let metrics = signer.metrics();
+        assert_eq!(metrics.next_epoch, 0);
+        assert_eq!(metrics.remaining_epochs, 1 << LOG_LIFETIME);
+
+        let message = rng.random();
+        signer.sign(5, &message).unwrap();
+        let metrics = signer.metrics();
+        assert_eq!(metrics.next_epoch, 6);
+        assert_eq!(metrics.remaining_epochs, (1 << LOG_LIFETIME) - 6);
+        assert_eq!(metrics.remaining_prepared_epochs, 16 - 6);
+    }
+
+    #[test]
+    fn test_refuses_epochs_key_cannot_sign() {
+        let mut rng = rand::rng();
+        let (_pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let mut signer =
+            StatefulSigner::<Sig, _>::new(sk, InMemoryWatermarkStorage::default()).unwrap();
+        let message = rng.random();
+
+        let prepared_end = signer.secret_key().get_prepared_interval().end;
+        assert!(matches!(
+            signer.sign(prepared_end, &message),
+            Err(StatefulSigningError::Signing(
+                SigningError::EpochNotPrepared { .. }
+            ))
+        ));
+        assert!(matches!(
+            signer.sign(1 << LOG_LIFETIME, &message),
+            Err(StatefulSigningError::Signing(
+                SigningError::EpochNotActive { .. }
+            ))
+        ));
+
+        // rejected epochs do not move the watermark
+        assert_eq!(signer.last_signed_epoch(), None);
+    }
+
+    #[test]
+    fn test_no_signature_if_storage_fails() {
+        let mut rng = rand::rng();
+        let (_pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let mut signer = StatefulSigner::<Sig, _>::new(sk, FailingWatermarkStorage).unwrap();
+        let message = rng.random();
+
+        assert!(matches!(
+            signer.sign(1, &message),
+            Err(StatefulSigningError::Storage(_))
+        ));
+        assert_eq!(signer.last_signed_epoch(), None);
+    }
+
+    #[test]
+    fn test_file_watermark_survives_restart() {
+        let path = temporary_watermark_path();
+        let mut rng = rand::rng();
+        let (pk, sk) = Sig::key_gen(&mut rng, 0, 1 << LOG_LIFETIME);
+        let message = rng.random();
+
+        // nothing has been signed so far
+        let storage = FileWatermarkStorage::new(&path);
+        assert_eq!(storage.load().unwrap(), None);
+
+        // sign once and drop the signer, which simulates a restart
+        let mut signer = StatefulSigner::<Sig>::new(sk, storage).unwrap();
+        let signature = signer.sign(5, &message).unwrap();
+        assert!(Sig::verify(&pk, 5, &message, &signature));
+        let (sk, _) = signer.into_parts();
+
+        // the new signer picks up the watermark from the file
+        let mut signer = StatefulSigner::<Sig>::new(sk, FileWatermarkStorage::new(&path)).unwrap();
+        assert_eq!(signer.last_signed_epoch(), Some(5));
+        assert!(matches!(
+            signer.sign(5, &message),
+            Err(StatefulSigningError::EpochNotAboveWatermark { .. })
+        ));
+        signer.sign(6, &message).unwrap();
+        assert_eq!(FileWatermarkStorage::new(&path).load().unwrap(), Some(6));
+
+        // the temporary file is gone after the rename
+        assert!(!signer.storage.temporary_path().exists());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_watermark_rejects_corrupted_file() {
+        let path = temporary_watermark_path();
+        fs::write(&path, [0x01, 0x02, 0x03]).unwrap();
+
+        let storage = FileWatermarkStorage::new(&path);
+        let err = storage.load().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use rand::Rng;
 use serde::{Serialize, de::DeserializeOwned};
 
@@ -30,11 +31,17 @@ pub trait MessageHash {
     /// 0 and BASE - 1 (inclusive).
     fn apply(
         parameter: &Self::Parameter,
-        epoch: u32,
+        epoch: u64,
         randomness: &Self::Randomness,
         message: &[u8; MESSAGE_LENGTH],
     ) -> Vec<u8>;
 
+    /// Appends bytes that identify this message hash and its parameters to `out`.
+    /// Distinct instantiations append distinct bytes, which are never a prefix of one
+    /// another (see `write_identifier`). They identify instantiations of signature
+    /// schemes, e.g., in the header of encoded keys and signatures.
+    fn write_identifier(out: &mut Vec<u8>);
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use num_bigint::BigUint;
 use p3_field::PrimeCharacteristicRing;
 use p3_field::PrimeField;
@@ -10,6 +11,10 @@ use crate::MESSAGE_LENGTH;
 use crate::TWEAK_SEPARATOR_FOR_MESSAGE_HASH;
 use crate::poseidon2_24;
 use crate::symmetric::tweak_hash::poseidon::poseidon_compress;
+use crate::write_identifier;
+
+/// Kind of this message hash in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x02;
 
 /// Function to encode a message as an array of field elements
 pub fn encode_message<const MSG_LEN_FE: usize>(message: &[u8; MESSAGE_LENGTH]) -> [F; MSG_LEN_FE] {
@@ -17,7 +22,7 @@ pub fn encode_message<const MSG_LEN_FE: usize>(message: &[u8; MESSAGE_LENGTH]) -
     let mut acc = BigUint::from_bytes_le(message);
 
     // Perform base-p decomposition
-    std::array::from_fn(|_| {
+    core::array::from_fn(|_| {
         let digit = &acc % F::ORDER_U64;
         acc /= F::ORDER_U64;
         F::from_u64(digit.try_into().unwrap())
@@ -26,45 +31,27 @@ pub fn encode_message<const MSG_LEN_FE: usize>(message: &[u8; MESSAGE_LENGTH]) -
 
 /// Encodes an epoch and a domain separator into an array of field elements.
 ///
-/// This function combines the `u32` epoch and a constant 8-bit separator into a single
-/// `u64` value. It then decomposes this value into its base-`p` representation,
-/// where `p` is the field's order, to produce the output array.
-///
-/// ### Warning: Implementation Assumptions
-///
-/// This implementation is highly optimized and relies on two key assumptions about the field `F`:
-///
-/// 1.  **`u64`-Based Modulus:** It assumes the field's modulus fits within a `u64`. It is **not**
-///     suitable for fields with larger moduli that require `BigUint` arithmetic.
+/// This function combines the `u64` epoch and a constant 8-bit separator into a single
+/// integer. It then decomposes this integer into its base-`p` representation,
+/// where `p` is the field's order, to produce the output array. For epochs below 2^32,
+/// the result is the same as when epochs were 32 bits wide.
 ///
-/// 2.  **Sufficient Bit-Size:** The fast, two-step decomposition assumes the field is large
-///     enough to hold a 40-bit value in at most two "digits". This requires the field's
-///     prime to be **at least 20 bits wide**.
-pub fn encode_epoch<const TWEAK_LEN_FE: usize>(epoch: u32) -> [F; TWEAK_LEN_FE] {
-    // Combine epoch and domain separator into a single u64.
-    let acc = ((epoch as u64) << 8) | (TWEAK_SEPARATOR_FOR_MESSAGE_HASH as u64);
-
-    // Decompose the combined u64 value into field elements using base-p representation.
-    //
-    // This direct, two-step decomposition is an optimization that is only valid if
-    // the field is large enough to represent a 40-bit number in at most two "digits".
-    //
-    // The condition is: ceil(40 / log2(p)) <= 2, which implies log2(p) >= 20.
-    // This holds for 31 bit fields, but would fail for very small fields.
-    //
-    // We assume this function is only used with fields that satisfy this constraint.
-    let mut result = [F::ZERO; TWEAK_LEN_FE];
-
-    // The first "digit" of the base conversion.
-    if TWEAK_LEN_FE > 0 {
-        result[0] = F::from_u64(acc % F::ORDER_U64);
-    }
-    // The second "digit" of the base conversion.
-    if TWEAK_LEN_FE > 1 {
-        result[1] = F::from_u64(acc / F::ORDER_U64);
-    }
-
-    // Any subsequent elements (if TWEAK_LEN_FE > 2) remain zero.
+/// Panics if the integer does not fit into `TWEAK_LEN_FE` field elements. The consistency
+/// check of the message hash ensures that this does not happen for 32-bit epochs.
+pub fn encode_epoch<const TWEAK_LEN_FE: usize>(epoch: u64) -> [F; TWEAK_LEN_FE] {
+    // Combine epoch and domain separator into a single integer.
+    let mut acc = ((epoch as u128) << 8) | (TWEAK_SEPARATOR_FOR_MESSAGE_HASH as u128);
+
+    // Decompose the combined value into field elements using base-p representation.
+    let result = core::array::from_fn(|_| {
+        let digit = (acc % F::ORDER_U64 as u128) as u64;
+        acc /= F::ORDER_U64 as u128;
+        F::from_u64(digit)
+    });
+    assert!(
+        acc == 0,
+        "Poseidon Message Hash: not enough field elements to encode the epoch"
+    );
     result
 }
 
@@ -82,7 +69,7 @@ fn decode_to_chunks<const DIMENSION: usize, const BASE: usize, const HASH_LEN_FE
     }
 
     // Convert to base-BASE
-    std::array::from_fn(|_| {
+    core::array::from_fn(|_| {
         let chunk = (&acc % BASE).try_into().unwrap();
         acc /= BASE;
         chunk
@@ -144,7 +131,7 @@ where
 
     fn apply(
         parameter: &Self::Parameter,
-        epoch: u32,
+        epoch: u64,
         randomness: &Self::Randomness,
         message: &[u8; MESSAGE_LENGTH],
     ) -> Vec<u8> {
@@ -164,12 +151,28 @@ where
             .copied()
             .collect();
 
-        let hash_fe = poseidon_compress::<_, 24, HASH_LEN_FE>(&perm, &combined_input_vec);
+        let hash_fe = poseidon_compress::<_, 24, HASH_LEN_FE>(perm, &combined_input_vec);
 
         // decode field elements into chunks and return them
         decode_to_chunks::<DIMENSION, BASE, HASH_LEN_FE>(&hash_fe).to_vec()
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(
+            out,
+            IDENTIFIER_KIND,
+            &[
+                PARAMETER_LEN,
+                RAND_LEN_FE,
+                HASH_LEN_FE,
+                DIMENSION,
+                BASE,
+                TWEAK_LEN_FE,
+                MSG_LEN_FE,
+            ],
+        );
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         // Check that Poseidon of width 24 is enough
@@ -202,8 +205,9 @@ where
             "Poseidon Message Hash: Parameter mismatch: not enough field elements to encode the message"
         );
 
-        // Check that we have enough bits to encode tweak
-        // Epoch is a u32, and we have one domain separator byte
+        // Check that we have enough bits to encode tweak for 32-bit epochs
+        // (plus one domain separator byte). Larger epochs need more field
+        // elements, which is checked when encoding the epoch.
         let tweak_fe_bits = bits_per_fe * f64::from(TWEAK_LEN_FE as u32);
         assert!(
             tweak_fe_bits >= f64::from(32 + 8_u32),
@@ -291,7 +295,7 @@ mod tests {
 
     #[test]
     fn test_encode_epoch_small_value() {
-        let epoch = 42u32;
+        let epoch = 42u64;
         let sep = TWEAK_SEPARATOR_FOR_MESSAGE_HASH;
 
         // Compute: (epoch << 8) + sep
@@ -314,7 +318,7 @@ mod tests {
 
     #[test]
     fn test_encode_epoch_zero() {
-        let epoch = 0u32;
+        let epoch = 0u64;
         let sep = TWEAK_SEPARATOR_FOR_MESSAGE_HASH;
 
         let epoch_bigint = BigUint::from(sep);
@@ -333,7 +337,7 @@ mod tests {
 
     #[test]
     fn test_encode_epoch_max_value() {
-        let epoch = u32::MAX;
+        let epoch = u64::from(u32::MAX);
         let sep = TWEAK_SEPARATOR_FOR_MESSAGE_HASH;
 
         let epoch_bigint: BigUint = (BigUint::from(epoch) << 8) + sep;
@@ -350,6 +354,27 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_encode_epoch_wide_value() {
+        let epoch = u64::MAX;
+        let sep = TWEAK_SEPARATOR_FOR_MESSAGE_HASH;
+
+        let epoch_bigint: BigUint = (BigUint::from(epoch) << 8) + sep;
+        let p = BigUint::from(F::ORDER_U64);
+
+        let expected = [
+            F::from_u128((&epoch_bigint % &p).try_into().unwrap()),
+            F::from_u128(((&epoch_bigint / &p) % &p).try_into().unwrap()),
+            F::from_u128(((&epoch_bigint / (&p * &p)) % &p).try_into().unwrap()),
+        ];
+
+        let result = encode_epoch::<3>(epoch);
+        assert_eq!(result, expected);
+
+        // two field elements are not enough for such an epoch
+        assert!(std::panic::catch_unwind(|| encode_epoch::<2>(1 << 60)).is_err());
+    }
+
     #[test]
     fn test_encode_epoch_injective() {
         // encoding an epoch must be injective
@@ -360,7 +385,7 @@ mod tests {
         let mut rng = rand::rng();
 
         for _ in 0..10_000 {
-            let epoch: u32 = rng.random();
+            let epoch: u64 = rng.random();
             let encoding = encode_epoch::<4>(epoch);
             if let Some(prev_epoch) = map.insert(encoding, epoch) {
                 assert_eq!(
@@ -1,12 +1,17 @@
 use crate::{
-    MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH, symmetric::message_hash::bytes_to_chunks,
+    MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH, epoch_encoding_len,
+    symmetric::message_hash::bytes_to_chunks, tweak_separator, write_identifier,
 };
+use alloc::vec::Vec;
 use serde::{Serialize, de::DeserializeOwned};
 
 use super::MessageHash;
 
 use sha3::{Digest, Sha3_256};
 
+/// Kind of this message hash in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x01;
+
 /// A message hash implemented using SHA3
 /// All lengths must be given in Bytes.
 /// All lengths must be less than 255 bits.
@@ -43,7 +48,7 @@ where
 
     fn apply(
         parameter: &Self::Parameter,
-        epoch: u32,
+        epoch: u64,
         randomness: &Self::Randomness,
         message: &[u8; MESSAGE_LENGTH],
     ) -> Vec<u8> {
@@ -57,8 +62,8 @@ where
 
         // now add tweak (= domain separator + epoch)
         // domain separator: this is a message hash tweak.
-        hasher.update([TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
-        hasher.update(epoch.to_le_bytes());
+        hasher.update([tweak_separator(TWEAK_SEPARATOR_FOR_MESSAGE_HASH, epoch)]);
+        hasher.update(&epoch.to_le_bytes()[..epoch_encoding_len(epoch)]);
 
         // now add the actual message to be hashed
         hasher.update(message);
@@ -69,6 +74,14 @@ where
         bytes_to_chunks(&hash[0..NUM_CHUNKS * CHUNK_SIZE / 8], CHUNK_SIZE)
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(
+            out,
+            IDENTIFIER_KIND,
+            &[PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE],
+        );
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         assert!(
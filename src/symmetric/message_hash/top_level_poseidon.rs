@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use num_bigint::BigUint;
 use p3_field::PrimeCharacteristicRing;
 use p3_field::PrimeField;
@@ -14,6 +15,10 @@ use crate::hypercube::hypercube_part_size;
 use crate::hypercube::map_to_vertex;
 use crate::poseidon2_24;
 use crate::symmetric::tweak_hash::poseidon::poseidon_compress;
+use crate::write_identifier;
+
+/// Kind of this message hash in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x03;
 
 /// Function to make a list of field elements to a vertex in layers 0, ..., FINAL_LAYER
 /// of the hypercube {0,...,BASE-1}^DIMENSION.
@@ -132,7 +137,7 @@ where
 
     fn apply(
         parameter: &Self::Parameter,
-        epoch: u32,
+        epoch: u64,
         randomness: &Self::Randomness,
         message: &[u8; MESSAGE_LENGTH],
     ) -> Vec<u8> {
@@ -159,7 +164,7 @@ where
                 .collect();
 
             let iteration_pos_output =
-                poseidon_compress::<_, 24, POS_OUTPUT_LEN_PER_INV_FE>(&perm, &combined_input);
+                poseidon_compress::<_, 24, POS_OUTPUT_LEN_PER_INV_FE>(perm, &combined_input);
 
             pos_outputs[i * POS_OUTPUT_LEN_PER_INV_FE..(i + 1) * POS_OUTPUT_LEN_PER_INV_FE]
                 .copy_from_slice(&iteration_pos_output);
@@ -170,6 +175,25 @@ where
         map_into_hypercube_part::<DIMENSION, BASE, FINAL_LAYER, POS_OUTPUT_LEN_FE>(&pos_outputs)
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(
+            out,
+            IDENTIFIER_KIND,
+            &[
+                POS_OUTPUT_LEN_PER_INV_FE,
+                POS_INVOCATIONS,
+                POS_OUTPUT_LEN_FE,
+                DIMENSION,
+                BASE,
+                FINAL_LAYER,
+                TWEAK_LEN_FE,
+                MSG_LEN_FE,
+                PARAMETER_LEN,
+                RAND_LEN,
+            ],
+        );
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         /// The width of the Poseidon2 permutation used.
@@ -227,7 +251,8 @@ where
         );
 
         // Check that we have enough bits to encode tweak
-        // Epoch is a u32, and we have one domain separator byte
+        // Tweaks for 32-bit epochs must fit, with one domain separator byte.
+        // Larger epochs need more field elements, which is checked when encoding.
         let tweak_fe_bits = bits_per_fe * f64::from(TWEAK_LEN_FE as u32);
         assert!(
             tweak_fe_bits >= f64::from(32 + 8_u32),
@@ -283,7 +308,7 @@ mod tests {
     proptest! {
         #[test]
         fn proptest_apply(
-            epoch in 0u32..1000,
+            epoch in 0u64..1000,
             message in any::<[u8; MESSAGE_LENGTH]>(),
         ) {
             const BASE: usize = 12;
@@ -1,11 +1,13 @@
 use rand::Rng;
 use serde::{Serialize, de::DeserializeOwned};
+use zeroize::ZeroizeOnDrop;
 
 use crate::MESSAGE_LENGTH;
 
 /// Trait to model a pseudorandom function (PRF)
 pub trait Pseudorandom {
-    type Key: Send + Sync + Serialize + DeserializeOwned;
+    /// Key of the PRF. It is wiped from memory when dropped.
+    type Key: Clone + Send + Sync + Serialize + DeserializeOwned + ZeroizeOnDrop;
     type Domain;
     type Randomness;
 
@@ -14,13 +16,13 @@ pub trait Pseudorandom {
 
     /// Apply the PRF to an epoch and an index to get a pseudorandom domain element.
     /// This can be used to create the chain starts pseudorandomly.
-    fn get_domain_element(key: &Self::Key, epoch: u32, index: u64) -> Self::Domain;
+    fn get_domain_element(key: &Self::Key, epoch: u64, index: u64) -> Self::Domain;
 
     /// Apply the PRF to an epoch, a message, and a counter to get a pseudorandom randomness.
     /// This can be used to produce a stream of (pseudo-)randomness for the encoding.
     fn get_randomness(
         key: &Self::Key,
-        epoch: u32,
+        epoch: u64,
         message: &[u8; MESSAGE_LENGTH],
         counter: u64,
     ) -> Self::Randomness;
@@ -1,6 +1,8 @@
 use super::Pseudorandom;
+use crate::epoch_encoding_len;
 use serde::{Serialize, de::DeserializeOwned};
 use sha3::{Digest, Sha3_256};
+use zeroize::Zeroizing;
 
 const KEY_LENGTH: usize = 32; // 32 bytes
 const PRF_DOMAIN_SEP: [u8; 16] = [
@@ -20,15 +22,15 @@ impl<const DOMAIN_LENGTH: usize, const RAND_LENGTH: usize> Pseudorandom
 where
     [u8; DOMAIN_LENGTH]: Serialize + DeserializeOwned,
 {
-    type Key = [u8; KEY_LENGTH];
+    type Key = Zeroizing<[u8; KEY_LENGTH]>;
     type Domain = [u8; DOMAIN_LENGTH];
     type Randomness = [u8; RAND_LENGTH];
 
     fn key_gen<R: rand::Rng>(rng: &mut R) -> Self::Key {
-        rng.random()
+        Zeroizing::new(rng.random())
     }
 
-    fn get_domain_element(key: &Self::Key, epoch: u32, index: u64) -> Self::Domain {
+    fn get_domain_element(key: &Self::Key, epoch: u64, index: u64) -> Self::Domain {
         let mut hasher = Sha3_256::new();
 
         // Hash the domain separator
@@ -41,8 +43,8 @@ where
         // Hash the key
         hasher.update(key);
 
-        // Hash the epoch
-        hasher.update(epoch.to_be_bytes());
+        // Hash the epoch (with four bytes if it fits, see `epoch_encoding_len`)
+        hasher.update(&epoch.to_be_bytes()[8 - epoch_encoding_len(epoch)..]);
 
         // Hash the index
         hasher.update(index.to_be_bytes());
@@ -54,7 +56,7 @@ where
 
     fn get_randomness(
         key: &Self::Key,
-        epoch: u32,
+        epoch: u64,
         message: &[u8; crate::MESSAGE_LENGTH],
         counter: u64,
     ) -> Self::Randomness {
@@ -70,8 +72,8 @@ where
         // Hash the key
         hasher.update(key);
 
-        // Hash the epoch
-        hasher.update(epoch.to_be_bytes());
+        // Hash the epoch (with four bytes if it fits, see `epoch_encoding_len`)
+        hasher.update(&epoch.to_be_bytes()[8 - epoch_encoding_len(epoch)..]);
 
         // Hash the message
         hasher.update(message);
@@ -126,4 +128,39 @@ mod tests {
             K
         );
     }
+
+    #[test]
+    fn test_sha_prf_wide_epochs() {
+        type PRF = ShaPRF<24, 24>;
+        let key = PRF::key_gen(&mut rand::rng());
+        let message = [0x42; crate::MESSAGE_LENGTH];
+
+        // epochs below 2^32 are hashed with four bytes, as before epochs were 64 bits wide
+        let mut hasher = Sha3_256::new();
+        hasher.update(PRF_DOMAIN_SEP);
+        hasher.update(PRF_DOMAIN_SEP_DOMAIN_ELEMENT);
+        hasher.update(&key);
+        hasher.update(7u32.to_be_bytes());
+        hasher.update(3u64.to_be_bytes());
+        assert_eq!(
+            PRF::get_domain_element(&key, 7, 3)[..],
+            hasher.finalize()[..24]
+        );
+
+        // larger epochs give different outputs, also if they agree in the lower 32 bits
+        for epoch in [u32::MAX as u64, 1 << 32, (1 << 40) + 7, u64::MAX] {
+            assert_ne!(
+                PRF::get_domain_element(&key, epoch, 3),
+                PRF::get_domain_element(&key, 7, 3)
+            );
+            assert_ne!(
+                PRF::get_randomness(&key, epoch, &message, 3),
+                PRF::get_randomness(&key, 7, &message, 3)
+            );
+        }
+        assert_ne!(
+            PRF::get_domain_element(&key, 1 << 32, 3),
+            PRF::get_domain_element(&key, 0, 3)
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use crate::F;
+use crate::{F, epoch_encoding_len};
 
 use super::Pseudorandom;
 use p3_field::PrimeCharacteristicRing;
@@ -7,6 +7,7 @@ use sha3::{
     Shake128,
     digest::{ExtendableOutput, Update, XofReader},
 };
+use zeroize::Zeroizing;
 
 // Number of pseudorandom bytes to generate one pseudorandom field element
 const PRF_BYTES_PER_FE: usize = 16;
@@ -28,15 +29,15 @@ impl<const DOMAIN_LENGTH_FE: usize, const RAND_LENGTH_FE: usize> Pseudorandom
 where
     [F; DOMAIN_LENGTH_FE]: Serialize + DeserializeOwned,
 {
-    type Key = [u8; KEY_LENGTH];
+    type Key = Zeroizing<[u8; KEY_LENGTH]>;
     type Domain = [F; DOMAIN_LENGTH_FE];
     type Randomness = [F; RAND_LENGTH_FE];
 
     fn key_gen<R: rand::Rng>(rng: &mut R) -> Self::Key {
-        rng.random()
+        Zeroizing::new(rng.random())
     }
 
-    fn get_domain_element(key: &Self::Key, epoch: u32, index: u64) -> Self::Domain {
+    fn get_domain_element(key: &Self::Key, epoch: u64, index: u64) -> Self::Domain {
         // Create a new SHAKE128 instance
         let mut hasher = Shake128::default();
 
@@ -48,10 +49,10 @@ where
         hasher.update(&PRF_DOMAIN_SEP_DOMAIN_ELEMENT);
 
         // Hash the key
-        hasher.update(key);
+        hasher.update(key.as_slice());
 
-        // Hash the epoch
-        hasher.update(&epoch.to_be_bytes());
+        // Hash the epoch (with four bytes if it fits, see `epoch_encoding_len`)
+        hasher.update(&epoch.to_be_bytes()[8 - epoch_encoding_len(epoch)..]);
 
         // Hash the index
         hasher.update(&index.to_be_bytes());
@@ -60,7 +61,7 @@ where
         let mut xof_reader = hasher.finalize_xof();
 
         // Mapping bytes to field elements
-        std::array::from_fn(|_| {
+        core::array::from_fn(|_| {
             // Buffer to store the output
             let mut buf = [0u8; PRF_BYTES_PER_FE];
 
@@ -74,7 +75,7 @@ where
 
     fn get_randomness(
         key: &Self::Key,
-        epoch: u32,
+        epoch: u64,
         message: &[u8; crate::MESSAGE_LENGTH],
         counter: u64,
     ) -> Self::Randomness {
@@ -89,10 +90,10 @@ where
         hasher.update(&PRF_DOMAIN_SEP_RANDOMNESS);
 
         // Hash the key
-        hasher.update(key);
+        hasher.update(key.as_slice());
 
-        // Hash the epoch
-        hasher.update(&epoch.to_be_bytes());
+        // Hash the epoch (with four bytes if it fits, see `epoch_encoding_len`)
+        hasher.update(&epoch.to_be_bytes()[8 - epoch_encoding_len(epoch)..]);
 
         // Hash the message
         hasher.update(message);
@@ -104,7 +105,7 @@ where
         let mut xof_reader = hasher.finalize_xof();
 
         // Mapping bytes to field elements
-        std::array::from_fn(|_| {
+        core::array::from_fn(|_| {
             // Buffer to store the output
             let mut buf = [0u8; PRF_BYTES_PER_FE];
 
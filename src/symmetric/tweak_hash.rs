@@ -1,6 +1,11 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use p3_field::PrimeCharacteristicRing;
 use rand::Rng;
 use serde::{Serialize, de::DeserializeOwned};
 
+use crate::{F, MESSAGE_LENGTH};
+
 /// Trait to model a tweakable hash function.
 /// Such a function takes a public parameter, a tweak, and a
 /// message to be hashed. The tweak should be understood as an
@@ -16,7 +21,14 @@ use serde::{Serialize, de::DeserializeOwned};
 pub trait TweakableHash {
     type Parameter: Copy + Sized + Send + Sync + Serialize + DeserializeOwned;
     type Tweak;
-    type Domain: Copy + PartialEq + Sized + Send + Sync + Serialize + DeserializeOwned;
+    type Domain: Copy
+        + PartialEq
+        + ConditionalSelect
+        + Sized
+        + Send
+        + Sync
+        + Serialize
+        + DeserializeOwned;
 
     /// Generates a random public parameter.
     fn rand_parameter<R: Rng>(rng: &mut R) -> Self::Parameter;
@@ -26,11 +38,11 @@ pub trait TweakableHash {
 
     /// Returns a tweak to be used in the Merkle tree.
     /// Note: this is assumed to be distinct from the outputs of chain_tweak
-    fn tree_tweak(level: u8, pos_in_level: u32) -> Self::Tweak;
+    fn tree_tweak(level: u8, pos_in_level: u64) -> Self::Tweak;
 
     /// Returns a tweak to be used in chains.
     /// Note: this is assumed to be distinct from the outputs of tree_tweak
-    fn chain_tweak(epoch: u32, chain_index: u8, pos_in_chain: u8) -> Self::Tweak;
+    fn chain_tweak(epoch: u64, chain_index: u8, pos_in_chain: u8) -> Self::Tweak;
 
     /// Applies the tweakable hash to parameter, tweak, and message.
     fn apply(
@@ -39,12 +51,30 @@ pub trait TweakableHash {
         message: &[Self::Domain],
     ) -> Self::Domain;
 
+    /// Appends bytes that identify this tweakable hash and its parameters to `out`.
+    /// Distinct instantiations append distinct bytes, which are never a prefix of one
+    /// another (see `write_identifier`). They identify instantiations of signature
+    /// schemes, e.g., in the header of encoded keys and signatures.
+    fn write_identifier(out: &mut Vec<u8>);
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
     fn internal_consistency_check();
 }
 
+/// Trait to hash messages of arbitrary length into messages of length `MESSAGE_LENGTH`,
+/// which can then be signed. The hash also takes a context string, so that signatures
+/// produced for one protocol (or purpose) cannot be used in another one.
+///
+/// This is implemented by tweakable hashes, so that messages are pre-hashed using
+/// the same primitive that is used by the signature scheme.
+pub trait MessagePreHash {
+    /// Hashes the context string and the message into a message of fixed length.
+    /// Distinct pairs of context and message are hashed independently.
+    fn pre_hash(context: &[u8], message: &[u8]) -> [u8; MESSAGE_LENGTH];
+}
+
 /// Function implementing hash chains, implemented over a tweakable hash function
 /// The chain is specific to an epoch `epoch`, and an index `chain_index`. All
 /// evaluations of the tweakable hash function use the given parameter `parameter`
@@ -54,23 +84,91 @@ pub trait TweakableHash {
 /// with `start = A` would mean we walk A -> B -> C, and then return C.
 pub fn chain<TH: TweakableHash>(
     parameter: &TH::Parameter,
-    epoch: u32,
+    epoch: u64,
     chain_index: u8,
     start_pos_in_chain: u8,
     steps: usize,
     start: &TH::Domain,
 ) -> TH::Domain {
-    // keep track of what we have
-    let mut current = *start;
+    // the tweak of each step is determined by the position we walk to
+    let tweaks = (0..steps)
+        .map(|j| TH::chain_tweak(epoch, chain_index, start_pos_in_chain + (j as u8) + 1u8));
+    chain_with_tweaks::<TH, _>(parameter, tweaks, start)
+}
+
+/// Same as `chain`, but the tweaks are given by the caller, e.g., if they have been
+/// precomputed. We start walking the chain with `start`, and walk one step per tweak.
+pub fn chain_with_tweaks<TH: TweakableHash, T: Borrow<TH::Tweak>>(
+    parameter: &TH::Parameter,
+    tweaks: impl IntoIterator<Item = T>,
+    start: &TH::Domain,
+) -> TH::Domain {
+    tweaks.into_iter().fold(*start, |current, tweak| {
+        TH::apply(parameter, tweak.borrow(), &[current])
+    })
+}
+
+/// Selection between two values that does not branch on the choice, and whose
+/// memory accesses do not depend on it. Used to avoid timing side channels.
+pub trait ConditionalSelect: Sized {
+    /// Returns `a` if `choice` is 0, and `b` if `choice` is 1.
+    /// The choice must be 0 or 1.
+    fn conditional_select(a: &Self, b: &Self, choice: u8) -> Self;
+}
+
+impl<const N: usize> ConditionalSelect for [u8; N] {
+    fn conditional_select(a: &Self, b: &Self, choice: u8) -> Self {
+        // the mask is all ones if choice is 1, and all zeros otherwise
+        let mask = 0u8.wrapping_sub(choice);
+        core::array::from_fn(|i| a[i] ^ (mask & (a[i] ^ b[i])))
+    }
+}
+
+impl<const N: usize> ConditionalSelect for [F; N] {
+    fn conditional_select(a: &Self, b: &Self, choice: u8) -> Self {
+        // arithmetic in the field does not branch on the values
+        let choice = F::from_u8(choice);
+        core::array::from_fn(|i| a[i] + (b[i] - a[i]) * choice)
+    }
+}
+
+/// Returns 1 if `a == b` and 0 otherwise, without branching.
+const fn constant_time_eq(a: usize, b: usize) -> u8 {
+    let diff = (a ^ b) as u64;
+    // the top bit of `diff | -diff` is set if and only if `diff` is non-zero
+    let non_zero = (diff | diff.wrapping_neg()) >> 63;
+    (1 - non_zero) as u8
+}
+
+/// Same as `chain`, but the running time does not depend on `steps`: we always walk
+/// `max_steps` many steps, and keep the element after `steps` many steps using a
+/// conditional select. Requires that `steps <= max_steps`.
+pub fn chain_constant_time<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    epoch: u64,
+    chain_index: u8,
+    start_pos_in_chain: u8,
+    steps: usize,
+    max_steps: usize,
+    start: &TH::Domain,
+) -> TH::Domain {
+    assert!(
+        steps <= max_steps,
+        "Chain: number of steps exceeds the maximum number of steps"
+    );
 
-    // otherwise, walk the right amount of steps
-    for j in 0..steps {
+    let mut current = *start;
+    let mut result = *start;
+    for j in 0..max_steps {
         let tweak = TH::chain_tweak(epoch, chain_index, start_pos_in_chain + (j as u8) + 1u8);
         current = TH::apply(parameter, &tweak, &[current]);
+
+        // the black box prevents the compiler from turning the selection into a branch
+        let choice = core::hint::black_box(constant_time_eq(j + 1, steps));
+        result = TH::Domain::conditional_select(&result, &current, choice);
     }
 
-    // return where we are now
-    current
+    result
 }
 
 pub mod poseidon;
@@ -81,6 +179,7 @@ mod tests {
     use sha::ShaTweak128192;
 
     use super::*;
+    use p3_field::PrimeField32;
     use proptest::prelude::*;
 
     type TestTH = ShaTweak128192;
@@ -157,11 +256,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chain_constant_time() {
+        let mut rng = rand::rng();
+
+        let epoch = 3;
+        let chain_index = 5;
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let start = TestTH::rand_domain(&mut rng);
+        let max_steps = 15;
+
+        for start_pos_in_chain in [0, 4] {
+            for steps in 0..=max_steps {
+                let expected = chain::<TestTH>(
+                    &parameter,
+                    epoch,
+                    chain_index,
+                    start_pos_in_chain,
+                    steps,
+                    &start,
+                );
+                let actual = chain_constant_time::<TestTH>(
+                    &parameter,
+                    epoch,
+                    chain_index,
+                    start_pos_in_chain,
+                    steps,
+                    max_steps,
+                    &start,
+                );
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn test_conditional_select() {
+        let a = [1u8, 2, 3, 255];
+        let b = [0u8, 7, 3, 128];
+        assert_eq!(<[u8; 4]>::conditional_select(&a, &b, 0), a);
+        assert_eq!(<[u8; 4]>::conditional_select(&a, &b, 1), b);
+
+        let a = [F::from_u32(1), F::from_u32(F::ORDER_U32 - 1), F::ZERO];
+        let b = [F::from_u32(17), F::ZERO, F::from_u32(42)];
+        assert_eq!(<[F; 3]>::conditional_select(&a, &b, 0), a);
+        assert_eq!(<[F; 3]>::conditional_select(&a, &b, 1), b);
+
+        assert_eq!(constant_time_eq(0, 0), 1);
+        assert_eq!(constant_time_eq(5, 5), 1);
+        assert_eq!(constant_time_eq(5, 6), 0);
+        assert_eq!(constant_time_eq(0, usize::MAX), 0);
+        assert_eq!(constant_time_eq(1 << 63, 0), 0);
+    }
+
     proptest! {
         #[test]
         fn proptest_chain_associative(
             // Random epoch for domain separation (small range to keep tests fast)
-            epoch in 0u32..100,
+            epoch in 0u64..100,
 
             // Random chain index to simulate different chains (small range to keep tests fast)
             chain_index in 0u8..10,
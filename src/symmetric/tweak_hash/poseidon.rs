@@ -1,45 +1,89 @@
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "trace")]
+use core::cell::RefCell;
+#[cfg(feature = "trace")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use num_bigint::BigUint;
 use p3_field::PrimeCharacteristicRing;
+use p3_field::PrimeField;
 use p3_field::PrimeField64;
 use p3_symmetric::Permutation;
 use serde::{Serialize, de::DeserializeOwned};
 
 use crate::F;
+use crate::MESSAGE_LENGTH;
 use crate::TWEAK_SEPARATOR_FOR_CHAIN_HASH;
 use crate::TWEAK_SEPARATOR_FOR_TREE_HASH;
 use crate::poseidon2_16;
 use crate::poseidon2_24;
+use crate::write_identifier;
 
+use super::MessagePreHash;
 use super::TweakableHash;
 
-const DOMAIN_PARAMETERS_LENGTH: usize = 4;
+/// Kind of this tweakable hash in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x02;
+
+pub const DOMAIN_PARAMETERS_LENGTH: usize = 4;
 /// The state width for compressing a single hash in a chain.
-const CHAIN_COMPRESSION_WIDTH: usize = 16;
+pub const CHAIN_COMPRESSION_WIDTH: usize = 16;
 /// The state width for merging two hashes in a tree or for the sponge construction.
-const MERGE_COMPRESSION_WIDTH: usize = 24;
+pub const MERGE_COMPRESSION_WIDTH: usize = 24;
+
+/// Domain separator for pre-hashing messages. It is placed in the capacity together
+/// with the parameters of the encoding, see `poseidon_pre_hash`.
+const PRE_HASH_DOMAIN_SEP: u32 = 0xffff_ff02;
+
+const PRE_HASH_CAPACITY: usize = 9;
+
+/// Number of field elements output by the sponge when pre-hashing. They contain
+/// more than 8 * MESSAGE_LENGTH bits, and are then mapped to MESSAGE_LENGTH bytes.
+const PRE_HASH_OUTPUT_LEN: usize = 9;
+
+/// Number of bytes packed into one field element when pre-hashing.
+/// Three bytes are always smaller than the field modulus.
+const PRE_HASH_BYTES_PER_FE: usize = 3;
+
+/// Number of bytes of the lengths of context and message when pre-hashing.
+/// Lengths are encoded as `u64`, so that inputs of any length can be pre-hashed.
+const PRE_HASH_LENGTH_BYTES: usize = 8;
 
 /// Enum to implement tweaks.
 pub enum PoseidonTweak {
     TreeTweak {
         level: u8,
-        pos_in_level: u32,
+        pos_in_level: u64,
     },
     ChainTweak {
-        epoch: u32,
+        epoch: u64,
         chain_index: u8,
         pos_in_chain: u8,
     },
 }
 
 impl PoseidonTweak {
-    fn to_field_elements<const TWEAK_LEN: usize>(&self) -> [F; TWEAK_LEN] {
+    /// Encodes the tweak as `TWEAK_LEN` field elements, i.e., as the
+    /// digits of an integer in base p.
+    ///
+    /// For positions and epochs below 2^32, this integer is the same as when they were
+    /// 32 bits wide. For tree tweaks, the upper 32 bits of the position are placed above
+    /// the level for this reason.
+    ///
+    /// Panics if the integer does not fit into `TWEAK_LEN` field elements. This can only
+    /// happen for large epochs or positions: the `internal_consistency_check` of the tweak
+    /// hash ensures that tweaks for 32-bit epochs and positions fit, and the one of the
+    /// signature scheme ensures that the tweaks for all epochs of its lifetime fit.
+    pub fn to_field_elements<const TWEAK_LEN: usize>(&self) -> [F; TWEAK_LEN] {
         // We first represent the entire tweak as one big integer
         let mut acc = match self {
             Self::TreeTweak {
                 level,
                 pos_in_level,
             } => {
-                ((*level as u128) << 40)
-                    | ((*pos_in_level as u128) << 8)
+                ((*pos_in_level as u128 >> 32) << 48)
+                    | ((*level as u128) << 40)
+                    | ((*pos_in_level as u128 & 0xffff_ffff) << 8)
                     | (TWEAK_SEPARATOR_FOR_TREE_HASH as u128)
             }
             Self::ChainTweak {
@@ -55,11 +99,157 @@ impl PoseidonTweak {
         };
 
         // Now we interpret this integer in base-p to get field elements
-        std::array::from_fn(|_| {
+        let elements = core::array::from_fn(|_| {
             let digit = (acc % F::ORDER_U64 as u128) as u64;
             acc /= F::ORDER_U64 as u128;
             F::from_u64(digit)
-        })
+        });
+        assert!(
+            acc == 0,
+            "Poseidon Tweak: not enough field elements to encode the tweak"
+        );
+        elements
+    }
+}
+
+/// Input and output state of one application of a Poseidon2 permutation.
+/// The width of the permutation (16 or 24) is the length of the states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermutationTrace {
+    pub input: Vec<F>,
+    pub output: Vec<F>,
+}
+
+// only needed for traces of verification
+#[cfg(feature = "trace")]
+impl PermutationTrace {
+    /// Returns the width of the permutation.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.input.len()
+    }
+}
+
+/// Trace of a call to `poseidon_compress` or to the Poseidon sponge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoseidonCall {
+    /// Call to `poseidon_compress`. The input of the permutation is the zero-padded
+    /// input, and the output is the truncation of the permutation output plus its input.
+    Compress {
+        permutation: PermutationTrace,
+        output: Vec<F>,
+    },
+    /// Call to the Poseidon sponge. The input is zero-padded to a multiple of the rate,
+    /// and the permutations are given in order, first for absorbing, then for squeezing.
+    Sponge {
+        capacity_value: Vec<F>,
+        input: Vec<F>,
+        permutations: Vec<PermutationTrace>,
+        output: Vec<F>,
+    },
+}
+
+#[cfg(feature = "trace")]
+impl PoseidonCall {
+    /// Returns all applications of the permutation within this call, in order.
+    #[must_use]
+    pub fn permutations(&self) -> &[PermutationTrace] {
+        match self {
+            Self::Compress { permutation, .. } => core::slice::from_ref(permutation),
+            Self::Sponge { permutations, .. } => permutations,
+        }
+    }
+
+    /// Returns the output of this call.
+    #[must_use]
+    pub fn output(&self) -> &[F] {
+        match self {
+            Self::Compress { output, .. } | Self::Sponge { output, .. } => output,
+        }
+    }
+}
+
+/// Number of threads that currently record Poseidon calls. This makes
+/// the check in `is_recording` cheap if no thread is recording.
+#[cfg(feature = "trace")]
+static NUM_RECORDING_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "trace")]
+thread_local! {
+    /// Poseidon calls recorded by the current thread, if it is recording.
+    static RECORDED_CALLS: RefCell<Option<Vec<PoseidonCall>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` and returns its result, together with all calls to `poseidon_compress`
+/// and to the Poseidon sponge that `f` made on the current thread, in order.
+///
+/// Note: calls that `f` makes on other threads (e.g., via rayon) are not recorded.
+#[cfg(feature = "trace")]
+pub fn record_poseidon_calls<T>(f: impl FnOnce() -> T) -> (T, Vec<PoseidonCall>) {
+    /// Restores the previous recording, also if `f` panics.
+    struct Recording(Option<Vec<PoseidonCall>>);
+
+    impl Drop for Recording {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            RECORDED_CALLS.with(|calls| *calls.borrow_mut() = previous);
+            NUM_RECORDING_THREADS.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    NUM_RECORDING_THREADS.fetch_add(1, Ordering::Relaxed);
+    let recording = Recording(RECORDED_CALLS.with(|calls| calls.replace(Some(Vec::new()))));
+    let result = f();
+    let recorded = RECORDED_CALLS.with(|calls| calls.borrow_mut().take().unwrap_or_default());
+    drop(recording);
+    (result, recorded)
+}
+
+/// Returns whether the current thread is recording Poseidon calls.
+#[cfg(feature = "trace")]
+fn is_recording() -> bool {
+    NUM_RECORDING_THREADS.load(Ordering::Relaxed) > 0
+        && RECORDED_CALLS.with(|calls| calls.borrow().is_some())
+}
+
+/// Adds a call to the recording of the current thread, if any.
+#[cfg(feature = "trace")]
+fn record(call: PoseidonCall) {
+    RECORDED_CALLS.with(|calls| {
+        if let Some(calls) = calls.borrow_mut().as_mut() {
+            calls.push(call);
+        }
+    });
+}
+
+/// Without the feature `trace`, Poseidon calls are never recorded. As this is known at
+/// compile time, hashing does not check for a recording.
+#[cfg(not(feature = "trace"))]
+const fn is_recording() -> bool {
+    false
+}
+
+#[cfg(not(feature = "trace"))]
+fn record(_call: PoseidonCall) {}
+
+/// Applies the permutation to the state, and adds the states to `trace` if given.
+fn permute_traced<P, const WIDTH: usize>(
+    perm: &P,
+    state: &mut [F; WIDTH],
+    trace: Option<&mut Vec<PermutationTrace>>,
+) where
+    P: Permutation<[F; WIDTH]>,
+{
+    match trace {
+        Some(trace) => {
+            let input = state.to_vec();
+            perm.permute_mut(state);
+            trace.push(PermutationTrace {
+                input,
+                output: state.to_vec(),
+            });
+        }
+        None => perm.permute_mut(state),
     }
 }
 
@@ -108,7 +298,8 @@ where
     let mut state = padded_input;
 
     // Apply the Poseidon permutation in-place.
-    perm.permute_mut(&mut state);
+    let mut trace = is_recording().then(Vec::new);
+    permute_traced(perm, &mut state, trace.as_mut());
 
     // Feed-forward: Add the input back into the state element-wise.
     for i in 0..WIDTH {
@@ -116,21 +307,30 @@ where
     }
 
     // Truncate and return the first `OUT_LEN` elements of the state.
-    state[..OUT_LEN]
+    let output: [F; OUT_LEN] = state[..OUT_LEN]
         .try_into()
-        .expect("OUT_LEN is larger than permutation width")
+        .expect("OUT_LEN is larger than permutation width");
+
+    if let Some(mut trace) = trace {
+        record(PoseidonCall::Compress {
+            permutation: trace.remove(0),
+            output: output.to_vec(),
+        });
+    }
+    output
 }
 
 /// Computes a Poseidon-based domain separator by compressing an array of `u32`
 /// values using a fixed Poseidon instance.
 ///
 /// ### Usage constraints
-/// - This function is private because it's tailored to a very specific case:
-///   the Poseidon2 instance with arity 24 and a fixed 4-word input.
+/// - This function is tailored to a very specific case: the Poseidon2 instance with
+///   arity 24 and a fixed 4-word input. Outside of this module, it is only used by the
+///   reference verifier constraints, which need the same domain separators.
 /// - As this function operates on constants, its output can be **precomputed**
 ///   for significant performance gains, especially within a circuit.
 /// - If generalization is ever needed, a more generic and slower version should be used.
-fn poseidon_safe_domain_separator<P, const WIDTH: usize, const OUT_LEN: usize>(
+pub fn poseidon_safe_domain_separator<P, const WIDTH: usize, const OUT_LEN: usize>(
     perm: &P,
     params: &[u32; DOMAIN_PARAMETERS_LENGTH],
 ) -> [F; OUT_LEN]
@@ -150,7 +350,7 @@ where
     //
     // We can use 24 as hardcoded because the only time we use this function
     // is for the corresponding Poseidon instance.
-    let input = std::array::from_fn::<_, 24, _>(|_| {
+    let input = core::array::from_fn::<_, 24, _>(|_| {
         let digit = (acc % F::ORDER_U64 as u128) as u64;
         acc /= F::ORDER_U64 as u128;
         F::from_u64(digit)
@@ -205,22 +405,98 @@ where
     let mut state = [F::ZERO; WIDTH];
     state[rate..].copy_from_slice(capacity_value);
 
+    let mut trace = is_recording().then(Vec::new);
+
     // absorb
     for chunk in input_vector.chunks(rate) {
         for i in 0..chunk.len() {
             state[i] += chunk[i];
         }
-        perm.permute_mut(&mut state);
+        permute_traced(perm, &mut state, trace.as_mut());
     }
 
     // squeeze
     let mut out = vec![];
     while out.len() < OUT_LEN {
         out.extend_from_slice(&state[..rate]);
-        perm.permute_mut(&mut state);
+        permute_traced(perm, &mut state, trace.as_mut());
     }
     let slice = &out[0..OUT_LEN];
-    slice.try_into().expect("Length mismatch")
+    let output: [F; OUT_LEN] = slice.try_into().expect("Length mismatch");
+
+    if let Some(permutations) = trace {
+        record(PoseidonCall::Sponge {
+            capacity_value: capacity_value.to_vec(),
+            input: input_vector,
+            permutations,
+            output: output.to_vec(),
+        });
+    }
+    output
+}
+
+/// Packs bytes into field elements, `PRE_HASH_BYTES_PER_FE` bytes each, little-endian.
+/// The last element may contain fewer bytes.
+fn pack_pre_hash_bytes(bytes: &[u8]) -> impl Iterator<Item = F> + '_ {
+    bytes.chunks(PRE_HASH_BYTES_PER_FE).map(|chunk| {
+        let value = chunk
+            .iter()
+            .rev()
+            .fold(0u32, |acc, &byte| (acc << 8) | byte as u32);
+        F::from_u32(value)
+    })
+}
+
+/// Pre-hashes a context string and a message of arbitrary length into a message
+/// of `MESSAGE_LENGTH` bytes, using the Poseidon2 sponge.
+///
+/// The input of the sponge consists of the lengths of context and message, each as
+/// `u64` in `PRE_HASH_LENGTH_BYTES` bytes, followed by context and message. All of them
+/// are packed into field elements (`PRE_HASH_BYTES_PER_FE` bytes each). The parameters of
+/// this encoding are placed in the capacity as a domain separator. The output field
+/// elements are interpreted as a number in base p, and its lowest `MESSAGE_LENGTH`
+/// bytes form the result.
+fn poseidon_pre_hash(context: &[u8], message: &[u8]) -> [u8; MESSAGE_LENGTH] {
+    let perm = poseidon2_24();
+
+    let domain_parameters: [u32; DOMAIN_PARAMETERS_LENGTH] = [
+        PRE_HASH_DOMAIN_SEP,
+        PRE_HASH_LENGTH_BYTES as u32,
+        PRE_HASH_BYTES_PER_FE as u32,
+        MESSAGE_LENGTH as u32,
+    ];
+    let capacity_value = poseidon_safe_domain_separator::<
+        _,
+        MERGE_COMPRESSION_WIDTH,
+        PRE_HASH_CAPACITY,
+    >(perm, &domain_parameters);
+
+    // lengths are needed to know where the context ends and how the last elements were
+    // packed. They have a fixed size, so they are packed separately from the data.
+    let context_len = (context.len() as u64).to_le_bytes();
+    let message_len = (message.len() as u64).to_le_bytes();
+    let input: Vec<F> = pack_pre_hash_bytes(&context_len)
+        .chain(pack_pre_hash_bytes(&message_len))
+        .chain(pack_pre_hash_bytes(context))
+        .chain(pack_pre_hash_bytes(message))
+        .collect();
+
+    let output = poseidon_sponge::<_, MERGE_COMPRESSION_WIDTH, PRE_HASH_OUTPUT_LEN>(
+        perm,
+        &capacity_value,
+        &input,
+    );
+
+    // combine the output into a number in base p, and take its lowest bytes
+    let mut acc = BigUint::ZERO;
+    for fe in output {
+        acc = &acc * F::ORDER_U64 + fe.as_canonical_biguint();
+    }
+    let acc_bytes = acc.to_bytes_le();
+    let mut result = [0u8; MESSAGE_LENGTH];
+    let num_bytes = acc_bytes.len().min(MESSAGE_LENGTH);
+    result[..num_bytes].copy_from_slice(&acc_bytes[..num_bytes]);
+    result
 }
 
 /// A tweakable hash function implemented using Poseidon2
@@ -260,14 +536,14 @@ where
         rng.random()
     }
 
-    fn tree_tweak(level: u8, pos_in_level: u32) -> Self::Tweak {
+    fn tree_tweak(level: u8, pos_in_level: u64) -> Self::Tweak {
         PoseidonTweak::TreeTweak {
             level,
             pos_in_level,
         }
     }
 
-    fn chain_tweak(epoch: u32, chain_index: u8, pos_in_chain: u8) -> Self::Tweak {
+    fn chain_tweak(epoch: u64, chain_index: u8, pos_in_chain: u8) -> Self::Tweak {
         PoseidonTweak::ChainTweak {
             epoch,
             chain_index,
@@ -297,7 +573,7 @@ where
                     .chain(single.iter())
                     .copied()
                     .collect();
-                poseidon_compress::<_, CHAIN_COMPRESSION_WIDTH, HASH_LEN>(&perm, &combined_input)
+                poseidon_compress::<_, CHAIN_COMPRESSION_WIDTH, HASH_LEN>(perm, &combined_input)
             }
 
             [left, right] => {
@@ -310,7 +586,7 @@ where
                     .chain(right.iter())
                     .copied()
                     .collect();
-                poseidon_compress::<_, MERGE_COMPRESSION_WIDTH, HASH_LEN>(&perm, &combined_input)
+                poseidon_compress::<_, MERGE_COMPRESSION_WIDTH, HASH_LEN>(perm, &combined_input)
             }
 
             _ if message.len() > 2 => {
@@ -331,10 +607,10 @@ where
                 ];
                 let capacity_value =
                     poseidon_safe_domain_separator::<_, MERGE_COMPRESSION_WIDTH, CAPACITY>(
-                        &perm, &lengths,
+                        perm, &lengths,
                     );
                 poseidon_sponge::<_, MERGE_COMPRESSION_WIDTH, HASH_LEN>(
-                    &perm,
+                    perm,
                     &capacity_value,
                     &combined_input,
                 )
@@ -343,6 +619,14 @@ where
         }
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(
+            out,
+            IDENTIFIER_KIND,
+            &[PARAMETER_LEN, HASH_LEN, TWEAK_LEN, CAPACITY, NUM_CHUNKS],
+        );
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         assert!(
@@ -365,6 +649,8 @@ where
             "Poseidon Tweak Leaf Hash: not enough field elements to hash the domain separator"
         );
 
+        // tweaks for 32-bit epochs and positions must fit. Larger epochs may need more
+        // field elements, which the signature scheme checks for its lifetime.
         let bits_for_tree_tweak = f64::from(32 + 8_u32);
         let bits_for_chain_tweak = f64::from(32 + 8 + 8 + 8_u32);
         let tweak_fe_bits = bits_per_fe * f64::from(TWEAK_LEN as u32);
@@ -379,6 +665,19 @@ where
     }
 }
 
+impl<
+    const PARAMETER_LEN: usize,
+    const HASH_LEN: usize,
+    const TWEAK_LEN: usize,
+    const CAPACITY: usize,
+    const NUM_CHUNKS: usize,
+> MessagePreHash for PoseidonTweakHash<PARAMETER_LEN, HASH_LEN, TWEAK_LEN, CAPACITY, NUM_CHUNKS>
+{
+    fn pre_hash(context: &[u8], message: &[u8]) -> [u8; MESSAGE_LENGTH] {
+        poseidon_pre_hash(context, message)
+    }
+}
+
 // Example instantiations
 #[cfg(test)]
 pub type PoseidonTweak44 = PoseidonTweakHash<4, 4, 3, 9, 128>;
@@ -396,6 +695,35 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    #[cfg(feature = "trace")]
+    fn test_record_poseidon_calls() {
+        let mut rng = rand::rng();
+        let parameter = PoseidonTweak44::rand_parameter(&mut rng);
+        let message = PoseidonTweak44::rand_domain(&mut rng);
+        let tweak = PoseidonTweak44::chain_tweak(2, 3, 4);
+
+        // nothing is recorded by default
+        assert!(!is_recording());
+
+        // recordings can be nested, and each one only contains its own calls
+        let ((first, (second, inner_calls)), outer_calls) = record_poseidon_calls(|| {
+            let first = PoseidonTweak44::apply(&parameter, &tweak, &[message]);
+            let inner =
+                record_poseidon_calls(|| PoseidonTweak44::apply(&parameter, &tweak, &[first]));
+            (first, inner)
+        });
+        assert!(!is_recording());
+        assert_eq!(outer_calls.len(), 1);
+        assert_eq!(inner_calls.len(), 1);
+        assert_eq!(outer_calls[0].output(), first.as_slice());
+        assert_eq!(inner_calls[0].output(), second.as_slice());
+        assert_eq!(
+            outer_calls[0].permutations()[0].width(),
+            CHAIN_COMPRESSION_WIDTH
+        );
+    }
+
     #[test]
     fn test_apply_44() {
         let mut rng = rand::rng();
@@ -444,6 +772,40 @@ mod tests {
         let _ = PoseidonTweak37::apply(&parameter, &tweak_chain, &[message_one]);
     }
 
+    #[test]
+    fn test_pre_hash() {
+        // known answer, this must never change as signatures of pre-hashed messages
+        // are verified by recomputing the pre-hash
+        let digest = PoseidonTweak44::pre_hash(b"context", b"message");
+        assert_eq!(
+            digest,
+            [
+                0x25, 0xd1, 0xab, 0xc6, 0xff, 0x36, 0xb8, 0xbd, 0x05, 0xd2, 0x2d, 0x3f, 0xa8, 0x1f,
+                0x7e, 0x5e, 0x8e, 0x4c, 0x71, 0x19, 0x0d, 0x84, 0x7f, 0xbc, 0x63, 0x16, 0xd0, 0xca,
+                0xb5, 0x6f, 0xb7, 0x87
+            ]
+        );
+
+        // the pre-hash does not depend on the parameters of the tweakable hash
+        assert_eq!(PoseidonTweak37::pre_hash(b"context", b"message"), digest);
+
+        // moving bytes between context and message changes the result
+        assert_ne!(PoseidonTweak44::pre_hash(b"contex", b"tmessage"), digest);
+        assert_ne!(PoseidonTweak44::pre_hash(b"", b"contextmessage"), digest);
+        assert_ne!(PoseidonTweak44::pre_hash(b"contextmessage", b""), digest);
+
+        // trailing zero bytes are not lost when packing into field elements
+        assert_ne!(PoseidonTweak44::pre_hash(b"context", b"message\0"), digest);
+        assert_ne!(
+            PoseidonTweak44::pre_hash(b"", &[0x00]),
+            PoseidonTweak44::pre_hash(b"", &[0x00, 0x00])
+        );
+        assert_ne!(
+            PoseidonTweak44::pre_hash(b"", &[]),
+            PoseidonTweak44::pre_hash(b"", &[0x00])
+        );
+    }
+
     #[test]
     fn test_rand_parameter_not_all_same() {
         // Setup a umber of trials
@@ -497,7 +859,7 @@ mod tests {
     fn test_tree_tweak_field_elements() {
         // Tweak
         let level = 1u8;
-        let pos_in_level = 2u32;
+        let pos_in_level = 2u64;
         let sep = TWEAK_SEPARATOR_FOR_TREE_HASH as u64;
 
         // Compute tweak_bigint
@@ -525,7 +887,7 @@ mod tests {
     #[test]
     fn test_chain_tweak_field_elements() {
         // Tweak
-        let epoch = 1u32;
+        let epoch = 1u64;
         let chain_index = 2u8;
         let pos_in_chain = 3u8;
         let sep = TWEAK_SEPARATOR_FOR_CHAIN_HASH as u64;
@@ -558,7 +920,7 @@ mod tests {
     #[test]
     fn test_tree_tweak_field_elements_max_values() {
         let level = u8::MAX;
-        let pos_in_level = u32::MAX;
+        let pos_in_level = u64::from(u32::MAX);
         let sep = TWEAK_SEPARATOR_FOR_TREE_HASH as u64;
 
         let tweak_bigint: BigUint =
@@ -580,7 +942,7 @@ mod tests {
 
     #[test]
     fn test_chain_tweak_field_elements_max_values() {
-        let epoch = u32::MAX;
+        let epoch = u64::from(u32::MAX);
         let chain_index = u8::MAX;
         let pos_in_chain = u8::MAX;
         let sep = TWEAK_SEPARATOR_FOR_CHAIN_HASH as u64;
@@ -605,10 +967,63 @@ mod tests {
         assert_eq!(computed, expected);
     }
 
+    #[test]
+    fn test_tweak_field_elements_wide_values() {
+        let p = BigUint::from(F::ORDER_U64);
+        let digits = |tweak_bigint: BigUint| -> [F; 3] {
+            core::array::from_fn(|i| {
+                F::from_u128(((&tweak_bigint / p.pow(i as u32)) % &p).try_into().unwrap())
+            })
+        };
+
+        // the upper 32 bits of the position are placed above the level
+        let level = u8::MAX;
+        let pos_in_level = u64::MAX;
+        let sep = TWEAK_SEPARATOR_FOR_TREE_HASH as u64;
+        let tweak_bigint: BigUint = ((BigUint::from(pos_in_level) >> 32) << 48)
+            + (BigUint::from(level) << 40)
+            + (BigUint::from(u32::MAX) << 8)
+            + sep;
+        let tweak = PoseidonTweak::TreeTweak {
+            level,
+            pos_in_level,
+        };
+        assert_eq!(tweak.to_field_elements::<3>(), digits(tweak_bigint));
+
+        let epoch = u64::MAX;
+        let chain_index = u8::MAX;
+        let pos_in_chain = u8::MAX;
+        let sep = TWEAK_SEPARATOR_FOR_CHAIN_HASH as u64;
+        let tweak_bigint: BigUint = (BigUint::from(epoch) << 24)
+            + (BigUint::from(chain_index) << 16)
+            + (BigUint::from(pos_in_chain) << 8)
+            + sep;
+        let tweak = PoseidonTweak::ChainTweak {
+            epoch,
+            chain_index,
+            pos_in_chain,
+        };
+        assert_eq!(tweak.to_field_elements::<3>(), digits(tweak_bigint));
+
+        // two field elements are not enough for such an epoch
+        let result = std::panic::catch_unwind(|| {
+            PoseidonTweak::ChainTweak {
+                epoch: 1 << 40,
+                chain_index,
+                pos_in_chain,
+            }
+            .to_field_elements::<2>()
+        });
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_tree_tweak_injective() {
         let mut rng = rand::rng();
 
+        // we use two field elements, as in our instantiations, and 32-bit positions
+        // so that all tweaks fit
+
         // basic test to check that tree tweak maps from
         // parameters to field elements array injectively
 
@@ -616,7 +1031,7 @@ mod tests {
         let mut map = HashMap::new();
         for _ in 0..100_000 {
             let level = rng.random();
-            let pos_in_level = rng.random();
+            let pos_in_level = u64::from(rng.random::<u32>());
             let tweak_encoding = PoseidonTweak::TreeTweak {
                 level,
                 pos_in_level,
@@ -643,7 +1058,7 @@ mod tests {
         let mut map = HashMap::new();
         let level = rng.random();
         for _ in 0..10_000 {
-            let pos_in_level = rng.random();
+            let pos_in_level = u64::from(rng.random::<u32>());
             let tweak_encoding = PoseidonTweak::TreeTweak {
                 level,
                 pos_in_level,
@@ -661,7 +1076,7 @@ mod tests {
 
         // inputs with common pos_in_level
         let mut map = HashMap::new();
-        let pos_in_level = rng.random();
+        let pos_in_level = u64::from(rng.random::<u32>());
         for _ in 0..10_000 {
             let level = rng.random();
             let tweak_encoding = PoseidonTweak::TreeTweak {
@@ -684,13 +1099,16 @@ mod tests {
     fn test_chain_tweak_injective() {
         let mut rng = rand::rng();
 
+        // we use two field elements, as in our instantiations, and 32-bit epochs
+        // so that all tweaks fit
+
         // basic test to check that chain tweak maps from
         // parameters to field element array injectively
 
         // random inputs
         let mut map = HashMap::new();
         for _ in 0..100_000 {
-            let epoch = rng.random();
+            let epoch = u64::from(rng.random::<u32>());
             let chain_index = rng.random();
             let pos_in_chain = rng.random();
 
@@ -713,7 +1131,7 @@ mod tests {
 
         // inputs with fixed epoch
         let mut map = HashMap::new();
-        let epoch = rng.random();
+        let epoch = u64::from(rng.random::<u32>());
         for _ in 0..10_000 {
             let chain_index = rng.random();
             let pos_in_chain = rng.random();
@@ -739,7 +1157,7 @@ mod tests {
         let mut map = HashMap::new();
         let chain_index = rng.random();
         for _ in 0..10_000 {
-            let epoch = rng.random();
+            let epoch = u64::from(rng.random::<u32>());
             let pos_in_chain = rng.random();
 
             let input = (epoch, pos_in_chain);
@@ -763,7 +1181,7 @@ mod tests {
         let mut map = HashMap::new();
         let pos_in_chain = rng.random();
         for _ in 0..10_000 {
-            let epoch = rng.random();
+            let epoch = u64::from(rng.random::<u32>());
             let chain_index = rng.random();
 
             let input = (epoch, chain_index);
@@ -783,4 +1201,187 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_tree_tweak_injective_wide() {
+        let mut rng = rand::rng();
+
+        // we use three field elements, so that tweaks for all 64-bit positions fit
+
+        // basic test to check that tree tweak maps from
+        // parameters to field elements array injectively
+
+        // random inputs
+        let mut map = HashMap::new();
+        for _ in 0..100_000 {
+            let level = rng.random();
+            let pos_in_level = rng.random();
+            let tweak_encoding = PoseidonTweak::TreeTweak {
+                level,
+                pos_in_level,
+            }
+            .to_field_elements::<3>();
+
+            if let Some((prev_level, prev_pos_in_level)) =
+                map.insert(tweak_encoding, (level, pos_in_level))
+            {
+                assert_eq!(
+                    (prev_level, prev_pos_in_level),
+                    (level, pos_in_level),
+                    "Collision detected for ({},{}) and ({},{}) with output {:?}",
+                    prev_level,
+                    prev_pos_in_level,
+                    level,
+                    pos_in_level,
+                    tweak_encoding
+                );
+            }
+        }
+
+        // inputs with common level
+        let mut map = HashMap::new();
+        let level = rng.random();
+        for _ in 0..10_000 {
+            let pos_in_level = rng.random();
+            let tweak_encoding = PoseidonTweak::TreeTweak {
+                level,
+                pos_in_level,
+            }
+            .to_field_elements::<3>();
+
+            if let Some(prev_pos_in_level) = map.insert(tweak_encoding, pos_in_level) {
+                assert_eq!(
+                    prev_pos_in_level, pos_in_level,
+                    "Collision detected for ({},{}) and ({},{}) with output {:?}",
+                    level, prev_pos_in_level, level, pos_in_level, tweak_encoding
+                );
+            }
+        }
+
+        // inputs with common pos_in_level
+        let mut map = HashMap::new();
+        let pos_in_level = rng.random();
+        for _ in 0..10_000 {
+            let level = rng.random();
+            let tweak_encoding = PoseidonTweak::TreeTweak {
+                level,
+                pos_in_level,
+            }
+            .to_field_elements::<3>();
+
+            if let Some(prev_level) = map.insert(tweak_encoding, level) {
+                assert_eq!(
+                    prev_level, level,
+                    "Collision detected for ({},{}) and ({},{}) with output {:?}",
+                    prev_level, pos_in_level, level, pos_in_level, tweak_encoding
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_chain_tweak_injective_wide() {
+        let mut rng = rand::rng();
+
+        // we use three field elements, so that tweaks for all 64-bit epochs fit
+
+        // basic test to check that chain tweak maps from
+        // parameters to field element array injectively
+
+        // random inputs
+        let mut map = HashMap::new();
+        for _ in 0..100_000 {
+            let epoch = rng.random();
+            let chain_index = rng.random();
+            let pos_in_chain = rng.random();
+
+            let input = (epoch, chain_index, pos_in_chain);
+
+            let tweak_encoding = PoseidonTweak::ChainTweak {
+                epoch,
+                chain_index,
+                pos_in_chain,
+            }
+            .to_field_elements::<3>();
+
+            if let Some(prev_input) = map.insert(tweak_encoding, input) {
+                assert_eq!(
+                    prev_input, input,
+                    "Collision detected for {prev_input:?} and {input:?} with output {tweak_encoding:?}"
+                );
+            }
+        }
+
+        // inputs with fixed epoch
+        let mut map = HashMap::new();
+        let epoch = rng.random();
+        for _ in 0..10_000 {
+            let chain_index = rng.random();
+            let pos_in_chain = rng.random();
+
+            let input = (chain_index, pos_in_chain);
+
+            let tweak_encoding = PoseidonTweak::ChainTweak {
+                epoch,
+                chain_index,
+                pos_in_chain,
+            }
+            .to_field_elements::<3>();
+
+            if let Some(prev_input) = map.insert(tweak_encoding, input) {
+                assert_eq!(
+                    prev_input, input,
+                    "Collision detected for {prev_input:?} and {input:?} with output {tweak_encoding:?}"
+                );
+            }
+        }
+
+        // inputs with fixed chain_index
+        let mut map = HashMap::new();
+        let chain_index = rng.random();
+        for _ in 0..10_000 {
+            let epoch = rng.random();
+            let pos_in_chain = rng.random();
+
+            let input = (epoch, pos_in_chain);
+
+            let tweak_encoding = PoseidonTweak::ChainTweak {
+                epoch,
+                chain_index,
+                pos_in_chain,
+            }
+            .to_field_elements::<3>();
+
+            if let Some(prev_input) = map.insert(tweak_encoding, input) {
+                assert_eq!(
+                    prev_input, input,
+                    "Collision detected for {prev_input:?} and {input:?} with output {tweak_encoding:?}"
+                );
+            }
+        }
+
+        // inputs with fixed pos_in_chain
+        let mut map = HashMap::new();
+        let pos_in_chain = rng.random();
+        for _ in 0..10_000 {
+            let epoch = rng.random();
+            let chain_index = rng.random();
+
+            let input = (epoch, chain_index);
+
+            let tweak_encoding = PoseidonTweak::ChainTweak {
+                epoch,
+                chain_index,
+                pos_in_chain,
+            }
+            .to_field_elements::<3>();
+
+            if let Some(prev_input) = map.insert(tweak_encoding, input) {
+                assert_eq!(
+                    prev_input, input,
+                    "Collision detected for {prev_input:?} and {input:?} with output {tweak_encoding:?}"
+                );
+            }
+        }
+    }
 }
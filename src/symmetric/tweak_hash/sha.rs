@@ -1,18 +1,29 @@
+use alloc::vec::Vec;
 use serde::{Serialize, de::DeserializeOwned};
 use sha3::{Digest, Sha3_256};
 
-use crate::{TWEAK_SEPARATOR_FOR_CHAIN_HASH, TWEAK_SEPARATOR_FOR_TREE_HASH};
+use crate::{
+    MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_CHAIN_HASH, TWEAK_SEPARATOR_FOR_TREE_HASH,
+    epoch_encoding_len, tweak_separator, write_identifier,
+};
 
-use super::TweakableHash;
+use super::{MessagePreHash, TweakableHash};
+
+/// Kind of this tweakable hash in identifiers, see `write_identifier`.
+const IDENTIFIER_KIND: u8 = 0x01;
+
+const PRE_HASH_DOMAIN_SEP: [u8; 16] = [
+    0x9e, 0x4a, 0x50, 0xff, 0x00, 0x02, 0xc7, 0x11, 0x00, 0xb3, 0x5d, 0xff, 0x02, 0x08, 0xe1, 0x00,
+];
 
 /// Enum to implement tweaks.
 pub enum ShaTweak {
     TreeTweak {
         level: u8,
-        pos_in_level: u32,
+        pos_in_level: u64,
     },
     ChainTweak {
-        epoch: u32,
+        epoch: u64,
         chain_index: u8,
         pos_in_chain: u8,
     },
@@ -27,14 +38,19 @@ impl ShaTweak {
             } => {
                 let mut bytes = Vec::new();
                 // start with the tree tweak prefix.
-                bytes.push(TWEAK_SEPARATOR_FOR_TREE_HASH);
+                bytes.push(tweak_separator(
+                    TWEAK_SEPARATOR_FOR_TREE_HASH,
+                    *pos_in_level,
+                ));
                 // then we extend with the actual data
                 bytes.extend(&level.to_be_bytes());
-                bytes.extend(&pos_in_level.to_be_bytes());
+                bytes.extend(&pos_in_level.to_be_bytes()[8 - epoch_encoding_len(*pos_in_level)..]);
                 // Note: it is fine that both tweaks have different
                 // lengths as the domain separator (0x00 or 0x01)
                 // ensures that the length is known and we know when
-                // the tweak ends.
+                // the tweak ends. Positions and epochs are encoded
+                // with four bytes if they fit, and eight bytes otherwise,
+                // which is also given by the separator (see `tweak_separator`).
                 bytes
             }
             Self::ChainTweak {
@@ -44,9 +60,9 @@ impl ShaTweak {
             } => {
                 let mut bytes = Vec::new();
                 // start with the chain tweak prefix.
-                bytes.push(TWEAK_SEPARATOR_FOR_CHAIN_HASH);
+                bytes.push(tweak_separator(TWEAK_SEPARATOR_FOR_CHAIN_HASH, *epoch));
                 // then we extend with the actual data
-                bytes.extend(&epoch.to_be_bytes());
+                bytes.extend(&epoch.to_be_bytes()[8 - epoch_encoding_len(*epoch)..]);
                 bytes.extend(&chain_index.to_be_bytes());
                 bytes.extend(&pos_in_chain.to_be_bytes());
                 bytes
@@ -81,14 +97,14 @@ where
         rng.random()
     }
 
-    fn tree_tweak(level: u8, pos_in_level: u32) -> Self::Tweak {
+    fn tree_tweak(level: u8, pos_in_level: u64) -> Self::Tweak {
         ShaTweak::TreeTweak {
             level,
             pos_in_level,
         }
     }
 
-    fn chain_tweak(epoch: u32, chain_index: u8, pos_in_chain: u8) -> Self::Tweak {
+    fn chain_tweak(epoch: u64, chain_index: u8, pos_in_chain: u8) -> Self::Tweak {
         ShaTweak::ChainTweak {
             epoch,
             chain_index,
@@ -117,6 +133,10 @@ where
         result[0..HASH_LEN].try_into().unwrap()
     }
 
+    fn write_identifier(out: &mut Vec<u8>) {
+        write_identifier(out, IDENTIFIER_KIND, &[PARAMETER_LEN, HASH_LEN]);
+    }
+
     #[cfg(test)]
     fn internal_consistency_check() {
         assert!(
@@ -130,6 +150,26 @@ where
     }
 }
 
+impl<const PARAMETER_LEN: usize, const HASH_LEN: usize> MessagePreHash
+    for ShaTweakHash<PARAMETER_LEN, HASH_LEN>
+{
+    fn pre_hash(context: &[u8], message: &[u8]) -> [u8; MESSAGE_LENGTH] {
+        let mut hasher = Sha3_256::new();
+
+        // domain separator, so that pre-hashing is independent of all other uses of SHA3
+        hasher.update(PRE_HASH_DOMAIN_SEP);
+
+        // the context is prefixed with its length, so that the boundary
+        // between context and message is unambiguous
+        hasher.update((context.len() as u64).to_be_bytes());
+        hasher.update(context);
+        hasher.update(message);
+
+        // SHA3-256 outputs exactly MESSAGE_LENGTH = 32 bytes
+        hasher.finalize().into()
+    }
+}
+
 // Example instantiations
 #[cfg(test)]
 pub type ShaTweak128128 = ShaTweakHash<16, 16>;
@@ -144,6 +184,8 @@ mod tests {
 
     use rand::Rng;
 
+    use crate::TWEAK_SEPARATOR_WIDE_FLAG;
+
     use super::*;
 
     #[test]
@@ -212,6 +254,28 @@ mod tests {
         ShaTweak192192::apply(&parameter, &tweak_chain, &[message_one, message_two]);
     }
 
+    #[test]
+    fn test_pre_hash() {
+        // known answer, computed independently from the specification
+        let digest = ShaTweak128128::pre_hash(b"context", b"message");
+        assert_eq!(
+            digest,
+            [
+                0x4a, 0x9d, 0xe2, 0x5c, 0x53, 0xfd, 0xc0, 0x69, 0x9f, 0x0e, 0x16, 0x21, 0x35, 0x21,
+                0x81, 0x14, 0x45, 0xdd, 0xae, 0xbf, 0x26, 0x07, 0x7d, 0xd8, 0x7f, 0xcc, 0xf6, 0xff,
+                0x91, 0x0e, 0x41, 0x7b
+            ]
+        );
+
+        // the pre-hash does not depend on the parameters of the tweakable hash
+        assert_eq!(ShaTweak192192::pre_hash(b"context", b"message"), digest);
+
+        // moving bytes between context and message changes the result
+        assert_ne!(ShaTweak128128::pre_hash(b"contex", b"tmessage"), digest);
+        assert_ne!(ShaTweak128128::pre_hash(b"", b"contextmessage"), digest);
+        assert_ne!(ShaTweak128128::pre_hash(b"contextmessage", b""), digest);
+    }
+
     #[test]
     fn test_tree_tweak_injective() {
         let mut rng = rand::rng();
@@ -390,4 +454,126 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_tweak_encoding_wide_values() {
+        // values below 2^32 are encoded with four bytes, as before epochs were 64 bits wide
+        let tree_tweak = ShaTweak::TreeTweak {
+            level: 3,
+            pos_in_level: 0x0102_0304,
+        };
+        assert_eq!(
+            tree_tweak.to_bytes(),
+            [TWEAK_SEPARATOR_FOR_TREE_HASH, 3, 1, 2, 3, 4]
+        );
+        let chain_tweak = ShaTweak::ChainTweak {
+            epoch: 0x0102_0304,
+            chain_index: 5,
+            pos_in_chain: 6,
+        };
+        assert_eq!(
+            chain_tweak.to_bytes(),
+            [TWEAK_SEPARATOR_FOR_CHAIN_HASH, 1, 2, 3, 4, 5, 6]
+        );
+
+        // larger values are encoded with eight bytes, which is marked in the separator
+        let tree_tweak = ShaTweak::TreeTweak {
+            level: 3,
+            pos_in_level: 0x0a0b_0102_0304,
+        };
+        assert_eq!(
+            tree_tweak.to_bytes(),
+            [
+                TWEAK_SEPARATOR_FOR_TREE_HASH | TWEAK_SEPARATOR_WIDE_FLAG,
+                3,
+                0,
+                0,
+                0x0a,
+                0x0b,
+                1,
+                2,
+                3,
+                4
+            ]
+        );
+        let chain_tweak = ShaTweak::ChainTweak {
+            epoch: 1 << 32,
+            chain_index: 5,
+            pos_in_chain: 6,
+        };
+        assert_eq!(
+            chain_tweak.to_bytes(),
+            [
+                TWEAK_SEPARATOR_FOR_CHAIN_HASH | TWEAK_SEPARATOR_WIDE_FLAG,
+                0,
+                0,
+                0,
+                1,
+                0,
+                0,
+                0,
+                0,
+                5,
+                6
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tweak_encoding_prefix_free() {
+        // a tweak with four bytes followed by the first bytes of the message never equals
+        // a tweak with eight bytes. Without the flag, these two inputs would be the same.
+        let short = ShaTweak::ChainTweak {
+            epoch: 1,
+            chain_index: 5,
+            pos_in_chain: 6,
+        };
+        let long = ShaTweak::ChainTweak {
+            epoch: 0x0000_0001_0506_0000,
+            chain_index: 5,
+            pos_in_chain: 6,
+        };
+        let mut short_with_message = short.to_bytes();
+        short_with_message.extend([0, 0, 5, 6]);
+        assert_eq!(short_with_message[1..], long.to_bytes()[1..]);
+        assert_ne!(short_with_message, long.to_bytes());
+
+        // more generally, no tweak encoding is a prefix of another one
+        let mut rng = rand::rng();
+        let mut values: Vec<u64> = vec![0, 1, u64::from(u32::MAX), 1 << 32, u64::MAX];
+        values.extend((0..50).map(|_| u64::from(rng.random::<u32>())));
+        values.extend((0..50).map(|_| rng.random::<u64>()));
+        let encodings: Vec<Vec<u8>> = values
+            .iter()
+            .flat_map(|&value| {
+                [
+                    ShaTweak::TreeTweak {
+                        level: rng.random(),
+                        pos_in_level: value,
+                    },
+                    ShaTweak::ChainTweak {
+                        epoch: value,
+                        chain_index: rng.random(),
+                        pos_in_chain: rng.random(),
+                    },
+                    ShaTweak::ChainTweak {
+                        epoch: value,
+                        chain_index: 0,
+                        pos_in_chain: 0,
+                    },
+                ]
+            })
+            .map(|tweak| tweak.to_bytes())
+            .collect();
+        for short in &encodings {
+            for long in &encodings {
+                if short.len() < long.len() {
+                    assert!(
+                        !long.starts_with(short),
+                        "{short:?} is a prefix of {long:?}"
+                    );
+                }
+            }
+        }
+    }
 }
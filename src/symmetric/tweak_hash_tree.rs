@@ -1,8 +1,9 @@
-use crate::symmetric::tweak_hash::TweakableHash;
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
-use rayon::prelude::*;
+use crate::{parallelism::map_range, symmetric::tweak_hash::TweakableHash};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// A single layer of a sparse Hash-Tree
 /// based on tweakable hash function
@@ -13,61 +14,100 @@ struct HashTreeLayer<TH: TweakableHash> {
     nodes: Vec<TH::Domain>,
 }
 
-impl<TH: TweakableHash> HashTreeLayer<TH> {
-    /// Construct a layer from a contiguous run of nodes and pad it so that:
-    /// - the layer starts at an even index (a left child), and
-    /// - the layer ends at an odd index (a right child).
-    ///
-    /// Input interpretation:
-    /// - `nodes` conceptually occupy tree indices
-    ///   `[start_index, start_index + nodes.len() - 1]` (inclusive).
+/// Padding nodes of one layer of a sparse Hash-Tree, given by their position and value.
+struct LayerPadding<TH: TweakableHash> {
+    front: Option<(usize, TH::Domain)>,
+    back: Option<(usize, TH::Domain)>,
+}
+
+/// Random padding nodes of a sparse Hash-Tree.
+///
+/// Each layer of a sparse tree is padded so that:
+/// - the layer starts at an even index (a left child), and
+/// - the layer ends at an odd index (a right child).
+///
+/// Padding rules:
+/// - If the start index is odd, we insert one random node in front and shift
+///   the effective start to the previous even index.
+/// - If the end index is even, we append one random node at the back so the
+///   final index is odd.
+///
+/// With this alignment every parent is formed from exactly two children,
+/// so upper layers can be built with exact size-2 chunks, with no edge cases.
+///
+/// The positions of the padding nodes only depend on the range of nodes in the lowest
+/// layer, so all padding nodes can be sampled before any hashing. This allows to build
+/// each sub-tree of a sparse tree on its own (see `new_subtree_with_padding`).
+pub struct SparseTreePadding<TH: TweakableHash> {
+    /// The layer in which the range of nodes is given.
+    lowest_layer: usize,
+
+    /// Padding nodes of each layer, starting with `lowest_layer` and
+    /// ending with the layer of the root.
+    layers: Vec<LayerPadding<TH>>,
+}
+
+impl<TH: TweakableHash> SparseTreePadding<TH> {
+    /// Samples the padding nodes of a sparse tree of the given depth, in which the nodes
+    /// `start_index, ..., start_index + num_nodes - 1` exist in layer `lowest_layer`.
     ///
-    /// Padding rules:
-    /// - If `start_index` is odd, we insert one random node in front and shift
-    ///   the effective start to the previous even index.
-    /// - If the end index is even, we append one random node at the back so the
-    ///   final index is odd.
+    /// The padding nodes are sampled layer by layer, starting with the lowest layer,
+    /// and within a layer the node in front before the node at the back.
     ///
-    /// Why this matters:
-    /// - With this alignment every parent is formed from exactly two children,
-    ///   so upper layers can be built with exact size-2 chunks, with no edge cases.
-    #[inline]
-    fn padded<R: Rng>(rng: &mut R, nodes: Vec<TH::Domain>, start_index: usize) -> Self {
-        // End index of the provided contiguous run (inclusive).
-        let end_index = start_index + nodes.len() - 1;
-
-        // Do we need a front pad? Start must be even.
-        let needs_front = (start_index & 1) == 1;
-
-        // Do we need a back pad? End must be odd.
-        let needs_back = (end_index & 1) == 0;
-
-        // The effective start index after optional front padding (always even).
-        let actual_start_index = start_index - (needs_front as usize);
-
-        // Reserve exactly the space we may need: original nodes plus up to two pads.
-        let mut out =
-            Vec::with_capacity(nodes.len() + (needs_front as usize) + (needs_back as usize));
-
-        // Optional front padding to align to an even start index.
-        if needs_front {
-            out.push(TH::rand_domain(rng));
-        }
-
-        // Insert the actual content in order.
-        out.extend(nodes);
+    /// Note: The RNG is used for generating nodes used for padding in the case of
+    /// sparse trees. They could as well be fixed, and hence the RNG does not need
+    /// to be cryptographically secure for this function.
+    pub fn new<R: Rng>(
+        rng: &mut R,
+        lowest_layer: usize,
+        depth: usize,
+        start_index: usize,
+        num_nodes: usize,
+    ) -> Self {
+        assert!(
+            num_nodes > 0,
+            "Hash-Tree padding: Need at least one node in the lowest layer."
+        );
 
-        // Optional back padding to ensure we end on an odd index.
-        if needs_back {
-            out.push(TH::rand_domain(rng));
+        let mut layers = Vec::with_capacity(depth + 1 - lowest_layer);
+        let mut start = start_index;
+        let mut end = start_index + num_nodes - 1;
+        for _ in lowest_layer..=depth {
+            // front padding to align to an even start index
+            let front = (start & 1 == 1).then(|| {
+                start -= 1;
+                (start, TH::rand_domain(rng))
+            });
+            // back padding to ensure we end on an odd index
+            let back = (end & 1 == 0).then(|| {
+                end += 1;
+                (end, TH::rand_domain(rng))
+            });
+            layers.push(LayerPadding { front, back });
+
+            // the parents of the padded layer
+            start >>= 1;
+            end >>= 1;
         }
 
-        // Return the padded layer with the corrected start index.
         Self {
-            start_index: actual_start_index,
-            nodes: out,
+            lowest_layer,
+            layers,
         }
     }
+
+    /// Returns the padding node at the given position in the given layer,
+    /// or `None` if there is no padding node at this position.
+    #[must_use]
+    pub fn node(&self, layer: usize, position: u64) -> Option<TH::Domain> {
+        let LayerPadding { front, back } =
+            self.layers.get(layer.checked_sub(self.lowest_layer)?)?;
+        [front, back]
+            .into_iter()
+            .flatten()
+            .find(|(padding_position, _)| *padding_position as u64 == position)
+            .map(|(_, node)| *node)
+    }
 }
 
 /// Sub-tree of a sparse Hash-Tree based on a tweakable hashes.
@@ -109,7 +149,20 @@ pub struct HashTreeOpening<TH: TweakableHash> {
     /// The co-path needed to verify
     /// If the tree has depth h, i.e, 2^h leafs
     /// the co-path should have size D
-    co_path: Vec<TH::Domain>,
+    pub(crate) co_path: Vec<TH::Domain>,
+}
+
+/// Opening of several positions in a hash-tree at once, without the leafs.
+///
+/// The co-paths of positions that are close to each other share most of their
+/// nodes, and some of their nodes can be recomputed from the leafs. A multi-opening
+/// contains each node that is needed to verify all positions exactly once, and omits
+/// the nodes that the verifier recomputes. The nodes are ordered by layer, starting
+/// with the lowest layer, and by their position within each layer.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HashTreeMultiOpening<TH: TweakableHash> {
+    pub(crate) nodes: Vec<TH::Domain>,
 }
 
 impl<TH> HashSubTree<TH>
@@ -137,6 +190,10 @@ where
     /// Note: The RNG is used for generating nodes used for padding in the case of
     /// sparse trees. They could as well be fixed, and hence the RNG does not need
     /// to be cryptographically secure for this function.
+    ///
+    /// Note: secret keys build their trees level by level (see `new_subtree_with_padding`),
+    /// so this is only used to compare against the full tree in tests.
+    #[cfg(test)]
     pub fn new_subtree<R: Rng>(
         rng: &mut R,
         lowest_layer: usize,
@@ -155,81 +212,18 @@ where
             "Hash-Tree new: Not enough space for lowest layer nodes. Consider changing start_index or number of lowest layer nodes."
         );
 
-        // we build the tree from the lowest layer to the root,
-        // while building the tree, we ensure that the following two invariants hold via appropriate padding:
-        // 1. the layer starts at an even index, i.e., a left child
-        // 2. the layer ends at an odd index, i.e., a right child (does not hold for the root layer)
-        // In this way, we can ensure that we can always hash two siblings to get their parent
-        // The padding is ensured using the helper function `get_padded_layer`.
-
-        let mut layers = Vec::with_capacity(depth + 1 - lowest_layer);
-
-        // start with the lowest layer, padded accordingly
-        layers.push(HashTreeLayer::padded(rng, lowest_layer_nodes, start_index));
-
-        // now, build the tree layer by layer
-        for level in lowest_layer..depth {
-            // Previous layer (already padded so len is even and start_index is even)
-            let prev = &layers[level - lowest_layer];
-
-            // Parent layer starts at half the previous start index
-            let parent_start = prev.start_index >> 1;
-
-            // Compute all parents in parallel, pairing children two-by-two
-            //
-            // We do exact chunks of two children, no remainder.
-            let parents = prev
-                .nodes
-                .par_chunks_exact(2)
-                .enumerate()
-                .map(|(i, children)| {
-                    // Parent index in this layer
-                    let parent_pos = (parent_start + i) as u32;
-                    // Hash children into their parent using the tweak
-                    TH::apply(
-                        parameter,
-                        &TH::tree_tweak((level as u8) + 1, parent_pos),
-                        children,
-                    )
-                })
-                .collect();
-
-            // Add the new layer with padding so next iteration also has even start and length
-            layers.push(HashTreeLayer::padded(rng, parents, parent_start));
-        }
-
-        Self {
-            depth,
+        // we first sample the padding of all layers, and then
+        // build the tree from the lowest layer to the root.
+        let padding = SparseTreePadding::new(
+            rng,
             lowest_layer,
-            layers,
-        }
-    }
-
-    /// Function to compute a top sub-tree of a tree of even depth.
-    /// The top tree contains only the top layers, starting with layer
-    /// depth / 2, and ending with the root of the full tree, which is layer depth + 1.
-    ///
-    /// It takes as input the roots of all 2^{depth/2} bottom trees. Note that these are
-    /// exactly the nodes in layer depth / 2. The `start_index` indicates which bottom tree
-    /// is the first that is given. It be in [0, 2^{depth/2}).
-    pub fn new_top_tree<R: Rng>(
-        rng: &mut R,
-        depth: usize,
-        start_index: usize,
-        parameter: &TH::Parameter,
-        roots_of_bottom_trees: Vec<TH::Domain>,
-    ) -> Self {
-        assert!(
-            depth.is_multiple_of(2),
-            "Hash-Tree new top tree: Depth must be even."
+            depth,
+            start_index,
+            lowest_layer_nodes.len(),
         );
-
-        // the top tree is just the sub-tree that starts at layer depth / 2, and contains
-        // the roots of the bottom trees in the lowest layer.
-        let lowest_layer = depth / 2;
-        let lowest_layer_nodes = roots_of_bottom_trees;
-        Self::new_subtree(
-            rng,
+        Self::new_subtree_with_padding(
+            &padding,
+            depth,
             lowest_layer,
             depth,
             start_index,
@@ -238,60 +232,121 @@ where
         )
     }
 
-    /// Function to compute a bottom sub-tree of a tree of even depth.
-    /// This is a tree containing 2^{depth/2} leafs, which are at positions
-    /// bottom_tree_index * 2^{depth/2}, ... (bottom_tree_index + 1) * 2^{depth/2} - 1
-    pub fn new_bottom_tree(
+    /// Function to compute a sub-tree of a sparse hash-tree, which contains the layers
+    /// `lowest_layer` up to `root_layer` of the full tree of depth `depth`. The root of
+    /// the sub-tree is the ancestor of the given nodes in layer `root_layer`.
+    ///
+    /// The `lowest_layer_nodes` are all nodes below the root of the sub-tree that exist
+    /// in layer `lowest_layer` (without padding), starting at the given start index.
+    /// All padding nodes are taken from `padding`. Thereby, the sub-tree contains exactly
+    /// the nodes of the sparse tree that are below its root. This allows to split a
+    /// sparse tree into levels of sub-trees that can be computed independently, e.g.,
+    /// bottom trees, whose roots are the lowest layer nodes of a top tree.
+    ///
+    /// Unless `root_layer = depth`, the root is alone in its layer, and the path
+    /// of the sub-tree ends below the root.
+    pub fn new_subtree_with_padding(
+        padding: &SparseTreePadding<TH>,
         depth: usize,
-        bottom_tree_index: usize,
+        lowest_layer: usize,
+        root_layer: usize,
+        start_index: usize,
         parameter: &TH::Parameter,
-        leafs: Vec<TH::Domain>,
+        lowest_layer_nodes: Vec<TH::Domain>,
     ) -> Self {
         assert!(
-            depth > 2 && depth.is_multiple_of(2),
-            "Hash-Tree new bottom tree: Depth must be even and more than 2."
+            padding.lowest_layer <= lowest_layer
+                && lowest_layer < root_layer
+                && root_layer <= depth
+                && depth + 1 == padding.lowest_layer + padding.layers.len(),
+            "Hash-Tree new with padding: Layers of the sub-tree do not match the padding."
         );
-
         assert!(
-            leafs.len() == 1 << (depth / 2),
-            "Hash-Tree new bottom tree: Bottom trees must be full, not sparse."
+            !lowest_layer_nodes.is_empty(),
+            "Hash-Tree new with padding: Need at least one node in the lowest layer."
         );
 
-        // note that this bottom tree will have no padding due to the previous
-        // assert this means we can instantiate the RNG used in new_subtree with some
-        // dummy RNG, because it will never be used. More precisely, all padding nodes
-        // that will be generated in new_subtree will be removed below.
-        //
-        // Also, even if there were dummy nodes, it is not critical for security
-        // that they are generated by a good PRG, they could be fixed as well.
-        let mut dummy_rng = StdRng::seed_from_u64(0);
-
-        // we first compute the bottom tree as if it was a sparse tree, i.e.,
-        // as if we were to compute the full tree but only this bottom tree part was filled.
-        let leafs_per_bottom_tree = 1 << (depth / 2);
-        let lowest_layer = 0;
-        let lowest_layer_nodes = leafs;
-        let start_index = bottom_tree_index * leafs_per_bottom_tree;
-        let mut bottom_tree = Self::new_subtree(
-            &mut dummy_rng,
-            lowest_layer,
-            depth,
-            start_index,
-            parameter,
-            lowest_layer_nodes,
+        // the nodes of the sub-tree in each layer are within the block of positions below its root
+        let root_index = start_index >> (root_layer - lowest_layer);
+        assert!(
+            (start_index + lowest_layer_nodes.len() - 1) >> (root_layer - lowest_layer)
+                == root_index,
+            "Hash-Tree new with padding: Lowest layer nodes must be below the same root."
         );
 
-        // Now, note that the bottom_tree contains dummy nodes for the top depth/2 + 1 layers,
-        // These notes are incompatible with the other bottom trees, so we need to make sure that we remove
-        // them. We also make sure the root is alone in its layer so that the root() function works.
-        let bottom_tree_root = bottom_tree.layers[depth / 2].nodes[bottom_tree_index % 2];
-        bottom_tree.layers.truncate(depth / 2);
-        bottom_tree.layers.push(HashTreeLayer {
-            start_index: bottom_tree_index,
-            nodes: vec![bottom_tree_root],
-        });
+        let mut layers = Vec::with_capacity(root_layer + 1 - lowest_layer);
+        let mut start = start_index;
+        let mut nodes = lowest_layer_nodes;
+        for level in lowest_layer..=root_layer {
+            // add the padding nodes of this layer if they are below the root,
+            // i.e., if they are within the block of positions of this sub-tree.
+            let shift = root_layer - level;
+            let block = (root_index << shift)..((root_index + 1) << shift);
+            let LayerPadding { front, back } = &padding.layers[level - padding.lowest_layer];
+            let front = front.filter(|(position, _)| block.contains(position));
+            let back = back.filter(|(position, _)| block.contains(position));
+
+            let mut padded_nodes = Vec::with_capacity(
+                nodes.len() + (front.is_some() as usize) + (back.is_some() as usize),
+            );
+            if let Some((position, node)) = front {
+                assert!(
+                    position + 1 == start,
+                    "Hash-Tree new with padding: Padding does not match the lowest layer nodes."
+                );
+                padded_nodes.push(node);
+                start = position;
+            }
+            padded_nodes.extend_from_slice(&nodes);
+            wipe_nodes(&mut nodes);
+            if let Some((position, node)) = back {
+                assert!(
+                    position == start + padded_nodes.len(),
+                    "Hash-Tree new with padding: Padding does not match the lowest layer nodes."
+                );
+                padded_nodes.push(node);
+            }
+
+            if level == root_layer {
+                layers.push(HashTreeLayer {
+                    start_index: start,
+                    nodes: padded_nodes,
+                });
+                break;
+            }
+
+            // The layer (with padding) starts at an even index and has an even number of
+            // nodes, so that we can compute all parents in parallel (see `Parallelism`),
+            // pairing children two-by-two.
+            assert!(
+                start.is_multiple_of(2) && padded_nodes.len().is_multiple_of(2),
+                "Hash-Tree new with padding: Padding does not match the lowest layer nodes."
+            );
+            let parent_start = start >> 1;
+            let parents = map_range(0..padded_nodes.len() / 2, |i| {
+                // Parent index in this layer
+                let parent_pos = (parent_start + i) as u64;
+                // Hash children into their parent using the tweak
+                TH::apply(
+                    parameter,
+                    &TH::tree_tweak((level as u8) + 1, parent_pos),
+                    &padded_nodes[2 * i..2 * i + 2],
+                )
+            });
+
+            layers.push(HashTreeLayer {
+                start_index: start,
+                nodes: padded_nodes,
+            });
+            start = parent_start;
+            nodes = parents;
+        }
 
-        bottom_tree
+        Self {
+            depth,
+            lowest_layer,
+            layers,
+        }
     }
 
     /// Function to get a sub-tree root from a sub-tree.
@@ -304,24 +359,38 @@ where
             .nodes[0]
     }
 
+    /// Returns the position of the root of the sub-tree in its layer.
+    #[must_use]
+    pub fn root_index(&self) -> usize {
+        self.layers
+            .last()
+            .expect("Hash-Tree must have at least one layer")
+            .start_index
+    }
+
+    /// Returns the number of nodes stored in the sub-tree, including padding.
+    #[must_use]
+    pub fn num_nodes(&self) -> usize {
+        self.layers.iter().map(|layer| layer.nodes.len()).sum()
+    }
+
     /// Function to compute the Merkle authentication path
     /// from a sub-tree and the position of the node in the lowest layer.
     /// It is assumed that the tree is well-formed, i.e., each layer is half
     /// the size of the previous layer, and the final layer has
     /// size 1.
     #[must_use]
-    pub fn path(&self, position: u32) -> HashTreeOpening<TH> {
+    pub fn path(&self, position: u64) -> HashTreeOpening<TH> {
         assert!(
             !self.layers.is_empty(),
             "Hash-Tree path: Need at least one layer"
         );
         assert!(
-            (position as u64) >= (self.layers[0].start_index as u64),
+            position >= self.layers[0].start_index as u64,
             "Hash-Tree path: Invalid position, position before start index"
         );
         assert!(
-            (position as u64)
-                < (self.layers[0].start_index as u64 + self.layers[0].nodes.len() as u64),
+            position < self.layers[0].start_index as u64 + self.layers[0].nodes.len() as u64,
             "Hash-Tree path: Invalid position, position too large"
         );
 
@@ -336,7 +405,7 @@ where
             }
             // position of the sibling that we want to include
             let sibling_position = current_position ^ 0x01;
-            let sibling_position_in_vec = sibling_position - self.layers[l].start_index as u32;
+            let sibling_position_in_vec = sibling_position - self.layers[l].start_index as u64;
             // add to the co-path
             let sibling = self.layers[l].nodes[sibling_position_in_vec as usize];
             co_path.push(sibling);
@@ -346,47 +415,189 @@ where
 
         HashTreeOpening { co_path }
     }
+
+    /// Function to compute a Merkle multi-opening from a sub-tree and a list of
+    /// positions of nodes in the lowest layer, see `HashTreeMultiOpening`.
+    /// The positions may be given in any order and may contain duplicates.
+    /// As for `path`, the tree is assumed to be well-formed.
+    ///
+    /// Note: secret keys split their tree into levels of sub-trees, so they use
+    /// `combined_multi_path` instead, and this is only used in tests.
+    #[cfg(test)]
+    #[must_use]
+    pub fn multi_path(&self, positions: &[u64]) -> HashTreeMultiOpening<TH> {
+        assert!(
+            !self.layers.is_empty(),
+            "Hash-Tree multi path: Need at least one layer"
+        );
+        let root_layer = self.lowest_layer + self.layers.len() - 1;
+        multi_path_in_layers(&[self], self.lowest_layer, root_layer, positions)
+    }
+
+    /// Returns the node at the given position in the given layer of the full tree,
+    /// or `None` if the sub-tree does not contain it.
+    #[must_use]
+    pub fn node(&self, layer: usize, position: u64) -> Option<&TH::Domain> {
+        let layer = self.layers.get(layer.checked_sub(self.lowest_layer)?)?;
+        let index = position.checked_sub(layer.start_index as u64)?;
+        layer.nodes.get(usize::try_from(index).ok()?)
+    }
+
+    /// Returns the address and length in bytes of the buffer of each layer,
+    /// including its spare capacity.
+    #[cfg(test)]
+    pub(crate) fn node_buffers(&self) -> Vec<(*const u8, usize)> {
+        self.layers
+            .iter()
+            .map(|layer| {
+                (
+                    layer.nodes.as_ptr().cast(),
+                    layer.nodes.capacity() * core::mem::size_of::<TH::Domain>(),
+                )
+            })
+            .collect()
+    }
 }
 
+impl<TH: TweakableHash> Drop for HashSubTree<TH> {
+    fn drop(&mut self) {
+        // The nodes of bottom trees are derived from the PRF key, so we overwrite
+        // all nodes before the memory is freed.
+        for layer in &mut self.layers {
+            wipe_nodes(&mut layer.nodes);
+        }
+    }
+}
+
+/// Overwrites the whole buffer of `nodes` with zeros, including its spare capacity,
+/// and leaves it empty. The writes are volatile, so that the compiler does not remove
+/// them, even though the buffer is usually freed right afterwards.
+pub fn wipe_nodes<T: Copy>(nodes: &mut Vec<T>) {
+    // nodes are `Copy`, so nothing is dropped here
+    nodes.clear();
+    nodes.spare_capacity_mut().zeroize();
+}
+
+impl<TH: TweakableHash> ZeroizeOnDrop for HashSubTree<TH> {}
+
 /// Function to compute a Merkle authentication path from a tree that is
-/// splitted into top tree and bottom trees.
+/// splitted into levels of sub-trees, e.g., a top tree and bottom trees.
+///
+/// The sub-trees are given from the bottom to the top, one per level, such that
+/// the root of each sub-tree is a node in the lowest layer of the next one. The
+/// position is the position of the leaf in the full tree.
 pub fn combined_path<TH: TweakableHash>(
-    top_tree: &HashSubTree<TH>,
-    bottom_tree: &HashSubTree<TH>,
-    position: u32,
+    subtrees: &[&HashSubTree<TH>],
+    position: u64,
 ) -> HashTreeOpening<TH> {
     assert!(
-        bottom_tree.depth == top_tree.depth,
-        "Hash-Tree combined path: Bottom tree and top tree must have the same depth."
+        !subtrees.is_empty(),
+        "Hash-Tree combined path: Need at least one sub-tree."
+    );
+    assert!(
+        subtrees[0].lowest_layer == 0,
+        "Hash-Tree combined path: Lowest sub-tree must contain the leafs."
     );
+    for pair in subtrees.windows(2) {
+        assert!(
+            pair[0].depth == pair[1].depth,
+            "Hash-Tree combined path: Sub-trees must have the same depth."
+        );
+        assert!(
+            pair[0].lowest_layer + pair[0].layers.len() - 1 == pair[1].lowest_layer,
+            "Hash-Tree combined path: Root of each sub-tree must be in the lowest layer of the next one."
+        );
+    }
+
+    // Note: other asserts are in path.
+
+    // We compute the path in each sub-tree, starting with the lowest one. Each path
+    // authenticates the root of the sub-tree below, which is the ancestor of the
+    // leaf in the lowest layer of the sub-tree. Paths of sub-trees (except the top one)
+    // end below their root, so we can just concatenate them.
+    let co_path = subtrees
+        .iter()
+        .flat_map(|subtree| subtree.path(position >> subtree.lowest_layer).co_path)
+        .collect();
 
+    HashTreeOpening { co_path }
+}
+
+/// Function to compute a Merkle multi-opening from a tree that is splitted into
+/// levels of sub-trees, see `combined_path` and `HashTreeMultiOpening`.
+///
+/// In contrast to `combined_path`, there can be several sub-trees per level, and
+/// the sub-trees can be given in any order. Each node of the multi-opening must be
+/// contained in one of them. The positions are positions of leafs in the full tree.
+pub fn combined_multi_path<TH: TweakableHash>(
+    subtrees: &[&HashSubTree<TH>],
+    positions: &[u64],
+) -> HashTreeMultiOpening<TH> {
     assert!(
-        bottom_tree.depth.is_multiple_of(2),
-        "Hash-Tree combined path: Tree depth must be even."
+        !subtrees.is_empty(),
+        "Hash-Tree combined multi path: Need at least one sub-tree."
     );
-    let depth = bottom_tree.depth;
+    let depth = subtrees[0].depth;
     assert!(
-        bottom_tree.layers[0]
-            .start_index
-            .is_multiple_of(1 << (depth / 2)),
-        "Hash-Tree combined path: Bottom tree start index must be multiple of 1 << depth/2."
+        subtrees.iter().all(|subtree| subtree.depth == depth),
+        "Hash-Tree combined multi path: Sub-trees must have the same depth."
     );
-    let bottom_tree_index = bottom_tree.layers[0].start_index / (1 << (depth / 2));
+    multi_path_in_layers(subtrees, 0, depth, positions)
+}
 
-    // Note: other asserts are in path.
+/// Helper function to compute a Merkle multi-opening for the given positions in
+/// layer `lowest_layer`, which authenticates them with respect to their ancestor
+/// in layer `root_layer`. The nodes are taken from the given sub-trees.
+fn multi_path_in_layers<TH: TweakableHash>(
+    subtrees: &[&HashSubTree<TH>],
+    lowest_layer: usize,
+    root_layer: usize,
+    positions: &[u64],
+) -> HashTreeMultiOpening<TH> {
+    assert!(
+        !positions.is_empty(),
+        "Hash-Tree multi path: Need at least one position"
+    );
 
-    // First, we compute the path for the bottom tree. Note that this contains
-    // dummy elements in the top layers. We will remove them below.
-    let bottom_opening = bottom_tree.path(position);
+    let mut current_positions = positions.to_vec();
+    current_positions.sort_unstable();
+    current_positions.dedup();
 
-    // Now, we compute the path for the top tree. Intuitively, this authenticates
-    // the root of the bottom tree.
-    let top_opening = top_tree.path(bottom_tree_index as u32);
+    let node = |layer: usize, position: u64| {
+        *subtrees
+            .iter()
+            .find_map(|subtree| subtree.node(layer, position))
+            .expect("Hash-Tree multi path: Invalid position, node is not in the sub-trees")
+    };
 
-    // Finally, we combine them.
-    let co_path = [bottom_opening.co_path, top_opening.co_path].concat();
+    // the positions themselves must exist in the tree
+    for &position in &current_positions {
+        node(lowest_layer, position);
+    }
 
-    HashTreeOpening { co_path }
+    // in each layer, we include the siblings of all nodes that are not recomputed by
+    // the verifier, i.e., that are not in the current list of positions.
+    let mut nodes = Vec::new();
+    for layer in lowest_layer..root_layer {
+        let mut i = 0;
+        while i < current_positions.len() {
+            let position = current_positions[i];
+            if position.is_multiple_of(2) && current_positions.get(i + 1) == Some(&(position + 1)) {
+                // both children are known, so the sibling is not needed
+                i += 2;
+            } else {
+                nodes.push(node(layer, position ^ 0x01));
+                i += 1;
+            }
+        }
+        // new positions in next layer
+        for position in &mut current_positions {
+            *position >>= 1;
+        }
+        current_positions.dedup();
+    }
+
+    HashTreeMultiOpening { nodes }
 }
 
 /// Function to verify an Merkle authentication path
@@ -398,29 +609,43 @@ pub fn combined_path<TH: TweakableHash>(
 pub fn hash_tree_verify<TH: TweakableHash>(
     parameter: &TH::Parameter,
     root: &TH::Domain,
-    position: u32,
+    position: u64,
     leaf: &[TH::Domain],
     opening: &HashTreeOpening<TH>,
+) -> bool {
+    hash_tree_verify_with_tweaks(parameter, root, position, leaf, opening, TH::tree_tweak)
+}
+
+/// Same as `hash_tree_verify`, but the tweaks are given by the caller, e.g., if they
+/// have been precomputed. The tweak of a node is `tree_tweak(level, position)`, where
+/// the leaf is hashed into the node in level 0.
+pub fn hash_tree_verify_with_tweaks<TH: TweakableHash, T: Borrow<TH::Tweak>>(
+    parameter: &TH::Parameter,
+    root: &TH::Domain,
+    position: u64,
+    leaf: &[TH::Domain],
+    opening: &HashTreeOpening<TH>,
+    tree_tweak: impl Fn(u8, u64) -> T,
 ) -> bool {
     // given the length of the path, we know how
     // large the tree was. So we can check if the
     // position makes sense.
     let depth = opening.co_path.len();
-    let num_leafs: u64 = 1 << depth;
 
     assert!(
-        depth <= 32,
-        "Hash-Tree verify: Tree depth must be at most 32"
+        depth <= 63,
+        "Hash-Tree verify: Tree depth must be at most 63"
     );
+    let num_leafs: u64 = 1 << depth;
 
     assert!(
-        (position as u64) < num_leafs,
+        position < num_leafs,
         "Hash-Tree verify: Position and Path Length not compatible"
     );
 
     // first hash the leaf to get the node in the bottom layer
-    let tweak = TH::tree_tweak(0, position);
-    let mut current_node = TH::apply(parameter, &tweak, leaf);
+    let tweak = tree_tweak(0, position);
+    let mut current_node = TH::apply(parameter, tweak.borrow(), leaf);
 
     // now reconstruct the root using the co-path
     let mut current_position = position;
@@ -439,19 +664,109 @@ pub fn hash_tree_verify<TH: TweakableHash>(
         current_position >>= 1;
 
         // now hash to get the parent
-        let tweak = TH::tree_tweak((l + 1) as u8, current_position);
-        current_node = TH::apply(parameter, &tweak, &children);
+        let tweak = tree_tweak((l + 1) as u8, current_position);
+        current_node = TH::apply(parameter, tweak.borrow(), &children);
     }
 
     // Finally, check that recomputed root matches given root
     current_node == *root
 }
 
+/// Function to verify a Merkle multi-opening with respect to a root, a list of
+/// positions, and the leafs at these positions. The positions must be strictly
+/// increasing, and the tree has depth `depth`.
+///
+/// The opening may come from an untrusted source: if it contains too few or too
+/// many nodes, the function returns false.
+///
+/// Note: as for `hash_tree_verify`, each leaf is a list of hashes.
+pub fn hash_tree_verify_multi<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    root: &TH::Domain,
+    depth: usize,
+    positions: &[u64],
+    leafs: &[Vec<TH::Domain>],
+    opening: &HashTreeMultiOpening<TH>,
+) -> bool {
+    assert!(
+        depth <= 63,
+        "Hash-Tree verify multi: Tree depth must be at most 63"
+    );
+    assert!(
+        !positions.is_empty() && positions.len() == leafs.len(),
+        "Hash-Tree verify multi: Need one leaf per position, and at least one position"
+    );
+    assert!(
+        positions.windows(2).all(|pair| pair[0] < pair[1]),
+        "Hash-Tree verify multi: Positions must be strictly increasing"
+    );
+    assert!(
+        positions[positions.len() - 1] < 1 << depth,
+        "Hash-Tree verify multi: Position and depth not compatible"
+    );
+
+    // first hash the leafs to get the nodes in the bottom layer
+    let mut current_nodes: Vec<(u64, TH::Domain)> = positions
+        .iter()
+        .zip(leafs)
+        .map(|(&position, leaf)| {
+            let tweak = TH::tree_tweak(0, position);
+            (position, TH::apply(parameter, &tweak, leaf))
+        })
+        .collect();
+
+    // now reconstruct the root layer by layer, taking the siblings that
+    // we cannot recompute from the opening, in the order in which they are needed
+    let mut opening_nodes = opening.nodes.iter();
+    for l in 0..depth {
+        let mut parents = Vec::with_capacity(current_nodes.len().div_ceil(2));
+        let mut i = 0;
+        while i < current_nodes.len() {
+            let (position, node) = current_nodes[i];
+            let children = match current_nodes.get(i + 1) {
+                // left child, and we know the right sibling
+                Some(&(sibling_position, sibling))
+                    if position.is_multiple_of(2) && sibling_position == position + 1 =>
+                {
+                    i += 1;
+                    [node, sibling]
+                }
+                _ => {
+                    let Some(&sibling) = opening_nodes.next() else {
+                        return false;
+                    };
+                    if position.is_multiple_of(2) {
+                        [node, sibling]
+                    } else {
+                        [sibling, node]
+                    }
+                }
+            };
+            i += 1;
+
+            // now hash to get the parent
+            let parent_position = position >> 1;
+            let tweak = TH::tree_tweak((l + 1) as u8, parent_position);
+            parents.push((parent_position, TH::apply(parameter, &tweak, &children)));
+        }
+        current_nodes = parents;
+    }
+
+    // Finally, all nodes of the opening must have been used, and the
+    // recomputed root must match the given root
+    opening_nodes.next().is_none() && current_nodes[0].1 == *root
+}
+
 #[cfg(test)]
 mod tests {
 
+    use alloc::collections::BTreeSet;
+
     use proptest::prelude::*;
 
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
     use crate::symmetric::tweak_hash::sha::ShaTweak128192;
 
     use super::*;
@@ -487,7 +802,7 @@ mod tests {
             .map(|(i, v)| {
                 TestTH::apply(
                     &parameter,
-                    &TestTH::tree_tweak(0, (i + start_index) as u32),
+                    &TestTH::tree_tweak(0, (i + start_index) as u64),
                     v.as_slice(),
                 )
             })
@@ -509,7 +824,7 @@ mod tests {
         // now check that opening and verification works as expected
         for (offset, leaf) in leafs.iter().enumerate().take(num_leafs) {
             // calculate the position
-            let position = start_index as u32 + offset as u32;
+            let position = (start_index + offset) as u64;
             // first get the opening
             let path = tree.path(position);
             // now assert that it verifies
@@ -589,35 +904,236 @@ mod tests {
         }
     }
 
+    /// We test that a multi-opening for the leafs at the given offsets from the start
+    /// index verifies, and that it contains exactly the nodes of their authentication
+    /// paths that cannot be recomputed from the leafs, each of them once.
+    /// We also test that the multi-opening is rejected if it is modified.
+    fn test_multi_open_helper(
+        num_leafs: usize,
+        depth: usize,
+        start_index: usize,
+        offsets: &[usize],
+    ) {
+        let mut rng = rand::rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+
+        let leafs: Vec<Vec<_>> = (0..num_leafs)
+            .map(|_| (0..2).map(|_| TestTH::rand_domain(&mut rng)).collect())
+            .collect();
+        let leafs_hashes: Vec<_> = leafs
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                TestTH::apply(
+                    &parameter,
+                    &TestTH::tree_tweak(0, (i + start_index) as u64),
+                    v.as_slice(),
+                )
+            })
+            .collect();
+        let tree = HashSubTree::<TestTH>::new_subtree(
+            &mut rng,
+            0,
+            depth,
+            start_index,
+            &parameter,
+            leafs_hashes,
+        );
+        let root = tree.root();
+
+        // the multi-opening accepts any order and duplicates
+        let positions: Vec<u64> = offsets
+            .iter()
+            .map(|offset| (start_index + offset) as u64)
+            .collect();
+        let opening = tree.multi_path(&positions);
+
+        // the verifier gets the positions sorted
+        let mut positions = positions;
+        positions.sort_unstable();
+        positions.dedup();
+        let mut leafs: Vec<Vec<_>> = positions
+            .iter()
+            .map(|&position| leafs[position as usize - start_index].clone())
+            .collect();
+        assert!(hash_tree_verify_multi(
+            &parameter, &root, depth, &positions, &leafs, &opening
+        ));
+
+        // compare with the individual authentication paths
+        let mut co_path_nodes = BTreeSet::new();
+        let mut recomputed_nodes = BTreeSet::new();
+        for (&position, leaf) in positions.iter().zip(&leafs) {
+            let path = tree.path(position);
+            assert!(hash_tree_verify(&parameter, &root, position, leaf, &path));
+            for (layer, sibling) in path.co_path.iter().enumerate() {
+                let sibling_position = (position >> layer) ^ 0x01;
+                assert!(tree.node(layer, sibling_position) == Some(sibling));
+                co_path_nodes.insert((layer, sibling_position));
+                recomputed_nodes.insert((layer, position >> layer));
+            }
+        }
+        let expected_nodes: Vec<_> = co_path_nodes
+            .difference(&recomputed_nodes)
+            .map(|&(layer, position)| *tree.node(layer, position).unwrap())
+            .collect();
+        assert!(opening.nodes == expected_nodes);
+        assert!(opening.nodes.len() <= positions.len() * depth);
+
+        // the opening is rejected if nodes are missing, added, or modified
+        let mut opening = opening;
+        opening.nodes.push(TestTH::rand_domain(&mut rng));
+        assert!(!hash_tree_verify_multi(
+            &parameter, &root, depth, &positions, &leafs, &opening
+        ));
+        opening.nodes.pop();
+        if let Some(node) = opening.nodes.pop() {
+            assert!(!hash_tree_verify_multi(
+                &parameter, &root, depth, &positions, &leafs, &opening
+            ));
+            opening.nodes.push(TestTH::rand_domain(&mut rng));
+            assert!(!hash_tree_verify_multi(
+                &parameter, &root, depth, &positions, &leafs, &opening
+            ));
+            opening.nodes.pop();
+            opening.nodes.push(node);
+        }
+
+        // it is also rejected for other leafs
+        leafs[0][0] = TestTH::rand_domain(&mut rng);
+        assert!(!hash_tree_verify_multi(
+            &parameter, &root, depth, &positions, &leafs, &opening
+        ));
+    }
+
+    #[test]
+    fn test_multi_open_verify_consecutive() {
+        // consecutive positions share most of their co-paths
+        let offsets: Vec<usize> = (100..116).collect();
+        test_multi_open_helper(1024, 10, 0, &offsets);
+    }
+
+    #[test]
+    fn test_multi_open_verify_single() {
+        test_multi_open_helper(213, 10, 217, &[17]);
+    }
+
+    #[test]
+    fn test_multi_open_verify_all_leafs() {
+        // all nodes are recomputed, except for the padding
+        let offsets: Vec<usize> = (0..213).collect();
+        test_multi_open_helper(213, 10, 217, &offsets);
+    }
+
+    #[test]
+    fn test_multi_open_verify_unordered_duplicates() {
+        test_multi_open_helper(64, 6, 0, &[63, 0, 17, 17, 16, 0]);
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_multi_open_verify(
+            num_leafs in 1usize..32,
+            depth in 3usize..7,
+            start_index in 0usize..64,
+
+            // Random sets of positions, given as offsets modulo the number of leafs
+            offsets in proptest::collection::vec(0usize..32, 1..12),
+        ) {
+            prop_assume!(start_index + num_leafs <= 1 << depth);
+
+            let offsets: Vec<usize> = offsets.iter().map(|offset| offset % num_leafs).collect();
+            test_multi_open_helper(num_leafs, depth, start_index, &offsets);
+        }
+    }
+
+    #[test]
+    fn test_nodes_wiped_on_drop() {
+        let mut rng = rand::rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let leafs_hashes: Vec<_> = (0..8).map(|_| TestTH::rand_domain(&mut rng)).collect();
+        let tree = HashSubTree::<TestTH>::new_subtree(&mut rng, 0, 4, 4, &parameter, leafs_hashes);
+
+        let buffers = tree.node_buffers();
+        assert_eq!(buffers.len(), 5);
+        let wiped = crate::test_allocator::wiped_on_free(&buffers, || drop(tree));
+        assert!(wiped.iter().all(|wiped| *wiped == Some(true)));
+    }
+
+    #[test]
+    fn test_intermediate_nodes_wiped() {
+        let mut rng = rand::rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let leafs_hashes: Vec<_> = (0..8).map(|_| TestTH::rand_domain(&mut rng)).collect();
+
+        // the buffer of the lowest layer is copied into the tree and then freed
+        let buffer = (
+            leafs_hashes.as_ptr().cast(),
+            core::mem::size_of_val(leafs_hashes.as_slice()),
+        );
+        let wiped = crate::test_allocator::wiped_on_free(&[buffer], || {
+            drop(HashSubTree::<TestTH>::new_subtree(
+                &mut rng,
+                0,
+                4,
+                4,
+                &parameter,
+                leafs_hashes,
+            ));
+        });
+        assert_eq!(wiped, [Some(true)]);
+    }
+
+    #[test]
+    fn test_wipe_nodes_spare_capacity() {
+        let mut rng = rand::rng();
+        let mut nodes: Vec<_> = Vec::with_capacity(16);
+        nodes.extend((0..16).map(|_| TestTH::rand_domain(&mut rng)));
+        // the removed nodes remain in the spare capacity of the buffer
+        nodes.truncate(4);
+
+        let buffer = (
+            nodes.as_ptr().cast(),
+            nodes.capacity() * core::mem::size_of::<<TestTH as TweakableHash>::Domain>(),
+        );
+        let wiped = crate::test_allocator::wiped_on_free(&[buffer], || {
+            wipe_nodes(&mut nodes);
+            drop(nodes);
+        });
+        assert_eq!(wiped, [Some(true)]);
+    }
+
     /// We test that the following honest procedure succeeds:
     /// (1) build the Merkle tree to get the root,
     /// (2) build an authentication path for the leaf,
     /// (3) verify the authentication path with respect to leaf and root
     ///
-    /// This is tested for the approach that first builds all bottom trees,
-    /// and then the top tree on top of that. And it computes the Merkle
-    /// authentication paths using the combined path function.
-    fn test_commit_open_helper_top_bottom(
-        num_bottom_trees: usize,
-        depth: usize,
-        start_bottom_tree_index: usize,
+    /// This is tested for the approach that splits the tree into levels of
+    /// sub-trees, where level j contains the layers `boundaries[j]` to
+    /// `boundaries[j + 1]`. It first builds all sub-trees of the lowest level,
+    /// and then the sub-trees of each level on top of the roots of the level
+    /// below. It computes the Merkle authentication paths using the combined
+    /// path function. We also test that the root is the same as for the flat tree.
+    fn test_commit_open_helper_levels(
+        boundaries: &[usize],
+        num_leafs: usize,
+        start_index: usize,
         leaf_len: usize,
     ) {
+        let depth = *boundaries.last().unwrap();
+        assert_eq!(boundaries[0], 0);
+
         // sample a random parameter and leafs
         let mut rng = rand::rng();
         let parameter = TestTH::rand_parameter(&mut rng);
 
-        let leafs_per_bottom_tree = 1 << (depth / 2);
-        let num_leafs = num_bottom_trees * leafs_per_bottom_tree;
-        let start_index = start_bottom_tree_index * leafs_per_bottom_tree;
-        let mut leafs = Vec::new();
-        for _ in 0..num_leafs {
-            let mut leaf = Vec::new();
-            for _ in 0..leaf_len {
-                leaf.push(TestTH::rand_domain(&mut rng));
-            }
-            leafs.push(leaf);
-        }
+        let leafs: Vec<Vec<_>> = (0..num_leafs)
+            .map(|_| {
+                (0..leaf_len)
+                    .map(|_| TestTH::rand_domain(&mut rng))
+                    .collect()
+            })
+            .collect();
 
         let leafs_hashes: Vec<_> = leafs
             .iter()
@@ -625,61 +1141,121 @@ mod tests {
             .map(|(i, v)| {
                 TestTH::apply(
                     &parameter,
-                    &TestTH::tree_tweak(0, (i + start_index) as u32),
+                    &TestTH::tree_tweak(0, (i + start_index) as u64),
                     v.as_slice(),
                 )
             })
             .collect();
 
-        // Now, we build the hash tree. To this end, we first build all bottom trees.
-        let mut bottom_trees = Vec::with_capacity(num_bottom_trees);
-        let mut roots_of_bottom_trees = Vec::with_capacity(num_bottom_trees);
-        for bottom_tree_index in
-            start_bottom_tree_index..(start_bottom_tree_index + num_bottom_trees)
-        {
-            // compute a bottom tree, which is for 1 << depth/2 many leafs
-            let leafs_start = (bottom_tree_index - start_bottom_tree_index) * leafs_per_bottom_tree;
-            let leafs_end = leafs_start + leafs_per_bottom_tree;
-            let bottom_tree = HashSubTree::<TestTH>::new_bottom_tree(
-                depth,
-                bottom_tree_index,
-                &parameter,
-                leafs_hashes[leafs_start..leafs_end].to_vec(),
-            );
-            roots_of_bottom_trees.push(bottom_tree.root());
-            bottom_trees.push(bottom_tree);
-        }
-        // We now build the top tree using the roots of the bottom trees
-        let top_tree = HashSubTree::<TestTH>::new_top_tree(
-            &mut rng,
+        // The padding is sampled with a seeded RNG, so that we can compare with the flat tree.
+        let padding_seed: [u8; 32] = rng.random();
+        let padding = SparseTreePadding::<TestTH>::new(
+            &mut ChaCha20Rng::from_seed(padding_seed),
+            0,
             depth,
-            start_bottom_tree_index,
-            &parameter,
-            roots_of_bottom_trees,
+            start_index,
+            num_leafs,
         );
 
-        // now compute a commitment, i.e., Merkle root of the top tree
-        let root = top_tree.root();
-
-        // now check that opening and verification works as expected. We iterate over each bottom tree, and
-        // over each leaf in that bottom tree. Then, we compute an authentication path and verify it.
-        for bottom_tree_index in
-            start_bottom_tree_index..(start_bottom_tree_index + num_bottom_trees)
-        {
-            let leafs_start = (bottom_tree_index - start_bottom_tree_index) * leafs_per_bottom_tree;
-            let bottom_tree = &bottom_trees[bottom_tree_index - start_bottom_tree_index];
-
-            for l in 0..leafs_per_bottom_tree {
-                // calculate the position and get the leaf
-                let offset = leafs_start + l;
-                let leaf = leafs[offset].clone();
-                let position = start_index as u32 + offset as u32;
-                // compute the path using the combined_path function
-                let path = combined_path(&top_tree, bottom_tree, position);
-                // assert that the path verifies
-                assert!(hash_tree_verify(&parameter, &root, position, &leaf, &path));
+        // Now, we build the hash tree level by level. For each level, we split the nodes
+        // into groups below the same sub-tree root, and build one sub-tree per group.
+        let mut levels: Vec<Vec<HashSubTree<TestTH>>> = Vec::with_capacity(boundaries.len() - 1);
+        let mut nodes = leafs_hashes.clone();
+        let mut start = start_index;
+        for window in boundaries.windows(2) {
+            let (lowest_layer, root_layer) = (window[0], window[1]);
+            let shift = root_layer - lowest_layer;
+            let mut subtrees = Vec::new();
+            let mut offset = 0;
+            while offset < nodes.len() {
+                let group_start = start + offset;
+                let group_end = (((group_start >> shift) + 1) << shift).min(start + nodes.len());
+                subtrees.push(HashSubTree::<TestTH>::new_subtree_with_padding(
+                    &padding,
+                    depth,
+                    lowest_layer,
+                    root_layer,
+                    group_start,
+                    &parameter,
+                    nodes[offset..group_end - start].to_vec(),
+                ));
+                offset = group_end - start;
             }
+            nodes = subtrees.iter().map(HashSubTree::root).collect();
+            start >>= shift;
+            levels.push(subtrees);
         }
+        assert_eq!(levels.last().unwrap().len(), 1);
+
+        // now compute a commitment, i.e., Merkle root of the top tree, and compare it
+        // with the root of the flat tree, which uses the same padding
+        let root = levels.last().unwrap()[0].root();
+        let flat_tree = HashSubTree::<TestTH>::new_subtree(
+            &mut ChaCha20Rng::from_seed(padding_seed),
+            0,
+            depth,
+            start_index,
+            &parameter,
+            leafs_hashes,
+        );
+        assert!(root == flat_tree.root());
+
+        // now check that opening and verification works as expected. For each leaf,
+        // we collect the sub-trees containing it and compute an authentication path.
+        for (offset, leaf) in leafs.iter().enumerate() {
+            let position = start_index + offset;
+            let subtrees: Vec<_> = boundaries[1..]
+                .iter()
+                .zip(&levels)
+                .map(|(&root_layer, subtrees)| {
+                    // sub-trees of a level are ordered by the index of their root
+                    let first_root_index = subtrees[0].layers.last().unwrap().start_index;
+                    &subtrees[(position >> root_layer) - first_root_index]
+                })
+                .collect();
+            // compute the path using the combined_path function
+            let path = combined_path(&subtrees, position as u64);
+            // the path must be the same as for the flat tree
+            assert!(path.co_path == flat_tree.path(position as u64).co_path);
+            // assert that the path verifies
+            assert!(hash_tree_verify(
+                &parameter,
+                &root,
+                position as u64,
+                leaf,
+                &path
+            ));
+        }
+
+        // the multi-opening for every third leaf, computed from all sub-trees of all
+        // levels, must be the same as for the flat tree, and it must verify
+        let positions: Vec<u64> = (start_index..start_index + num_leafs)
+            .step_by(3)
+            .map(|position| position as u64)
+            .collect();
+        let all_subtrees: Vec<_> = levels.iter().flatten().collect();
+        let opening = combined_multi_path(&all_subtrees, &positions);
+        assert!(opening.nodes == flat_tree.multi_path(&positions).nodes);
+        let leafs: Vec<_> = leafs.iter().step_by(3).cloned().collect();
+        assert!(hash_tree_verify_multi(
+            &parameter, &root, depth, &positions, &leafs, &opening
+        ));
+    }
+
+    /// Tests building a tree that is split into a top tree and bottom trees.
+    fn test_commit_open_helper_top_bottom(
+        num_bottom_trees: usize,
+        depth: usize,
+        start_bottom_tree_index: usize,
+        leaf_len: usize,
+    ) {
+        let leafs_per_bottom_tree = 1 << (depth / 2);
+        test_commit_open_helper_levels(
+            &[0, depth / 2, depth],
+            num_bottom_trees * leafs_per_bottom_tree,
+            start_bottom_tree_index * leafs_per_bottom_tree,
+            leaf_len,
+        );
     }
 
     #[test]
@@ -737,4 +1313,44 @@ mod tests {
             leaf_len,
         );
     }
+
+    #[test]
+    fn test_commit_open_verify_full_tree_three_levels() {
+        test_commit_open_helper_levels(&[0, 2, 4, 6], 64, 0, 3);
+    }
+
+    #[test]
+    fn test_commit_open_verify_sparse_tree_four_levels() {
+        test_commit_open_helper_levels(&[0, 2, 4, 6, 8], 52, 44, 3);
+    }
+
+    #[test]
+    fn test_commit_open_verify_sparse_non_aligned_levels() {
+        test_commit_open_helper_levels(&[0, 3, 5, 9], 213, 217, 2);
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_commit_open_verify_levels(
+            // Depth of each level, there are 1 to 4 levels
+            level_depths in proptest::collection::vec(1usize..4, 1..5),
+
+            // Fractions of the leafs that exist, sparse and not aligned to sub-trees
+            start_fraction in 0.0f64..1.0,
+            length_fraction in 0.0f64..1.0,
+        ) {
+            let boundaries: Vec<usize> = core::iter::once(0)
+                .chain(level_depths.iter().scan(0, |layer, depth| {
+                    *layer += depth;
+                    Some(*layer)
+                }))
+                .collect();
+            let num_slots = 1usize << boundaries.last().unwrap();
+            let start_index = (start_fraction * num_slots as f64) as usize;
+            let num_leafs = 1 + (length_fraction * (num_slots - start_index) as f64) as usize;
+            let num_leafs = num_leafs.min(num_slots - start_index);
+
+            test_commit_open_helper_levels(&boundaries, num_leafs, start_index, 2);
+        }
+    }
 }
@@ -0,0 +1,117 @@
+//! Global allocator for tests, which checks whether memory has been wiped before it is freed.
+//!
+//! It forwards all calls to the system allocator. In addition, a test can watch byte ranges
+//! within allocations via `wiped_on_free`. When an allocation containing a watched range
+//! is freed, the allocator records whether the range only contains zeros at that point.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Maximum number of byte ranges that can be watched at the same time.
+const MAX_WATCHED: usize = 64;
+
+/// A watched byte range, given by its address and length, and whether it
+/// was all zeros when freed (or `None` if it has not been freed yet).
+#[derive(Clone, Copy)]
+struct Watched {
+    start: usize,
+    len: usize,
+    wiped: Option<bool>,
+}
+
+thread_local! {
+    // Ranges are watched per thread, so that tests running in parallel do not interfere.
+    // The storage is fixed-size, as the allocator must not allocate itself.
+    static WATCHED: [Cell<Option<Watched>>; MAX_WATCHED] =
+        const { [const { Cell::new(None) }; MAX_WATCHED] };
+}
+
+struct WipeCheckingAllocator;
+
+unsafe impl GlobalAlloc for WipeCheckingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let allocation = ptr as usize..ptr as usize + layout.size();
+        // accessing the thread local fails if the thread is shutting down, then nothing is watched
+        let _ = WATCHED.try_with(|watched| {
+            for slot in watched {
+                let Some(mut range) = slot.get() else {
+                    continue;
+                };
+                if allocation.contains(&range.start) && range.start + range.len <= allocation.end {
+                    // the range is inside the allocation, which is still valid here
+                    let bytes =
+                        unsafe { std::slice::from_raw_parts(range.start as *const u8, range.len) };
+                    range.wiped = Some(bytes.iter().all(|&byte| byte == 0));
+                    slot.set(Some(range));
+                }
+            }
+        });
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: WipeCheckingAllocator = WipeCheckingAllocator;
+
+/// Watches the given byte ranges (address and length) while running `f`. Returns, for each
+/// range, whether it only contained zeros when its allocation was freed, or `None` if the
+/// allocation was not freed while running `f`.
+///
+/// Ranges must be within heap allocations, and must not be empty.
+pub fn wiped_on_free(ranges: &[(*const u8, usize)], f: impl FnOnce()) -> Vec<Option<bool>> {
+    assert!(ranges.len() <= MAX_WATCHED, "Too many watched ranges");
+
+    WATCHED.with(|watched| {
+        for (slot, &(start, len)) in watched.iter().zip(ranges) {
+            assert!(len > 0, "Watched ranges must not be empty");
+            slot.set(Some(Watched {
+                start: start as usize,
+                len,
+                wiped: None,
+            }));
+        }
+    });
+
+    f();
+
+    WATCHED.with(|watched| {
+        watched[..ranges.len()]
+            .iter()
+            .map(|slot| slot.take().and_then(|range| range.wiped))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiped_on_free() {
+        let wiped = vec![0u8; 64];
+        let not_wiped = vec![1u8; 64];
+        let kept = vec![0u8; 64];
+
+        let result = wiped_on_free(
+            &[
+                (wiped.as_ptr(), wiped.len()),
+                (not_wiped.as_ptr(), not_wiped.len()),
+                (kept.as_ptr(), kept.len()),
+                // a range within an allocation
+                (not_wiped[8..].as_ptr(), 8),
+            ],
+            || {
+                drop(wiped);
+                drop(not_wiped);
+            },
+        );
+        assert_eq!(result, [Some(true), Some(false), None, Some(false)]);
+        drop(kept);
+    }
+}